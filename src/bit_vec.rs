@@ -1,5 +1,6 @@
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
+use std::cmp;
 use std::mem;
 use std::ops::{Index, Range};
 use std::slice;
@@ -278,6 +279,25 @@ impl BitVec {
     pub fn count_zeros(&self) -> usize {
         self.len - self.one_count
     }
+
+    pub fn count_ones_in_range(&self, range: Range<usize>) -> usize {
+        assert!(range.end <= self.len);
+        let aligned_start = cmp::min(
+            (range.start + BLOCK_BIT_COUNT - 1) / BLOCK_BIT_COUNT * BLOCK_BIT_COUNT,
+            range.end,
+        );
+        let aligned_end = cmp::max(range.end / BLOCK_BIT_COUNT * BLOCK_BIT_COUNT, aligned_start);
+        let block_one_count: usize = self.blocks
+            [aligned_start / BLOCK_BIT_COUNT..aligned_end / BLOCK_BIT_COUNT]
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum();
+        let bit_one_count = (range.start..aligned_start)
+            .chain(aligned_end..range.end)
+            .filter(|index| self[*index])
+            .count();
+        block_one_count + bit_one_count
+    }
 }
 
 impl Clone for BitVec {
@@ -641,6 +661,20 @@ mod tests {
         assert_eq!(bv.len(), 1);
     }
 
+    #[test]
+    fn test_count_ones_in_range() {
+        let mut bv = BitVec::new(20);
+        bv.set(1, true);
+        bv.set(8, true);
+        bv.set(12, true);
+        bv.set(19, true);
+
+        assert_eq!(bv.count_ones_in_range(0..20), 4);
+        assert_eq!(bv.count_ones_in_range(2..19), 2);
+        assert_eq!(bv.count_ones_in_range(8..16), 2);
+        assert_eq!(bv.count_ones_in_range(5..5), 0);
+    }
+
     #[test]
     fn test_clone() {
         let bv = BitVec::from_bytes(&[0b1101_0000]);
@@ -0,0 +1,651 @@
+use crate::bit_vec::BitVec;
+use crate::{DoubleHasher, HashIter, SipHasherBuilder};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const BLOCK_BIT_COUNT: usize = 512;
+const BLOCK_BIT_COUNT_LOG2: u32 = 9;
+const MULTIPLICATIVE_HASH_CONSTANT: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// A space-efficient probabilistic data structure to test for membership in a set that only
+/// touches a single cache line per operation.
+///
+/// A blocked bloom filter is split into 512-bit blocks. The first hash of an element selects a
+/// block, and the remaining `K` hashes map the element to `K` bits within that block. Since all
+/// of the bits of an element are in the same cache line, insertions and lookups incur at most
+/// one cache miss. The trade-off is a higher false positive probability than a bloom filter with
+/// the same number of bits because elements are not spread evenly across blocks. The
+/// constructors account for this by allocating more bits when necessary.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::bloom::BlockedBloomFilter;
+///
+/// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.clear();
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.len(), 512);
+/// assert_eq!(filter.block_count(), 1);
+/// assert_eq!(filter.hasher_count(), 36);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct BlockedBloomFilter<T, B = SipHasherBuilder> {
+    bit_vec: BitVec,
+    hasher: DoubleHasher<T, B>,
+    hasher_count: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> BlockedBloomFilter<T> {
+    /// Constructs a new, empty `BlockedBloomFilter` with an estimated max capacity of
+    /// `item_count` items, and a maximum false positive probability of `fpp`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::with_hashers(
+            item_count,
+            fpp,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `BlockedBloomFilter` with at least `bit_count` bits, and an
+    /// estimated max capacity of `item_count` items. The number of bits is rounded up to a
+    /// multiple of 512.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_item_count(1000, 100);
+    /// ```
+    pub fn from_item_count(bit_count: usize, item_count: usize) -> Self {
+        Self::from_item_count_with_hashers(
+            bit_count,
+            item_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `BlockedBloomFilter` with at least `bit_count` bits, and a
+    /// maximum false positive probability of `fpp`. The number of bits is rounded up to a
+    /// multiple of 512.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_fpp(1000, 0.01);
+    /// ```
+    pub fn from_fpp(bit_count: usize, fpp: f64) -> Self {
+        Self::from_fpp_with_hashers(
+            bit_count,
+            fpp,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> BlockedBloomFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_block_count(bit_count: usize) -> usize {
+        ((bit_count as f64) / (BLOCK_BIT_COUNT as f64))
+            .ceil()
+            .max(1.0) as usize
+    }
+
+    fn get_hasher_count(bit_count: usize, item_count: usize) -> usize {
+        ((bit_count as f64) / (item_count as f64) * 2f64.ln()).ceil() as usize
+    }
+
+    // The number of items in each block follows a Poisson distribution, so the false positive
+    // probability is the expected false positive probability of a single block.
+    fn get_fpp(block_count: usize, item_count: usize, hasher_count: usize) -> f64 {
+        if item_count == 0 {
+            return 0.0;
+        }
+        let lambda = item_count as f64 / block_count as f64;
+        let spread = 10.0 * lambda.sqrt() + 10.0;
+        let start = (lambda - spread).max(0.0) as usize;
+        let end = (lambda + spread).ceil() as usize;
+
+        let mut ln_factorial = (1..=start).map(|i| (i as f64).ln()).sum::<f64>();
+        let mut fpp = 0.0;
+        for item_count in start..=end {
+            if item_count > start {
+                ln_factorial += (item_count as f64).ln();
+            }
+            let ln_probability = item_count as f64 * lambda.ln() - lambda - ln_factorial;
+            let unset_probability =
+                (1.0 - 1.0 / BLOCK_BIT_COUNT as f64).powf(item_count as f64 * hasher_count as f64);
+            fpp += ln_probability.exp() * (1.0 - unset_probability).powi(hasher_count as i32);
+        }
+        fpp
+    }
+
+    fn from_block_count(block_count: usize, hasher_count: usize, hash_builders: [B; 2]) -> Self {
+        BlockedBloomFilter {
+            bit_vec: BitVec::new(block_count * BLOCK_BIT_COUNT),
+            hasher: DoubleHasher::with_hashers(hash_builders),
+            hasher_count,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `BlockedBloomFilter` with an estimated max capacity of
+    /// `item_count` items, a maximum false positive probability of `fpp`, and two hasher
+    /// builders for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(item_count: usize, fpp: f64, hash_builders: [B; 2]) -> Self {
+        let bit_count = (-fpp.log2() * (item_count as f64) / 2f64.ln()).ceil() as usize;
+        let mut block_count = Self::get_block_count(bit_count);
+        let mut hasher_count;
+        loop {
+            hasher_count = Self::get_hasher_count(block_count * BLOCK_BIT_COUNT, item_count);
+            if Self::get_fpp(block_count, item_count, hasher_count) <= fpp {
+                break;
+            }
+            block_count += (block_count / 64).max(1);
+        }
+        Self::from_block_count(block_count, hasher_count, hash_builders)
+    }
+
+    /// Constructs a new, empty `BlockedBloomFilter` with at least `bit_count` bits, an estimated
+    /// max capacity of `item_count` items, and two hasher builders for double hashing. The
+    /// number of bits is rounded up to a multiple of 512.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_item_count_with_hashers(
+    ///     1000,
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_item_count_with_hashers(
+        bit_count: usize,
+        item_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        let block_count = Self::get_block_count(bit_count);
+        let hasher_count = Self::get_hasher_count(block_count * BLOCK_BIT_COUNT, item_count);
+        Self::from_block_count(block_count, hasher_count, hash_builders)
+    }
+
+    /// Constructs a new, empty `BlockedBloomFilter` with at least `bit_count` bits, a maximum
+    /// false positive probability of `fpp`, and two hasher builders for double hashing. The
+    /// number of bits is rounded up to a multiple of 512.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_fpp_with_hashers(
+    ///     1000,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_fpp_with_hashers(bit_count: usize, fpp: f64, hash_builders: [B; 2]) -> Self {
+        let block_count = Self::get_block_count(bit_count);
+        let bit_count = block_count * BLOCK_BIT_COUNT;
+
+        // binary search for the largest item count that satisfies the false positive probability
+        let mut low = 1;
+        let mut high = (-(2f64.ln() * (bit_count as f64) / fpp.log2()).floor() as usize).max(1);
+        while low < high {
+            let mid = (low + high) / 2 + 1;
+            let hasher_count = Self::get_hasher_count(bit_count, mid);
+            if Self::get_fpp(block_count, mid, hasher_count) <= fpp {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let hasher_count = Self::get_hasher_count(bit_count, low);
+        Self::from_block_count(block_count, hasher_count, hash_builders)
+    }
+
+    /// Inserts an element into the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.insert_hashes(self.hasher.hash(item));
+    }
+
+    /// Inserts an element into the blocked bloom filter using its precomputed 128-bit hash. The
+    /// upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.insert_hashes(HashIter::from_hash(hash));
+    }
+
+    fn insert_hashes(&mut self, mut hashes: HashIter) {
+        let block_offset = self.get_block_offset(hashes.next().expect("Expected hash."));
+        hashes.take(self.hasher_count).for_each(|hash| {
+            let offset = block_offset + Self::get_bit_offset(hash);
+            self.bit_vec.set(offset, true);
+        })
+    }
+
+    /// Checks if an element is possibly in the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.contains_hashes(self.hasher.hash(item))
+    }
+
+    /// Checks if an element is possibly in the blocked bloom filter using its precomputed 128-bit
+    /// hash. The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
+    fn contains_hashes(&self, mut hashes: HashIter) -> bool {
+        let block_offset = self.get_block_offset(hashes.next().expect("Expected hash."));
+        hashes.take(self.hasher_count).all(|hash| {
+            let offset = block_offset + Self::get_bit_offset(hash);
+            self.bit_vec[offset]
+        })
+    }
+
+    #[inline]
+    fn get_block_offset(&self, hash: u64) -> usize {
+        (hash % self.block_count() as u64) as usize * BLOCK_BIT_COUNT
+    }
+
+    // Double hashing modulo a power of two frequently maps an element to the same bit multiple
+    // times, so the hash is scrambled with a multiplicative hash and the top bits are used.
+    #[inline]
+    fn get_bit_offset(hash: u64) -> usize {
+        (hash.wrapping_mul(MULTIPLICATIVE_HASH_CONSTANT) >> (64 - BLOCK_BIT_COUNT_LOG2)) as usize
+    }
+
+    /// Returns the number of bits in the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_fpp(1000, 0.01);
+    ///
+    /// assert_eq!(filter.len(), 1024);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.bit_vec.len()
+    }
+
+    /// Returns `true` if the blocked bloom filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_fpp(1000, 0.01);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.bit_vec.is_empty()
+    }
+
+    /// Returns the number of 512-bit blocks in the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::from_fpp(1000, 0.01);
+    ///
+    /// assert_eq!(filter.block_count(), 2);
+    /// ```
+    pub fn block_count(&self) -> usize {
+        self.bit_vec.len() / BLOCK_BIT_COUNT
+    }
+
+    /// Returns the number of hash functions used by the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert_eq!(filter.hasher_count(), 36);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Clears the blocked bloom filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.bit_vec.set_all(false)
+    }
+
+    /// Returns the number of set bits in the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::from_item_count_with_hashers(
+    ///     1000,
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_ones(), 8);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.bit_vec.count_ones()
+    }
+
+    /// Returns the number of unset bits in the blocked bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::from_item_count_with_hashers(
+    ///     1000,
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_zeros(), 1016);
+    /// ```
+    pub fn count_zeros(&self) -> usize {
+        self.bit_vec.count_zeros()
+    }
+
+    /// Returns the estimated false positive probability of the blocked bloom filter. This value
+    /// will increase as more items are added. Since each element is checked against a single
+    /// block, the estimate is the average false positive probability of the blocks, which is
+    /// higher than that of a bloom filter with the same number of set bits when the blocks are
+    /// unevenly filled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let mut filter = BlockedBloomFilter::<String>::new(100, 0.01);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > 0.0);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fpp_sum = (0..self.block_count())
+            .map(|block_index| {
+                let block_offset = block_index * BLOCK_BIT_COUNT;
+                let one_count = self
+                    .bit_vec
+                    .count_ones_in_range(block_offset..block_offset + BLOCK_BIT_COUNT);
+                (one_count as f64 / BLOCK_BIT_COUNT as f64).powi(self.hasher_count as i32)
+            })
+            .sum::<f64>();
+        fpp_sum / self.block_count() as f64
+    }
+
+    /// Returns a reference to the blocked bloom filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BlockedBloomFilter;
+    ///
+    /// let filter = BlockedBloomFilter::<String>::new(10, 0.01);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        self.hasher.hashers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockedBloomFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+
+    #[test]
+    fn test_new() {
+        let mut filter = BlockedBloomFilter::<String>::with_hashers(
+            1000,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert!(!filter.contains("foo"));
+        filter.insert("foo");
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count_ones(), 8);
+        assert_eq!(filter.count_zeros(), 10232);
+
+        filter.clear();
+        assert!(!filter.contains("foo"));
+
+        assert_eq!(filter.len(), 10240);
+        assert_eq!(filter.block_count(), 20);
+        assert_eq!(filter.hasher_count(), 8);
+    }
+
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = BlockedBloomFilter::<String>::with_hashers(
+            1000,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert_hash(hash_item("foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(hash_item("bar")));
+        assert!(!filter.contains_hash(hash_item("baz")));
+    }
+
+    #[test]
+    fn test_from_item_count() {
+        let mut filter = BlockedBloomFilter::<String>::from_item_count_with_hashers(
+            1000,
+            100,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert!(!filter.contains("foo"));
+        filter.insert("foo");
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count_ones(), 8);
+        assert_eq!(filter.count_zeros(), 1016);
+
+        assert_eq!(filter.len(), 1024);
+        assert_eq!(filter.block_count(), 2);
+        assert_eq!(filter.hasher_count(), 8);
+    }
+
+    #[test]
+    fn test_from_fpp() {
+        let filter = BlockedBloomFilter::<String>::from_fpp_with_hashers(
+            100_000,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert_eq!(filter.len(), 100_352);
+        assert_eq!(filter.block_count(), 196);
+        assert_eq!(filter.hasher_count(), 7);
+    }
+
+    #[test]
+    fn test_fpp() {
+        let mut filter = BlockedBloomFilter::<u32>::with_hashers(
+            1000,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            filter.insert(&item);
+        }
+
+        let false_positive_count = (1000..11000).filter(|item| filter.contains(item)).count();
+        assert!(false_positive_count < 150);
+        assert!(filter.estimated_fpp() < 0.015);
+    }
+
+    #[test]
+    fn test_estimated_fpp() {
+        let mut filter = BlockedBloomFilter::<String>::from_item_count_with_hashers(
+            1000,
+            100,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert!(filter.estimated_fpp() < std::f64::EPSILON);
+
+        filter.insert("foo");
+
+        let expected_fpp = (8f64 / 512f64).powi(8) / 2f64;
+        assert!((filter.estimated_fpp() - expected_fpp).abs() < std::f64::EPSILON);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = BlockedBloomFilter::<String>::new(100, 0.01);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: BlockedBloomFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert_eq!(filter.bit_vec, de_filter.bit_vec);
+        assert_eq!(filter.hasher_count(), de_filter.hasher_count());
+        assert_eq!(filter.hashers(), de_filter.hashers());
+    }
+}
@@ -0,0 +1,637 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::{DoubleHasher, HashIter, SipHasherBuilder};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const DEFAULT_COUNTER_BIT_COUNT: usize = 4;
+
+/// A space-efficient probabilistic data structure to test for membership in a set. Counting bloom
+/// filters also provide the flexibility to remove items.
+///
+/// A counting bloom filter replaces each bit of a bloom filter with a small counter. Inserting an
+/// element increments the `K` counters that it maps to and removing an element decrements them.
+/// An element is possibly in the set if all of its `K` counters are non-zero. Counters saturate at
+/// their maximum value instead of overflowing. A saturated counter is never decremented since its
+/// true value is unknown, so removals can never introduce false negatives.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::bloom::CountingBloomFilter;
+///
+/// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.remove("foo");
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.len(), 96);
+/// assert_eq!(filter.counter_bit_count(), 4);
+/// assert_eq!(filter.hasher_count(), 7);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct CountingBloomFilter<T, B = SipHasherBuilder> {
+    counter_vec: BitstringVec,
+    hasher: DoubleHasher<T, B>,
+    hasher_count: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> CountingBloomFilter<T> {
+    /// Constructs a new, empty `CountingBloomFilter` with an estimated max capacity of
+    /// `item_count` items, and a maximum false positive probability of `fpp`. By default, the
+    /// counting bloom filter will have 4 bits per counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        Self::with_hashers(
+            item_count,
+            fpp,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `CountingBloomFilter` with an estimated max capacity of
+    /// `item_count` items, a maximum false positive probability of `fpp`, and `counter_bit_count`
+    /// bits per counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counter_bit_count` is not 4, 8, or 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::from_counter_bit_count(10, 0.01, 8);
+    /// ```
+    pub fn from_counter_bit_count(item_count: usize, fpp: f64, counter_bit_count: usize) -> Self {
+        Self::from_counter_bit_count_with_hashers(
+            item_count,
+            fpp,
+            counter_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> CountingBloomFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_hasher_count(counter_count: usize, item_count: usize) -> usize {
+        ((counter_count as f64) / (item_count as f64) * 2f64.ln()).ceil() as usize
+    }
+
+    /// Constructs a new, empty `CountingBloomFilter` with an estimated max capacity of
+    /// `item_count` items, a maximum false positive probability of `fpp`, and two hasher builders
+    /// for double hashing. By default, the counting bloom filter will have 4 bits per counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CountingBloomFilter::<String>::with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(item_count: usize, fpp: f64, hash_builders: [B; 2]) -> Self {
+        Self::from_counter_bit_count_with_hashers(
+            item_count,
+            fpp,
+            DEFAULT_COUNTER_BIT_COUNT,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `CountingBloomFilter` with an estimated max capacity of
+    /// `item_count` items, a maximum false positive probability of `fpp`, `counter_bit_count` bits
+    /// per counter, and two hasher builders for double hashing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counter_bit_count` is not 4, 8, or 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CountingBloomFilter::<String>::from_counter_bit_count_with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     8,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_counter_bit_count_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        counter_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(counter_bit_count == 4 || counter_bit_count == 8 || counter_bit_count == 16);
+        let counter_count = (-fpp.log2() * (item_count as f64) / 2f64.ln()).ceil() as usize;
+        CountingBloomFilter {
+            counter_vec: BitstringVec::new(counter_bit_count, counter_count),
+            hasher: DoubleHasher::with_hashers(hash_builders),
+            hasher_count: Self::get_hasher_count(counter_count, item_count),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn max_counter_value(&self) -> u64 {
+        (1 << self.counter_bit_count()) - 1
+    }
+
+    /// Inserts an element into the counting bloom filter. Returns `false` if any of the counters
+    /// of the element overflowed. The element is still inserted, but the overflowed counters will
+    /// stay saturated at their maximum value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(filter.insert("foo"));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.insert_hashes(self.hasher.hash(item))
+    }
+
+    /// Inserts an element into the counting bloom filter using its precomputed 128-bit hash. The
+    /// upper and lower 64 bits of `hash` are used as the two hashes for double hashing. Returns
+    /// `false` if any of the element's counters overflowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) -> bool {
+        self.insert_hashes(HashIter::from_hash(hash))
+    }
+
+    fn insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let max_counter_value = self.max_counter_value();
+        let mut is_overflowed = false;
+        for hash in hashes.take(self.hasher_count) {
+            let offset = (hash % self.counter_vec.len() as u64) as usize;
+            let counter = self.counter_vec.get(offset);
+            if counter == max_counter_value {
+                is_overflowed = true;
+            } else {
+                self.counter_vec.set(offset, counter + 1);
+            }
+        }
+        !is_overflowed
+    }
+
+    /// Removes an element from the counting bloom filter. Returns `false` if the element is
+    /// definitely not in the counting bloom filter, in which case decrementing its counters would
+    /// underflow and the counting bloom filter is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.remove("foo"));
+    /// assert!(!filter.remove("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.remove_hashes(self.hasher.hash(item))
+    }
+
+    /// Removes an element from the counting bloom filter using its precomputed 128-bit hash. The
+    /// upper and lower 64 bits of `hash` are used as the two hashes for double hashing. Returns
+    /// `false` if the element is definitely not in the counting bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.remove_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// assert!(!filter.remove_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn remove_hash(&mut self, hash: u128) -> bool {
+        self.remove_hashes(HashIter::from_hash(hash))
+    }
+
+    fn remove_hashes(&mut self, hashes: HashIter) -> bool {
+        if self.count_hashes(hashes) == 0 {
+            return false;
+        }
+
+        let max_counter_value = self.max_counter_value();
+        for hash in hashes.take(self.hasher_count) {
+            let offset = (hash % self.counter_vec.len() as u64) as usize;
+            let counter = self.counter_vec.get(offset);
+            // saturated counters no longer track their true value
+            if counter != 0 && counter != max_counter_value {
+                self.counter_vec.set(offset, counter - 1);
+            }
+        }
+        true
+    }
+
+    /// Checks if an element is possibly in the counting bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.count(item) > 0
+    }
+
+    /// Checks if an element is possibly in the counting bloom filter using its precomputed
+    /// 128-bit hash. The upper and lower 64 bits of `hash` are used as the two hashes for double
+    /// hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.count_hashes(HashIter::from_hash(hash)) > 0
+    }
+
+    /// Returns the estimated number of times an element was inserted into the counting bloom
+    /// filter. The estimate may be larger than the actual number of insertions, and is capped at
+    /// the maximum counter value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foo");
+    /// assert_eq!(filter.count("foo"), 2);
+    /// ```
+    pub fn count<U>(&self, item: &U) -> u64
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.count_hashes(self.hasher.hash(item))
+    }
+
+    fn count_hashes(&self, hashes: HashIter) -> u64 {
+        hashes
+            .take(self.hasher_count)
+            .map(|hash| {
+                let offset = hash % self.counter_vec.len() as u64;
+                self.counter_vec.get(offset as usize)
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of counters in the counting bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert_eq!(filter.len(), 96);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.counter_vec.len()
+    }
+
+    /// Returns `true` if the counting bloom filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.counter_vec.is_empty()
+    }
+
+    /// Returns the number of bits in each counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert_eq!(filter.counter_bit_count(), 4);
+    /// ```
+    pub fn counter_bit_count(&self) -> usize {
+        self.counter_vec.bit_count()
+    }
+
+    /// Returns the number of hash functions used by the counting bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert_eq!(filter.hasher_count(), 7);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Clears the counting bloom filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.counter_vec.clear()
+    }
+
+    /// Returns the number of non-zero counters in the counting bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_nonzero(), 7);
+    /// ```
+    pub fn count_nonzero(&self) -> usize {
+        self.counter_vec.occupied_len()
+    }
+
+    /// Returns the estimated false positive probability of the counting bloom filter. This value
+    /// will increase as more items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let mut filter = CountingBloomFilter::<String>::new(100, 0.01);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let single_fpp = self.counter_vec.occupied_len() as f64 / self.counter_vec.len() as f64;
+        single_fpp.powi(self.hasher_count as i32)
+    }
+
+    /// Returns a reference to the counting bloom filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::CountingBloomFilter;
+    ///
+    /// let filter = CountingBloomFilter::<String>::new(10, 0.01);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        self.hasher.hashers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountingBloomFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+
+    #[test]
+    fn test_new() {
+        let mut filter = CountingBloomFilter::<String>::with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert!(!filter.contains("foo"));
+        assert!(filter.insert("foo"));
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count_nonzero(), 7);
+
+        filter.clear();
+        assert!(!filter.contains("foo"));
+
+        assert_eq!(filter.len(), 96);
+        assert_eq!(filter.counter_bit_count(), 4);
+        assert_eq!(filter.hasher_count(), 7);
+    }
+
+    #[test]
+    fn test_from_counter_bit_count() {
+        let filter = CountingBloomFilter::<String>::from_counter_bit_count_with_hashers(
+            10,
+            0.01,
+            16,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert_eq!(filter.len(), 96);
+        assert_eq!(filter.counter_bit_count(), 16);
+        assert_eq!(filter.hasher_count(), 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_counter_bit_count_invalid() {
+        CountingBloomFilter::<String>::from_counter_bit_count(10, 0.01, 5);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut filter = CountingBloomFilter::<String>::with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.insert("foo");
+        filter.insert("bar");
+        assert_eq!(filter.count("foo"), 2);
+
+        assert!(filter.remove("foo"));
+        assert_eq!(filter.count("foo"), 1);
+        assert!(filter.remove("foo"));
+        assert!(!filter.contains("foo"));
+        assert!(filter.contains("bar"));
+
+        assert!(!filter.remove("foo"));
+        assert!(filter.contains("bar"));
+        assert_eq!(filter.count_nonzero(), 7);
+    }
+
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = CountingBloomFilter::<String>::with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert_hash(hash_item("foo"));
+        filter.insert("foo");
+        assert_eq!(filter.count("foo"), 2);
+        assert!(filter.contains_hash(hash_item("foo")));
+        assert!(!filter.contains_hash(hash_item("bar")));
+
+        assert!(filter.remove_hash(hash_item("foo")));
+        assert!(filter.remove("foo"));
+        assert!(!filter.remove_hash(hash_item("foo")));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut filter = CountingBloomFilter::<String>::with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for _ in 0..15 {
+            assert!(filter.insert("foo"));
+        }
+        assert!(!filter.insert("foo"));
+        assert_eq!(filter.count("foo"), 15);
+
+        // saturated counters are never decremented
+        for _ in 0..20 {
+            assert!(filter.remove("foo"));
+        }
+        assert!(filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_estimated_fpp() {
+        let mut filter = CountingBloomFilter::<String>::with_hashers(
+            100,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert!(filter.estimated_fpp() < std::f64::EPSILON);
+
+        filter.insert("foo");
+
+        let expected_fpp = (7f64 / 959f64).powi(7);
+        assert!((filter.estimated_fpp() - expected_fpp).abs() < std::f64::EPSILON);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = CountingBloomFilter::<String>::new(100, 0.01);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: CountingBloomFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert_eq!(filter.counter_vec, de_filter.counter_vec);
+        assert_eq!(filter.hasher_count(), de_filter.hasher_count());
+        assert_eq!(filter.hashers(), de_filter.hashers());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::bit_vec::BitVec;
+use crate::bitstring_vec::BitstringVec;
 use crate::{DoubleHasher, HashIter, SipHasherBuilder};
 use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
@@ -42,7 +43,6 @@ use std::marker::PhantomData;
 pub struct BSBloomFilter<T, B = SipHasherBuilder> {
     bit_vec: BitVec,
     hasher: DoubleHasher<T, B>,
-    #[cfg_attr(feature = "serde", serde(skip, default = "XorShiftRng::from_entropy"))]
     rng: XorShiftRng,
     bit_count: usize,
     hasher_count: usize,
@@ -107,7 +107,36 @@ where
         }
     }
 
-    /// Inserts an element into the bloom filter and returns if it is distinct
+    /// Constructs a new, empty `BSBloomFilter` with `bit_count` bits per filter, a false
+    /// positive probability of `fpp`, two hash builders for double hashing, and a seed for the
+    /// random number generator used to reset bits. Filters constructed with the same parameters
+    /// and seed behave identically when given the same stream of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BSBloomFilter::<String>::with_hashers_and_seed(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(
+        bit_count: usize,
+        fpp: f64,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::with_hashers(bit_count, fpp, hash_builders);
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Inserts an element into the bloom filter.
     ///
     /// # Examples
     ///
@@ -123,8 +152,49 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let hashes = self.hasher.hash(item);
-        if !self.contains_hashes(hashes) {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the bloom filter using its precomputed 128-bit hash. The upper and
+    /// lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSBloomFilter;
+    ///
+    /// let mut filter = BSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hashes(HashIter::from_hash(hash));
+    }
+
+    /// Inserts an element into the bloom filter and returns `true` if it is distinct. The element
+    /// is distinct if it was not in the bloom filter before the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSBloomFilter;
+    ///
+    /// let mut filter = BSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert_hashes(self.hasher.hash(item))
+    }
+
+    fn check_and_insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let is_distinct = !self.contains_hashes(hashes);
+        if is_distinct {
             (0..self.hasher_count).for_each(|index| {
                 let index = index * self.bit_count + self.rng.gen_range(0, self.bit_count);
                 self.bit_vec.set(index, false);
@@ -138,6 +208,7 @@ where
                     self.bit_vec.set(offset as usize, true);
                 })
         }
+        is_distinct
     }
 
     /// Checks if an element is possibly in the bloom filter.
@@ -161,6 +232,24 @@ where
         self.contains_hashes(self.hasher.hash(item))
     }
 
+    /// Checks if an element is possibly in the bloom filter using its precomputed 128-bit hash.
+    /// The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSBloomFilter;
+    ///
+    /// let mut filter = BSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
     fn contains_hashes(&self, hashes: HashIter) -> bool {
         hashes
             .take(self.hasher_count)
@@ -339,7 +428,6 @@ where
 pub struct BSSDBloomFilter<T, B = SipHasherBuilder> {
     bit_vec: BitVec,
     hasher: DoubleHasher<T, B>,
-    #[cfg_attr(feature = "serde", serde(skip, default = "XorShiftRng::from_entropy"))]
     rng: XorShiftRng,
     bit_count: usize,
     hasher_count: usize,
@@ -404,7 +492,36 @@ where
         }
     }
 
-    /// Inserts an element into the bloom filter and returns if it is distinct
+    /// Constructs a new, empty `BSSDBloomFilter` with `bit_count` bits per filter, a false
+    /// positive probability of `fpp`, two hash builders for double hashing, and a seed for the
+    /// random number generator used to reset bits. Filters constructed with the same parameters
+    /// and seed behave identically when given the same stream of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSSDBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BSSDBloomFilter::<String>::with_hashers_and_seed(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(
+        bit_count: usize,
+        fpp: f64,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::with_hashers(bit_count, fpp, hash_builders);
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Inserts an element into the bloom filter.
     ///
     /// # Examples
     ///
@@ -420,8 +537,49 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let hashes = self.hasher.hash(item);
-        if !self.contains_hashes(hashes) {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the bloom filter using its precomputed 128-bit hash. The upper and
+    /// lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSSDBloomFilter;
+    ///
+    /// let mut filter = BSSDBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hashes(HashIter::from_hash(hash));
+    }
+
+    /// Inserts an element into the bloom filter and returns `true` if it is distinct. The element
+    /// is distinct if it was not in the bloom filter before the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSSDBloomFilter;
+    ///
+    /// let mut filter = BSSDBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert_hashes(self.hasher.hash(item))
+    }
+
+    fn check_and_insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let is_distinct = !self.contains_hashes(hashes);
+        if is_distinct {
             let filter_index = self.rng.gen_range(0, self.hasher_count);
             let index = self.rng.gen_range(0, self.bit_count);
 
@@ -436,6 +594,7 @@ where
                     self.bit_vec.set(offset as usize, true);
                 })
         }
+        is_distinct
     }
 
     /// Checks if an element is possibly in the bloom filter.
@@ -459,6 +618,24 @@ where
         self.contains_hashes(self.hasher.hash(item))
     }
 
+    /// Checks if an element is possibly in the bloom filter using its precomputed 128-bit hash.
+    /// The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::BSSDBloomFilter;
+    ///
+    /// let mut filter = BSSDBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
     fn contains_hashes(&self, hashes: HashIter) -> bool {
         hashes
             .take(self.hasher_count)
@@ -637,7 +814,6 @@ where
 pub struct RLBSBloomFilter<T, B = SipHasherBuilder> {
     bit_vecs: Vec<BitVec>,
     hasher: DoubleHasher<T, B>,
-    #[cfg_attr(feature = "serde", serde(skip, default = "XorShiftRng::from_entropy"))]
     rng: XorShiftRng,
     bit_count: usize,
     hasher_count: usize,
@@ -702,7 +878,36 @@ where
         }
     }
 
-    /// Inserts an element into the bloom filter and returns if it is distinct
+    /// Constructs a new, empty `RLBSBloomFilter` with `bit_count` bits per filter, a false
+    /// positive probability of `fpp`, two hash builders for double hashing, and a seed for the
+    /// random number generator used to reset bits. Filters constructed with the same parameters
+    /// and seed behave identically when given the same stream of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::RLBSBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = RLBSBloomFilter::<String>::with_hashers_and_seed(
+    ///     10,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(
+        bit_count: usize,
+        fpp: f64,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::with_hashers(bit_count, fpp, hash_builders);
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Inserts an element into the bloom filter.
     ///
     /// # Examples
     ///
@@ -718,8 +923,49 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let hashes = self.hasher.hash(item);
-        if !self.contains_hashes(hashes) {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the bloom filter using its precomputed 128-bit hash. The upper and
+    /// lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::RLBSBloomFilter;
+    ///
+    /// let mut filter = RLBSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hashes(HashIter::from_hash(hash));
+    }
+
+    /// Inserts an element into the bloom filter and returns `true` if it is distinct. The element
+    /// is distinct if it was not in the bloom filter before the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::RLBSBloomFilter;
+    ///
+    /// let mut filter = RLBSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert_hashes(self.hasher.hash(item))
+    }
+
+    fn check_and_insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let is_distinct = !self.contains_hashes(hashes);
+        if is_distinct {
             (0..self.hasher_count).for_each(|filter_index| {
                 let prob = self.bit_vecs[filter_index].count_ones() as f64 / self.bit_count as f64;
                 let index = self.rng.gen_range(0, self.bit_count);
@@ -736,6 +982,7 @@ where
                     self.bit_vecs[filter_index].set(offset as usize, true);
                 })
         }
+        is_distinct
     }
 
     /// Checks if an element is possibly in the bloom filter.
@@ -759,6 +1006,24 @@ where
         self.contains_hashes(self.hasher.hash(item))
     }
 
+    /// Checks if an element is possibly in the bloom filter using its precomputed 128-bit hash.
+    /// The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::RLBSBloomFilter;
+    ///
+    /// let mut filter = RLBSBloomFilter::<String>::new(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
     fn contains_hashes(&self, hashes: HashIter) -> bool {
         hashes
             .take(self.hasher_count)
@@ -912,10 +1177,468 @@ where
     }
 }
 
+/// A space-efficient probabilistic data structure for data deduplication in streams.
+///
+/// This particular implementation is a Stable Bloom Filter. Each element is mapped to `K` cells
+/// of `d` bits. Before an element is inserted, `P` randomly chosen cells are decremented, and then
+/// the `K` cells of the element are set to `Max`. Old elements are continuously evicted this way,
+/// so the fraction of zero cells converges to a constant and the false positive probability
+/// converges to a stable point. Unlike the biased sampling filters, it can produce false
+/// negatives for elements that were evicted.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::bloom::StableBloomFilter;
+///
+/// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.clear();
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.len(), 100);
+/// assert_eq!(filter.cell_bit_count(), 2);
+/// assert_eq!(filter.max_value(), 3);
+/// assert_eq!(filter.decrement_count(), 10);
+/// assert_eq!(filter.hasher_count(), 3);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct StableBloomFilter<T, B = SipHasherBuilder> {
+    cell_vec: BitstringVec,
+    hasher: DoubleHasher<T, B>,
+    rng: XorShiftRng,
+    max_value: u64,
+    decrement_count: usize,
+    hasher_count: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> StableBloomFilter<T> {
+    /// Constructs a new, empty `StableBloomFilter` with `cell_count` cells that each store values
+    /// up to `max_value`, `decrement_count` cells decremented per insertion, and `hasher_count`
+    /// hash functions. Each cell uses the fewest bits that can represent `max_value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_value` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    /// ```
+    pub fn new(
+        cell_count: usize,
+        max_value: u64,
+        decrement_count: usize,
+        hasher_count: usize,
+    ) -> Self {
+        Self::with_hashers(
+            cell_count,
+            max_value,
+            decrement_count,
+            hasher_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> StableBloomFilter<T, B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `StableBloomFilter` with `cell_count` cells that each store values
+    /// up to `max_value`, `decrement_count` cells decremented per insertion, `hasher_count` hash
+    /// functions, and two hash builders for double hashing. Each cell uses the fewest bits that
+    /// can represent `max_value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_value` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = StableBloomFilter::<String>::with_hashers(
+    ///     100,
+    ///     3,
+    ///     10,
+    ///     3,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(
+        cell_count: usize,
+        max_value: u64,
+        decrement_count: usize,
+        hasher_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(max_value > 0);
+        let cell_bit_count = (64 - max_value.leading_zeros()) as usize;
+        StableBloomFilter {
+            cell_vec: BitstringVec::new(cell_bit_count, cell_count),
+            hasher: DoubleHasher::with_hashers(hash_builders),
+            rng: XorShiftRng::from_entropy(),
+            max_value,
+            decrement_count,
+            hasher_count,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `StableBloomFilter` with `cell_count` cells that each store values
+    /// up to `max_value`, `decrement_count` cells decremented per insertion, `hasher_count` hash
+    /// functions, two hash builders for double hashing, and a seed for the random number generator
+    /// used to decrement cells. Filters constructed with the same parameters and seed behave
+    /// identically when given the same stream of elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_value` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = StableBloomFilter::<String>::with_hashers_and_seed(
+    ///     100,
+    ///     3,
+    ///     10,
+    ///     3,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(
+        cell_count: usize,
+        max_value: u64,
+        decrement_count: usize,
+        hasher_count: usize,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::with_hashers(
+            cell_count,
+            max_value,
+            decrement_count,
+            hasher_count,
+            hash_builders,
+        );
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Inserts an element into the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the bloom filter using its precomputed 128-bit hash. The upper and
+    /// lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hashes(HashIter::from_hash(hash));
+    }
+
+    /// Inserts an element into the bloom filter and returns `true` if it is distinct. The element
+    /// is distinct if it was not in the bloom filter before the insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert_hashes(self.hasher.hash(item))
+    }
+
+    fn check_and_insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let is_distinct = !self.contains_hashes(hashes);
+
+        for _ in 0..self.decrement_count {
+            let index = self.rng.gen_range(0, self.cell_vec.len());
+            let value = self.cell_vec.get(index);
+            if value > 0 {
+                self.cell_vec.set(index, value - 1);
+            }
+        }
+
+        let cell_count = self.cell_vec.len() as u64;
+        for hash in hashes.take(self.hasher_count) {
+            self.cell_vec
+                .set((hash % cell_count) as usize, self.max_value);
+        }
+        is_distinct
+    }
+
+    /// Checks if an element is possibly in the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.contains_hashes(self.hasher.hash(item))
+    }
+
+    /// Checks if an element is possibly in the bloom filter using its precomputed 128-bit hash.
+    /// The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
+    fn contains_hashes(&self, hashes: HashIter) -> bool {
+        hashes.take(self.hasher_count).all(|hash| {
+            let offset = hash % self.cell_vec.len() as u64;
+            self.cell_vec.get(offset as usize) > 0
+        })
+    }
+
+    /// Returns the number of cells in the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert_eq!(filter.len(), 100);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.cell_vec.len()
+    }
+
+    /// Returns `true` if the bloom filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bits in each cell in the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert_eq!(filter.cell_bit_count(), 2);
+    /// ```
+    pub fn cell_bit_count(&self) -> usize {
+        self.cell_vec.bit_count()
+    }
+
+    /// Returns the value that a cell is set to when an element is inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert_eq!(filter.max_value(), 3);
+    /// ```
+    pub fn max_value(&self) -> u64 {
+        self.max_value
+    }
+
+    /// Returns the number of cells that are decremented when an element is inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert_eq!(filter.decrement_count(), 10);
+    /// ```
+    pub fn decrement_count(&self) -> usize {
+        self.decrement_count
+    }
+
+    /// Returns the number of hash functions used by the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// assert_eq!(filter.hasher_count(), 3);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Clears the bloom filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.cell_vec = BitstringVec::new(self.cell_vec.bit_count(), self.cell_vec.len());
+    }
+
+    /// Returns the number of non-zero cells in the bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = StableBloomFilter::<String>::with_hashers(
+    ///     100,
+    ///     3,
+    ///     10,
+    ///     3,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_nonzero(), 3);
+    /// ```
+    pub fn count_nonzero(&self) -> usize {
+        self.cell_vec.occupied_len()
+    }
+
+    /// Returns the false positive probability of the bloom filter once the fraction of zero cells
+    /// has converged, which happens after sufficiently many insertions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(1000, 3, 10, 3);
+    ///
+    /// assert!((filter.stable_fpp() - 0.163).abs() < 0.001);
+    /// ```
+    pub fn stable_fpp(&self) -> f64 {
+        let hasher_count = self.hasher_count as f64;
+        let cell_count = self.cell_vec.len() as f64;
+        let decrement_rate = self.decrement_count as f64 * (1.0 / hasher_count - 1.0 / cell_count);
+        let zero_probability = (1.0 / (1.0 + 1.0 / decrement_rate)).powf(self.max_value as f64);
+        (1.0 - zero_probability).powi(self.hasher_count as i32)
+    }
+
+    /// Returns a reference to the bloom filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::StableBloomFilter;
+    ///
+    /// let filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        self.hasher.hashers()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BSBloomFilter, BSSDBloomFilter, RLBSBloomFilter};
-    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use super::{BSBloomFilter, BSSDBloomFilter, RLBSBloomFilter, StableBloomFilter};
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
 
     #[test]
     fn test_bs() {
@@ -936,6 +1659,103 @@ mod tests {
         assert_eq!(filter.hasher_count(), 7);
     }
 
+    #[test]
+    fn test_check_and_insert() {
+        let mut bs_filter =
+            BSBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        assert!(bs_filter.check_and_insert("foo"));
+        assert!(!bs_filter.check_and_insert("foo"));
+
+        let mut bssd_filter =
+            BSSDBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        assert!(bssd_filter.check_and_insert("foo"));
+        assert!(!bssd_filter.check_and_insert("foo"));
+
+        let mut rlbs_filter =
+            RLBSBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        assert!(rlbs_filter.check_and_insert("foo"));
+        assert!(!rlbs_filter.check_and_insert("foo"));
+
+        let mut stable_filter = StableBloomFilter::<String>::with_hashers(
+            100,
+            3,
+            10,
+            3,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert!(stable_filter.check_and_insert("foo"));
+        assert!(!stable_filter.check_and_insert("foo"));
+    }
+
+    #[test]
+    fn test_insert_hash() {
+        let mut bs_filter =
+            BSBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        bs_filter.insert_hash(hash_item("foo"));
+        assert!(bs_filter.contains("foo"));
+        assert!(bs_filter.contains_hash(hash_item("foo")));
+
+        let mut bssd_filter =
+            BSSDBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        bssd_filter.insert_hash(hash_item("foo"));
+        assert!(bssd_filter.contains("foo"));
+        assert!(bssd_filter.contains_hash(hash_item("foo")));
+
+        let mut rlbs_filter =
+            RLBSBloomFilter::<String>::with_hashers(10, 0.01, [hash_builder_1(), hash_builder_2()]);
+        rlbs_filter.insert_hash(hash_item("foo"));
+        assert!(rlbs_filter.contains("foo"));
+        assert!(rlbs_filter.contains_hash(hash_item("foo")));
+
+        let mut stable_filter = StableBloomFilter::<String>::with_hashers(
+            100,
+            3,
+            10,
+            3,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        stable_filter.insert_hash(hash_item("foo"));
+        assert!(stable_filter.contains("foo"));
+        assert!(stable_filter.contains_hash(hash_item("foo")));
+    }
+
+    #[test]
+    fn test_seed() {
+        let hash_builders = [hash_builder_1(), hash_builder_2()];
+        let mut bs_filter_1 =
+            BSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut bs_filter_2 =
+            BSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut bssd_filter_1 =
+            BSSDBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut bssd_filter_2 =
+            BSSDBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut rlbs_filter_1 =
+            RLBSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut rlbs_filter_2 =
+            RLBSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut stable_filter_1 =
+            StableBloomFilter::<u32>::with_hashers_and_seed(100, 3, 10, 3, hash_builders, 0);
+        let mut stable_filter_2 =
+            StableBloomFilter::<u32>::with_hashers_and_seed(100, 3, 10, 3, hash_builders, 0);
+
+        for item in 0..100 {
+            bs_filter_1.insert(&item);
+            bs_filter_2.insert(&item);
+            bssd_filter_1.insert(&item);
+            bssd_filter_2.insert(&item);
+            rlbs_filter_1.insert(&item);
+            rlbs_filter_2.insert(&item);
+            stable_filter_1.insert(&item);
+            stable_filter_2.insert(&item);
+        }
+
+        assert_eq!(bs_filter_1.bit_vec, bs_filter_2.bit_vec);
+        assert_eq!(bssd_filter_1.bit_vec, bssd_filter_2.bit_vec);
+        assert_eq!(rlbs_filter_1.bit_vecs, rlbs_filter_2.bit_vecs);
+        assert_eq!(stable_filter_1.cell_vec, stable_filter_2.cell_vec);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_bs_ser_de() {
@@ -1021,4 +1841,112 @@ mod tests {
         assert_eq!(filter.hasher_count(), de_filter.hasher_count());
         assert_eq!(filter.hashers(), de_filter.hashers());
     }
+
+    #[test]
+    fn test_stable() {
+        let mut filter = StableBloomFilter::<String>::with_hashers(
+            100,
+            3,
+            10,
+            3,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert!(!filter.contains("foo"));
+        filter.insert("foo");
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count_nonzero(), 3);
+
+        filter.clear();
+        assert!(!filter.contains("foo"));
+        assert_eq!(filter.count_nonzero(), 0);
+
+        assert_eq!(filter.len(), 100);
+        assert_eq!(filter.cell_bit_count(), 2);
+        assert_eq!(filter.max_value(), 3);
+        assert_eq!(filter.decrement_count(), 10);
+        assert_eq!(filter.hasher_count(), 3);
+    }
+
+    #[test]
+    fn test_stable_fpp() {
+        let mut filter = StableBloomFilter::<u32>::with_hashers(
+            1000,
+            3,
+            10,
+            3,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..10_000 {
+            filter.insert(&item);
+        }
+
+        let false_positive_count = (10_000..20_000)
+            .filter(|item| filter.contains(item))
+            .count();
+        let fpp = false_positive_count as f64 / 10_000.0;
+        assert!((fpp - filter.stable_fpp()).abs() < 0.05);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_stable_ser_de() {
+        let mut filter = StableBloomFilter::<String>::new(100, 3, 10, 3);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: StableBloomFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert_eq!(filter.cell_vec, de_filter.cell_vec);
+        assert_eq!(filter.max_value(), de_filter.max_value());
+        assert_eq!(filter.decrement_count(), de_filter.decrement_count());
+        assert_eq!(filter.hasher_count(), de_filter.hasher_count());
+        assert_eq!(filter.hashers(), de_filter.hashers());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de_rng() {
+        let hash_builders = [hash_builder_1(), hash_builder_2()];
+        let mut bs_filter = BSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut bssd_filter =
+            BSSDBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut rlbs_filter =
+            RLBSBloomFilter::<u32>::with_hashers_and_seed(10, 0.01, hash_builders, 0);
+        let mut stable_filter =
+            StableBloomFilter::<u32>::with_hashers_and_seed(100, 3, 10, 3, hash_builders, 0);
+        for item in 0..50 {
+            bs_filter.insert(&item);
+            bssd_filter.insert(&item);
+            rlbs_filter.insert(&item);
+            stable_filter.insert(&item);
+        }
+
+        let mut de_bs_filter: BSBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&bs_filter).unwrap()).unwrap();
+        let mut de_bssd_filter: BSSDBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&bssd_filter).unwrap()).unwrap();
+        let mut de_rlbs_filter: RLBSBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&rlbs_filter).unwrap()).unwrap();
+        let mut de_stable_filter: StableBloomFilter<u32> =
+            bincode::deserialize(&bincode::serialize(&stable_filter).unwrap()).unwrap();
+
+        for item in 50..100 {
+            bs_filter.insert(&item);
+            de_bs_filter.insert(&item);
+            bssd_filter.insert(&item);
+            de_bssd_filter.insert(&item);
+            rlbs_filter.insert(&item);
+            de_rlbs_filter.insert(&item);
+            stable_filter.insert(&item);
+            de_stable_filter.insert(&item);
+        }
+
+        assert_eq!(bs_filter.bit_vec, de_bs_filter.bit_vec);
+        assert_eq!(bssd_filter.bit_vec, de_bssd_filter.bit_vec);
+        assert_eq!(rlbs_filter.bit_vecs, de_rlbs_filter.bit_vecs);
+        assert_eq!(stable_filter.cell_vec, de_stable_filter.cell_vec);
+    }
 }
@@ -1,11 +1,21 @@
 //! Space-efficient probabilistic data structure for approximate membership queries in a set.
 
+mod blocked_bloom_filter;
 mod bloom_filter;
+mod counting_bloom_filter;
 mod dd_bloom_filter;
 mod partitioned_bloom_filter;
 mod scalable_bloom_filter;
+mod sliding_window_bloom_filter;
+mod split_block_bloom_filter;
 
+pub use self::blocked_bloom_filter::BlockedBloomFilter;
 pub use self::bloom_filter::BloomFilter;
-pub use self::dd_bloom_filter::{BSBloomFilter, BSSDBloomFilter, RLBSBloomFilter};
+pub use self::counting_bloom_filter::CountingBloomFilter;
+pub use self::dd_bloom_filter::{
+    BSBloomFilter, BSSDBloomFilter, RLBSBloomFilter, StableBloomFilter,
+};
 pub use self::partitioned_bloom_filter::PartitionedBloomFilter;
 pub use self::scalable_bloom_filter::ScalableBloomFilter;
+pub use self::sliding_window_bloom_filter::SlidingWindowBloomFilter;
+pub use self::split_block_bloom_filter::SplitBlockBloomFilter;
@@ -1,10 +1,11 @@
 use crate::bit_vec::BitVec;
-use crate::{DoubleHasher, SipHasherBuilder};
+use crate::{DoubleHasher, HashIter, MergeError, SipHasherBuilder};
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::ops::{BitAndAssign, BitOrAssign};
 
 /// A space-efficient probabilistic data structure to test for membership in a set.
 ///
@@ -174,8 +175,27 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.hasher
-            .hash(item)
+        self.insert_hashes(self.hasher.hash(item));
+    }
+
+    /// Inserts an element into the bloom filter using its precomputed 128-bit hash. The upper and
+    /// lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::PartitionedBloomFilter;
+    ///
+    /// let mut filter = PartitionedBloomFilter::<String>::from_item_count(10, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.insert_hashes(HashIter::from_hash(hash));
+    }
+
+    fn insert_hashes(&mut self, hashes: HashIter) {
+        hashes
             .take(self.hasher_count)
             .enumerate()
             .for_each(|(index, hash)| {
@@ -202,8 +222,29 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.hasher
-            .hash(item)
+        self.contains_hashes(self.hasher.hash(item))
+    }
+
+    /// Checks if an element is possibly in the bloom filter using its precomputed 128-bit hash.
+    /// The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::PartitionedBloomFilter;
+    ///
+    /// let mut filter = PartitionedBloomFilter::<String>::from_item_count(10, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
+    fn contains_hashes(&self, hashes: HashIter) -> bool {
+        hashes
             .take(self.hasher_count)
             .enumerate()
             .all(|(index, hash)| {
@@ -355,6 +396,94 @@ where
         single_fpp.powi(self.hasher_count as i32)
     }
 
+    fn check_mergeable(&self, other: &PartitionedBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        if self.bit_count() != other.bit_count() {
+            return Err(MergeError::LengthMismatch);
+        }
+        if self.hasher_count() != other.hasher_count() {
+            return Err(MergeError::HasherCountMismatch);
+        }
+        if self.hashers() != other.hashers() {
+            return Err(MergeError::HasherMismatch);
+        }
+        Ok(())
+    }
+
+    /// Merges `other` into the bloom filter so that it contains every item in either filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two bloom filters do not have the same number of bits per
+    /// partition, the same number of hash functions, and the same hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::PartitionedBloomFilter;
+    ///
+    /// let mut filter1 = PartitionedBloomFilter::<String>::from_item_count(10, 0.01);
+    /// let mut filter2 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     *filter1.hashers(),
+    /// );
+    ///
+    /// filter1.insert("foo");
+    /// filter2.insert("bar");
+    /// filter1.union(&filter2).unwrap();
+    ///
+    /// assert!(filter1.contains("foo"));
+    /// assert!(filter1.contains("bar"));
+    /// ```
+    pub fn union(&mut self, other: &PartitionedBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        self.check_mergeable(other)?;
+        self.bit_vec.union(&other.bit_vec);
+        Ok(())
+    }
+
+    /// Merges `other` into the bloom filter so that it only contains items that are in both
+    /// filters. The result may have a higher false positive probability than a bloom filter built
+    /// directly from the intersection of the two sets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two bloom filters do not have the same number of bits per
+    /// partition, the same number of hash functions, and the same hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::PartitionedBloomFilter;
+    ///
+    /// let mut filter1 = PartitionedBloomFilter::<String>::from_item_count(10, 0.01);
+    /// let mut filter2 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+    ///     10,
+    ///     0.01,
+    ///     *filter1.hashers(),
+    /// );
+    ///
+    /// filter1.insert("foo");
+    /// filter2.insert("foo");
+    /// filter2.insert("bar");
+    /// filter1.intersect(&filter2).unwrap();
+    ///
+    /// assert!(filter1.contains("foo"));
+    /// ```
+    pub fn intersect(&mut self, other: &PartitionedBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        self.check_mergeable(other)?;
+        self.bit_vec.intersection(&other.bit_vec);
+        Ok(())
+    }
+
     /// Returns a reference to the bloom filter's hasher builders.
     ///
     /// # Examples
@@ -370,10 +499,41 @@ where
     }
 }
 
+impl<'a, T, B> BitOrAssign<&'a PartitionedBloomFilter<T, B>> for PartitionedBloomFilter<T, B>
+where
+    B: BuildHasher + PartialEq,
+{
+    /// Merges `other` into the bloom filter. See `PartitionedBloomFilter::union`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two bloom filters cannot be merged.
+    fn bitor_assign(&mut self, other: &'a PartitionedBloomFilter<T, B>) {
+        self.union(other)
+            .expect("Expected bloom filters with the same parameters.");
+    }
+}
+
+impl<'a, T, B> BitAndAssign<&'a PartitionedBloomFilter<T, B>> for PartitionedBloomFilter<T, B>
+where
+    B: BuildHasher + PartialEq,
+{
+    /// Intersects the bloom filter with `other`. See `PartitionedBloomFilter::intersect`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two bloom filters cannot be merged.
+    fn bitand_assign(&mut self, other: &'a PartitionedBloomFilter<T, B>) {
+        self.intersect(other)
+            .expect("Expected bloom filters with the same parameters.");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::PartitionedBloomFilter;
-    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+    use crate::MergeError;
 
     #[test]
     fn test_from_item_count() {
@@ -434,6 +594,96 @@ mod tests {
         assert!((filter.estimated_fpp() - expected_fpp).abs() < std::f64::EPSILON);
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            100,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert_hash(hash_item("foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(hash_item("bar")));
+        assert!(!filter.contains_hash(hash_item("baz")));
+    }
+
+    #[test]
+    fn test_union() {
+        let mut filter1 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let mut filter2 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter1.insert("foo");
+        filter2.insert("bar");
+        filter1 |= &filter2;
+
+        assert!(filter1.contains("foo"));
+        assert!(filter1.contains("bar"));
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut filter1 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let mut filter2 = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter1.insert("foo");
+        filter1.insert("bar");
+        filter2.insert("foo");
+        filter1 &= &filter2;
+
+        assert!(filter1.contains("foo"));
+        assert!(!filter1.contains("bar"));
+        assert_eq!(filter1.count_ones(), 7);
+    }
+
+    #[test]
+    fn test_merge_mismatch() {
+        let mut filter = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        let other = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            20,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert_eq!(filter.union(&other), Err(MergeError::LengthMismatch));
+
+        let other = PartitionedBloomFilter::<String>::from_bit_count_with_hashers(
+            14,
+            0.001,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert_eq!(filter.union(&other), Err(MergeError::HasherCountMismatch));
+
+        let other = PartitionedBloomFilter::<String>::from_item_count_with_hashers(
+            10,
+            0.01,
+            [hash_builder_2(), hash_builder_1()],
+        );
+        assert_eq!(filter.intersect(&other), Err(MergeError::HasherMismatch));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_ser_de() {
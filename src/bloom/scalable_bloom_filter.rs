@@ -1,9 +1,11 @@
 use crate::bloom::BloomFilter;
-use crate::SipHasherBuilder;
+use crate::{HashIter, MergeError, SipHasherBuilder};
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::cmp;
 use std::hash::{BuildHasher, Hash};
+use std::ops::{BitAndAssign, BitOrAssign};
 
 /// A growable, space-efficient probabilistic data structure to test for membership in a set.
 ///
@@ -122,28 +124,27 @@ where
         }
     }
 
+    fn grow(&mut self) {
+        let filter = self.filters.last().expect("Expected non-empty filters.");
+        let exponent = self.filters.len() as i32;
+        let new_filter = BloomFilter::from_fpp_with_hashers(
+            (filter.len() as f64 * self.growth_ratio).ceil() as usize,
+            self.initial_fpp * self.tightening_ratio.powi(exponent),
+            *filter.hashers(),
+        );
+        self.filters.push(new_filter);
+        self.approximate_bits_used = 0;
+    }
+
     fn try_grow(&mut self) {
-        let mut new_filter = None;
-        {
-            let filter = self.filters.last().expect("Expected non-empty filters.");
+        let filter = self.filters.last().expect("Expected non-empty filters.");
 
+        if self.approximate_bits_used * 2 >= filter.len() {
+            self.approximate_bits_used = filter.count_ones();
             if self.approximate_bits_used * 2 >= filter.len() {
-                self.approximate_bits_used = filter.count_ones();
-                if self.approximate_bits_used * 2 >= filter.len() {
-                    let exponent = self.filters.len() as i32;
-                    new_filter = Some(BloomFilter::from_fpp_with_hashers(
-                        (filter.len() as f64 * self.growth_ratio).ceil() as usize,
-                        self.initial_fpp * self.tightening_ratio.powi(exponent),
-                        *filter.hashers(),
-                    ));
-                    self.approximate_bits_used = 0;
-                }
+                self.grow();
             }
         }
-
-        if let Some(new_filter) = new_filter {
-            self.filters.push(new_filter);
-        }
     }
 
     /// Inserts an element into the scalable bloom filter.
@@ -162,15 +163,60 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        if !self.filters.iter().any(|filter| filter.contains(item)) {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the scalable bloom filter using its precomputed 128-bit hash. The
+    /// upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter = ScalableBloomFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hashes(HashIter::from_hash(hash));
+    }
+
+    /// Inserts an element into the scalable bloom filter and returns `true` if it is distinct.
+    /// The element is distinct if it was definitely not in the scalable bloom filter before the
+    /// insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter = ScalableBloomFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hashes = self.filters[0].hash(item);
+        self.check_and_insert_hashes(hashes)
+    }
+
+    fn check_and_insert_hashes(&mut self, hashes: HashIter) -> bool {
+        let is_distinct = !self.contains_hashes(hashes);
+        if is_distinct {
             let filter = self
                 .filters
                 .last_mut()
                 .expect("Expected non-empty filters.");
-            filter.insert(item);
+            filter.insert_hashes(hashes);
             self.approximate_bits_used += filter.hasher_count();
         }
         self.try_grow();
+        is_distinct
     }
 
     /// Checks if an element is possibly in the scalable bloom filter.
@@ -191,7 +237,32 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.filters.iter().any(|filter| filter.contains(item))
+        let hashes = self.filters[0].hash(item);
+        self.contains_hashes(hashes)
+    }
+
+    /// Checks if an element is possibly in the scalable bloom filter using its precomputed 128-bit
+    /// hash. The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter = ScalableBloomFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
+    fn contains_hashes(&self, hashes: HashIter) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| filter.contains_hashes(hashes))
     }
 
     /// Returns the number of bits in the scalable bloom filter.
@@ -263,6 +334,55 @@ where
         self.approximate_bits_used = 0;
     }
 
+    /// Replaces the bloom filters in the scalable bloom filter with a single bloom filter
+    /// containing `items`. Bloom filters cannot be resized without the original items, so
+    /// `items` should be every item that was inserted, or the subset that is still needed. The new
+    /// bloom filter has the initial false positive probability and enough bits to store `items`
+    /// at the optimal fill ratio, so the overall false positive probability of the scalable bloom
+    /// filter is still bounded by `initial_fpp * 1 / (1 - tightening_ratio)` as it grows.
+    ///
+    /// The new bloom filter is never smaller than the initial bloom filter. It replaces the
+    /// initial bloom filter, so `clear` and `union` use its number of bits afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter = ScalableBloomFilter::<u32>::new(100, 0.01, 2.0, 0.5);
+    /// for item in 0..1000 {
+    ///     filter.insert(&item);
+    /// }
+    /// assert!(filter.filter_count() > 1);
+    ///
+    /// filter.rebuild(0..1000);
+    /// assert_eq!(filter.filter_count(), 1);
+    /// assert!(filter.contains(&0));
+    /// ```
+    pub fn rebuild<I>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: Hash,
+    {
+        let initial_filter = self.filters.first().expect("Expected non-empty filters.");
+        let hashes: Vec<HashIter> = items
+            .into_iter()
+            .map(|item| initial_filter.hash(&item))
+            .collect();
+        let item_bit_count =
+            (-self.initial_fpp.log2() * hashes.len() as f64 / 2f64.ln()).ceil() as usize;
+        let mut filter = BloomFilter::from_fpp_with_hashers(
+            cmp::max(initial_filter.len(), item_bit_count),
+            self.initial_fpp,
+            *initial_filter.hashers(),
+        );
+        for hashes in hashes {
+            filter.insert_hashes(hashes);
+        }
+        self.approximate_bits_used = filter.count_ones();
+        self.filters = vec![filter];
+    }
+
     /// Returns the number of set bits in the scalable bloom filter.
     ///
     /// # Examples
@@ -332,6 +452,116 @@ where
             .product::<f64>()
     }
 
+    /// Merges `other` into the scalable bloom filter so that it contains every item in either
+    /// filter. Each bloom filter in `other` is merged into the bloom filter at the same position
+    /// in the scalable bloom filter, and new bloom filters are added if `other` has grown more.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two scalable bloom filters do not have the same initial number of
+    /// bits, false positive probability, growth ratio, tightening ratio, and hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter1 = ScalableBloomFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    /// let mut filter2 =
+    ///     ScalableBloomFilter::<String>::with_hashers(100, 0.01, 2.0, 0.5, *filter1.hashers());
+    ///
+    /// filter1.insert("foo");
+    /// filter2.insert("bar");
+    /// filter1.union(&filter2).unwrap();
+    ///
+    /// assert!(filter1.contains("foo"));
+    /// assert!(filter1.contains("bar"));
+    /// ```
+    pub fn union(&mut self, other: &ScalableBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        self.check_mergeable(other)?;
+        while self.filters.len() < other.filters.len() {
+            self.grow();
+        }
+        for (filter, other_filter) in self.filters.iter_mut().zip(&other.filters) {
+            filter.union(other_filter)?;
+        }
+
+        self.approximate_bits_used = self
+            .filters
+            .last()
+            .expect("Expected non-empty filters.")
+            .count_ones();
+        self.try_grow();
+        Ok(())
+    }
+
+    /// Merges `other` into the scalable bloom filter so that it only contains items that are in
+    /// both filters.
+    ///
+    /// Items are added to the most recent bloom filter, so once either scalable bloom filter has
+    /// grown, an item that is in both filters may be stored in bloom filters at different
+    /// positions and cannot be intersected. Only scalable bloom filters with a single bloom filter
+    /// can be intersected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two scalable bloom filters do not have the same initial number of
+    /// bits, false positive probability, growth ratio, tightening ratio, and hasher builders, or
+    /// if either scalable bloom filter has more than one bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::ScalableBloomFilter;
+    ///
+    /// let mut filter1 = ScalableBloomFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    /// let mut filter2 =
+    ///     ScalableBloomFilter::<String>::with_hashers(100, 0.01, 2.0, 0.5, *filter1.hashers());
+    ///
+    /// filter1.insert("foo");
+    /// filter2.insert("foo");
+    /// filter2.insert("bar");
+    /// filter1.intersect(&filter2).unwrap();
+    ///
+    /// assert!(filter1.contains("foo"));
+    /// ```
+    pub fn intersect(&mut self, other: &ScalableBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        self.check_mergeable(other)?;
+        if self.filters.len() > 1 || other.filters.len() > 1 {
+            return Err(MergeError::HasGrown);
+        }
+        self.filters[0].intersect(&other.filters[0])?;
+        self.approximate_bits_used = self.filters[0].count_ones();
+        Ok(())
+    }
+
+    fn check_mergeable(&self, other: &ScalableBloomFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        let initial_filter = self.filters.first().expect("Expected non-empty filters.");
+        let other_initial_filter = other.filters.first().expect("Expected non-empty filters.");
+        if initial_filter.len() != other_initial_filter.len() {
+            return Err(MergeError::LengthMismatch);
+        }
+        if self.hashers() != other.hashers() {
+            return Err(MergeError::HasherMismatch);
+        }
+        if (self.initial_fpp - other.initial_fpp).abs() > std::f64::EPSILON
+            || (self.growth_ratio - other.growth_ratio).abs() > std::f64::EPSILON
+            || (self.tightening_ratio - other.tightening_ratio).abs() > std::f64::EPSILON
+        {
+            return Err(MergeError::ParameterMismatch);
+        }
+        Ok(())
+    }
+
     /// Returns a reference to the scalable cuckoo filter's hasher builders.
     ///
     /// # Examples
@@ -350,10 +580,41 @@ where
     }
 }
 
+impl<'a, T, B> BitOrAssign<&'a ScalableBloomFilter<T, B>> for ScalableBloomFilter<T, B>
+where
+    B: BuildHasher + Clone + Copy + PartialEq,
+{
+    /// Merges `other` into the scalable bloom filter. See `ScalableBloomFilter::union`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two scalable bloom filters cannot be merged.
+    fn bitor_assign(&mut self, other: &'a ScalableBloomFilter<T, B>) {
+        self.union(other)
+            .expect("Expected scalable bloom filters with the same parameters.");
+    }
+}
+
+impl<'a, T, B> BitAndAssign<&'a ScalableBloomFilter<T, B>> for ScalableBloomFilter<T, B>
+where
+    B: BuildHasher + Clone + Copy + PartialEq,
+{
+    /// Intersects the scalable bloom filter with `other`. See `ScalableBloomFilter::intersect`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two scalable bloom filters cannot be merged.
+    fn bitand_assign(&mut self, other: &'a ScalableBloomFilter<T, B>) {
+        self.intersect(other)
+            .expect("Expected scalable bloom filters with the same parameters.");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ScalableBloomFilter;
-    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+    use crate::MergeError;
 
     #[test]
     fn test_scalable_bloom_filter() {
@@ -380,6 +641,43 @@ mod tests {
         assert_eq!(filter.filter_count(), 1);
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = ScalableBloomFilter::<String>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert_hash(hash_item("foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(hash_item("bar")));
+        assert!(!filter.contains_hash(hash_item("baz")));
+    }
+
+    #[test]
+    fn test_check_and_insert() {
+        let mut filter = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for item in 0..200 {
+            filter.insert(&item);
+        }
+        assert!(filter.filter_count() > 1);
+        assert!((0..200).all(|item| !filter.check_and_insert(&item)));
+        assert!(filter.check_and_insert(&1000));
+        assert!(!filter.check_and_insert(&1000));
+    }
+
     #[test]
     fn test_grow() {
         let mut filter = ScalableBloomFilter::<u32>::with_hashers(
@@ -400,6 +698,58 @@ mod tests {
         assert_eq!(filter.filters[1].hasher_count(), 8);
     }
 
+    #[test]
+    fn test_rebuild() {
+        let mut filter = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            filter.insert(&item);
+        }
+        assert!(filter.filter_count() > 1);
+
+        filter.rebuild(0..500);
+        assert_eq!(filter.filter_count(), 1);
+        assert_eq!(filter.filters[0].hasher_count(), 7);
+        assert!(filter.estimated_fpp() < 0.02);
+        for item in 0..500 {
+            assert!(filter.contains(&item));
+        }
+
+        for item in 1000..2000 {
+            filter.insert(&item);
+        }
+        assert!(filter.filter_count() > 1);
+        assert!(filter.estimated_fpp() < 0.02);
+        for item in (0..500).chain(1000..2000) {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_rebuild_empty() {
+        let mut filter = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            filter.insert(&item);
+        }
+
+        filter.rebuild(Vec::new());
+        assert_eq!(filter.filter_count(), 1);
+        assert_eq!(filter.len(), 100);
+        assert_eq!(filter.count_ones(), 0);
+        assert!(!filter.contains(&0));
+    }
+
     #[test]
     fn test_estimated_fpp() {
         let mut filter = ScalableBloomFilter::<u32>::with_hashers(
@@ -422,6 +772,155 @@ mod tests {
         assert!((filter.estimated_fpp() - expected_fpp).abs() < std::f64::EPSILON);
     }
 
+    #[test]
+    fn test_union() {
+        let mut filter1 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let mut filter2 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter1.insert(&0);
+        for i in 1..15 {
+            filter2.insert(&i);
+        }
+        assert_eq!(filter1.filter_count(), 1);
+        assert_eq!(filter2.filter_count(), 2);
+
+        filter1 |= &filter2;
+        assert_eq!(filter1.filter_count(), 2);
+        assert_eq!(filter1.filters[1], filter2.filters[1]);
+        for i in 0..15 {
+            assert!(filter1.contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut filter1 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let mut filter2 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for i in 0..5 {
+            filter1.insert(&i);
+            filter2.insert(&(i * 2));
+        }
+        assert_eq!(filter1.filter_count(), 1);
+        assert_eq!(filter2.filter_count(), 1);
+
+        let mut expected_filter = filter1.filters[0].clone();
+        expected_filter &= &filter2.filters[0];
+
+        filter1 &= &filter2;
+        assert_eq!(filter1.filters, vec![expected_filter.clone()]);
+        assert_eq!(filter1.approximate_bits_used, expected_filter.count_ones());
+        assert!(filter1.contains(&0));
+        assert!(filter1.contains(&2));
+        assert!(!filter1.contains(&1));
+    }
+
+    #[test]
+    fn test_intersect_grown() {
+        let mut filter1 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let mut filter2 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for i in 0..100 {
+            filter1.insert(&(i + 100));
+        }
+        for i in 0..100 {
+            filter1.insert(&i);
+            filter2.insert(&i);
+        }
+        assert!(filter1.filter_count() > 1);
+        assert!(filter2.filter_count() > 1);
+
+        let filters = filter1.filters.clone();
+        assert_eq!(filter1.intersect(&filter2), Err(MergeError::HasGrown));
+        assert_eq!(filter1.filters, filters);
+        assert!((0..100).all(|i| filter1.contains(&i)));
+
+        let mut filter3 = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert_eq!(filter3.intersect(&filter2), Err(MergeError::HasGrown));
+        assert_eq!(filter2.intersect(&filter3), Err(MergeError::HasGrown));
+    }
+
+    #[test]
+    fn test_union_mismatch() {
+        let mut filter = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        let other = ScalableBloomFilter::<u32>::with_hashers(
+            200,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert_eq!(filter.union(&other), Err(MergeError::LengthMismatch));
+
+        let other = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            3.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert_eq!(filter.union(&other), Err(MergeError::ParameterMismatch));
+
+        let other = ScalableBloomFilter::<u32>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_2(), hash_builder_1()],
+        );
+        assert_eq!(filter.union(&other), Err(MergeError::HasherMismatch));
+        assert_eq!(filter.intersect(&other), Err(MergeError::HasherMismatch));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_ser_de() {
@@ -0,0 +1,585 @@
+use crate::bloom::BloomFilter;
+use crate::{HashIter, SipHasherBuilder};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A space-efficient probabilistic data structure to test for membership in a sliding window of
+/// a stream.
+///
+/// A sliding window bloom filter is a ring of bloom filters called generations. Elements are
+/// always inserted into the newest generation, and an element is possibly in the window if any of
+/// the generations contain it. Rotating the ring clears the oldest generation and makes it the
+/// newest one, so elements expire a generation at a time. Rotation can either happen
+/// automatically after a fixed number of insertions, or be driven by the caller, for example on
+/// a timer.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+///
+/// let mut filter = SlidingWindowBloomFilter::<String>::from_time_window(100, 3, 0.01);
+///
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.rotate();
+/// filter.rotate();
+/// assert!(filter.contains("foo"));
+///
+/// filter.rotate();
+/// assert!(!filter.contains("foo"));
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SlidingWindowBloomFilter<T, B = SipHasherBuilder> {
+    filters: Vec<BloomFilter<T, B>>,
+    current_index: usize,
+    current_item_count: usize,
+    generation_item_count: usize,
+    is_count_based: bool,
+}
+
+impl<T> SlidingWindowBloomFilter<T> {
+    /// Constructs a new, empty `SlidingWindowBloomFilter` that contains the last
+    /// `window_item_count` inserted items using `generation_count` generations, and a maximum
+    /// false positive probability of `fpp` over the whole window. The generations are rotated
+    /// automatically after every `window_item_count / (generation_count - 1)` insertions, rounded
+    /// up, so items may also be reported up to one generation after they leave the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `generation_count` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    /// ```
+    pub fn new(window_item_count: usize, generation_count: usize, fpp: f64) -> Self {
+        Self::with_hashers(
+            window_item_count,
+            generation_count,
+            fpp,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `SlidingWindowBloomFilter` with `generation_count` generations that
+    /// each have an estimated max capacity of `generation_item_count` items, and a maximum false
+    /// positive probability of `fpp` over the whole window. The generations are only rotated when
+    /// `rotate` is called. If `rotate` is called every `T / (generation_count - 1)` seconds, then
+    /// the items inserted in the last `T` seconds are always reported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `generation_count` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::from_time_window(100, 5, 0.01);
+    /// ```
+    pub fn from_time_window(
+        generation_item_count: usize,
+        generation_count: usize,
+        fpp: f64,
+    ) -> Self {
+        Self::from_time_window_with_hashers(
+            generation_item_count,
+            generation_count,
+            fpp,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> SlidingWindowBloomFilter<T, B>
+where
+    B: BuildHasher + Clone + Copy,
+{
+    fn from_generations(
+        generation_item_count: usize,
+        generation_count: usize,
+        fpp: f64,
+        is_count_based: bool,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(generation_count >= 2);
+        let generation_fpp = 1.0 - (1.0 - fpp).powf(1.0 / generation_count as f64);
+        SlidingWindowBloomFilter {
+            filters: (0..generation_count)
+                .map(|_| {
+                    BloomFilter::with_hashers(generation_item_count, generation_fpp, hash_builders)
+                })
+                .collect(),
+            current_index: 0,
+            current_item_count: 0,
+            generation_item_count,
+            is_count_based,
+        }
+    }
+
+    /// Constructs a new, empty `SlidingWindowBloomFilter` that contains the last
+    /// `window_item_count` inserted items using `generation_count` generations, a maximum false
+    /// positive probability of `fpp` over the whole window, and two hasher builders for double
+    /// hashing. The generations are rotated automatically after every
+    /// `window_item_count / (generation_count - 1)` insertions, rounded up, so items may also be
+    /// reported up to one generation after they leave the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `generation_count` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::with_hashers(
+    ///     100,
+    ///     5,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(
+        window_item_count: usize,
+        generation_count: usize,
+        fpp: f64,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(generation_count >= 2);
+        Self::from_generations(
+            (window_item_count + generation_count - 2) / (generation_count - 1),
+            generation_count,
+            fpp,
+            true,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `SlidingWindowBloomFilter` with `generation_count` generations that
+    /// each have an estimated max capacity of `generation_item_count` items, a maximum false
+    /// positive probability of `fpp` over the whole window, and two hasher builders for double
+    /// hashing. The generations are only rotated when `rotate` is called. If `rotate` is called
+    /// every `T / (generation_count - 1)` seconds, then the items inserted in the last `T` seconds
+    /// are always reported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `generation_count` is less than 2.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::from_time_window_with_hashers(
+    ///     100,
+    ///     5,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_time_window_with_hashers(
+        generation_item_count: usize,
+        generation_count: usize,
+        fpp: f64,
+        hash_builders: [B; 2],
+    ) -> Self {
+        Self::from_generations(
+            generation_item_count,
+            generation_count,
+            fpp,
+            false,
+            hash_builders,
+        )
+    }
+
+    /// Inserts an element into the newest generation of the sliding window bloom filter. If the
+    /// generations are rotated automatically and the newest generation is full, the generations
+    /// are rotated first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hashes = self.filters[0].hash(item);
+        self.insert_hashes(hashes);
+    }
+
+    /// Inserts an element into the newest generation of the sliding window bloom filter using its
+    /// precomputed 128-bit hash. The upper and lower 64 bits of `hash` are used as the two hashes
+    /// for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.insert_hashes(HashIter::from_hash(hash));
+    }
+
+    fn insert_hashes(&mut self, hashes: HashIter) {
+        if self.is_count_based && self.current_item_count >= self.generation_item_count {
+            self.rotate();
+        }
+        self.filters[self.current_index].insert_hashes(hashes);
+        self.current_item_count += 1;
+    }
+
+    /// Checks if an element is possibly in any generation of the sliding window bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hashes = self.filters[0].hash(item);
+        self.contains_hashes(hashes)
+    }
+
+    /// Checks if an element is possibly in any generation of the sliding window bloom filter using
+    /// its precomputed 128-bit hash. The upper and lower 64 bits of `hash` are used as the two
+    /// hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_hashes(HashIter::from_hash(hash))
+    }
+
+    fn contains_hashes(&self, hashes: HashIter) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| filter.contains_hashes(hashes))
+    }
+
+    /// Expires the oldest generation of the sliding window bloom filter by clearing it and making
+    /// it the newest generation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::from_time_window(100, 2, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.rotate();
+    /// assert!(filter.contains("foo"));
+    ///
+    /// filter.rotate();
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn rotate(&mut self) {
+        self.current_index = (self.current_index + 1) % self.filters.len();
+        self.filters[self.current_index].clear();
+        self.current_item_count = 0;
+    }
+
+    /// Returns the number of bits in the sliding window bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert_eq!(filter.len(), 1620);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.filters.iter().map(|filter| filter.len()).sum()
+    }
+
+    /// Returns `true` if the sliding window bloom filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of generations in the sliding window bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert_eq!(filter.generation_count(), 5);
+    /// ```
+    pub fn generation_count(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Returns the estimated max capacity of each generation in the sliding window bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// assert_eq!(filter.generation_item_count(), 25);
+    /// ```
+    pub fn generation_item_count(&self) -> usize {
+        self.generation_item_count
+    }
+
+    /// Clears the sliding window bloom filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        for filter in &mut self.filters {
+            filter.clear();
+        }
+        self.current_index = 0;
+        self.current_item_count = 0;
+    }
+
+    /// Returns the number of set bits in the sliding window bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::with_hashers(
+    ///     100,
+    ///     5,
+    ///     0.01,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_ones(), 9);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.filters.iter().map(|filter| filter.count_ones()).sum()
+    }
+
+    /// Returns the estimated false positive probability of the sliding window bloom filter over
+    /// all of its generations. This value will increase as more items are added, and decrease
+    /// when generations are rotated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        1.0 - self
+            .filters
+            .iter()
+            .map(|filter| 1.0 - filter.estimated_fpp())
+            .product::<f64>()
+    }
+
+    /// Returns a reference to the sliding window bloom filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SlidingWindowBloomFilter;
+    ///
+    /// let filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        self.filters[0].hashers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlidingWindowBloomFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+
+    #[test]
+    fn test_new() {
+        let mut filter = SlidingWindowBloomFilter::<u32>::with_hashers(
+            100,
+            5,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert!(filter.estimated_fpp() < std::f64::EPSILON);
+
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+
+        for i in 900..1000 {
+            assert!(filter.contains(&i));
+        }
+
+        assert_eq!(filter.generation_count(), 5);
+        assert_eq!(filter.generation_item_count(), 25);
+        assert!(filter.estimated_fpp() < 0.01);
+    }
+
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = SlidingWindowBloomFilter::<String>::with_hashers(
+            100,
+            5,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert_hash(hash_item("foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(hash_item("bar")));
+        assert!(!filter.contains_hash(hash_item("baz")));
+    }
+
+    #[test]
+    fn test_from_time_window() {
+        let mut filter = SlidingWindowBloomFilter::<String>::from_time_window_with_hashers(
+            100,
+            3,
+            0.01,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for _ in 0..200 {
+            filter.insert("foo");
+        }
+        assert!(filter.contains("foo"));
+
+        filter.rotate();
+        filter.insert("bar");
+        filter.rotate();
+        assert!(filter.contains("foo"));
+        assert!(filter.contains("bar"));
+
+        filter.rotate();
+        assert!(!filter.contains("foo"));
+        assert!(filter.contains("bar"));
+
+        filter.rotate();
+        assert!(!filter.contains("bar"));
+        assert_eq!(filter.count_ones(), 0);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut filter = SlidingWindowBloomFilter::<u32>::new(100, 2, 0.01);
+
+        for i in 0..150 {
+            filter.insert(&i);
+        }
+        filter.clear();
+
+        assert_eq!(filter.count_ones(), 0);
+        assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = SlidingWindowBloomFilter::<String>::new(100, 5, 0.01);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: SlidingWindowBloomFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert_eq!(filter.filters, de_filter.filters);
+        assert_eq!(filter.current_index, de_filter.current_index);
+        assert_eq!(filter.current_item_count, de_filter.current_item_count);
+        assert_eq!(
+            filter.generation_item_count,
+            de_filter.generation_item_count
+        );
+        assert_eq!(filter.is_count_based, de_filter.is_count_based);
+    }
+}
@@ -0,0 +1,511 @@
+use crate::DecodeError;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::cmp;
+use std::hash::Hasher;
+use twox_hash::XxHash64;
+
+const WORD_COUNT: usize = 8;
+const WORD_BYTE_COUNT: usize = 4;
+const BLOCK_BYTE_COUNT: usize = WORD_COUNT * WORD_BYTE_COUNT;
+const MIN_BYTE_COUNT: usize = BLOCK_BYTE_COUNT;
+const MAX_BYTE_COUNT: usize = 128 * 1024 * 1024;
+const SALT: [u32; WORD_COUNT] = [
+    0x47b6_137b,
+    0x4497_4d91,
+    0x8824_ad5b,
+    0xa2b7_289d,
+    0x7054_95c7,
+    0x2df1_424b,
+    0x9efc_4947,
+    0x5c6b_fb31,
+];
+
+type Block = [u32; WORD_COUNT];
+
+/// A space-efficient probabilistic data structure to test for membership in a set that is
+/// compatible with the split block bloom filters used by Apache Parquet.
+///
+/// A split block bloom filter is split into 256-bit blocks that each consist of eight 32-bit
+/// words. The upper 32 bits of the hash of an element select a block, and the lower 32 bits are
+/// multiplied by eight salt constants to set exactly one bit in each word of that block. Elements
+/// are hashed with xxHash64 using a seed of zero, and the byte representation of the filter is
+/// the sequence of words in little-endian order. Both follow the Parquet specification, so filters
+/// can be read from and written to Parquet files.
+///
+/// Values are given as their Parquet plain encoding without a length prefix. For example, an
+/// `INT32` value is its four little-endian bytes and a `BYTE_ARRAY` value is its raw bytes.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+///
+/// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// filter.clear();
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.len(), 1024);
+/// assert_eq!(filter.block_count(), 4);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SplitBlockBloomFilter {
+    blocks: Vec<Block>,
+}
+
+impl SplitBlockBloomFilter {
+    /// Constructs a new, empty `SplitBlockBloomFilter` with an estimated max capacity of
+    /// `item_count` items, and a maximum false positive probability of `fpp`. The number of bytes
+    /// is rounded up to a power of two between 32 bytes and 128 MiB, following the Parquet
+    /// implementations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::new(100, 0.01);
+    /// ```
+    pub fn new(item_count: usize, fpp: f64) -> Self {
+        let bit_count = -8.0 * item_count as f64 / (1.0 - fpp.powf(1.0 / 8.0)).ln();
+        let byte_count = (bit_count / 8.0) as usize;
+        Self::from_byte_count(
+            cmp::min(cmp::max(byte_count, MIN_BYTE_COUNT), MAX_BYTE_COUNT).next_power_of_two(),
+        )
+    }
+
+    /// Constructs a new, empty `SplitBlockBloomFilter` with `byte_count` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_count` is zero or not a multiple of 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::from_byte_count(1024);
+    /// ```
+    pub fn from_byte_count(byte_count: usize) -> Self {
+        assert!(byte_count > 0 && byte_count % BLOCK_BYTE_COUNT == 0);
+        SplitBlockBloomFilter {
+            blocks: vec![[0; WORD_COUNT]; byte_count / BLOCK_BYTE_COUNT],
+        }
+    }
+
+    /// Constructs a `SplitBlockBloomFilter` from the bitset of a Parquet split block bloom
+    /// filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of bytes is zero or not a multiple of 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// let de_filter = SplitBlockBloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+    /// assert!(de_filter.contains("foo"));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.is_empty() || bytes.len() % BLOCK_BYTE_COUNT != 0 {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let blocks = bytes
+            .chunks(BLOCK_BYTE_COUNT)
+            .map(|block_bytes| {
+                let mut block = [0; WORD_COUNT];
+                for (word, word_bytes) in block.iter_mut().zip(block_bytes.chunks(WORD_BYTE_COUNT))
+                {
+                    let mut buffer = [0; WORD_BYTE_COUNT];
+                    buffer.copy_from_slice(word_bytes);
+                    *word = u32::from_le_bytes(buffer);
+                }
+                block
+            })
+            .collect();
+        Ok(SplitBlockBloomFilter { blocks })
+    }
+
+    /// Returns the bitset of the split block bloom filter as it is stored in a Parquet file. The
+    /// bitset does not include the Thrift `BloomFilterHeader` that precedes it in the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// assert_eq!(filter.to_bytes().len(), 128);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.iter().flat_map(|word| word.to_le_bytes().to_vec()))
+            .collect()
+    }
+
+    /// Returns the xxHash64 hash of a value with a seed of zero, as specified by Parquet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// assert_eq!(SplitBlockBloomFilter::hash(""), 0xef46_db37_51d8_e999);
+    /// ```
+    pub fn hash<U>(value: &U) -> u64
+    where
+        U: AsRef<[u8]> + ?Sized,
+    {
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(value.as_ref());
+        hasher.finish()
+    }
+
+    fn get_block_index(&self, hash: u64) -> usize {
+        (((hash >> 32) * self.blocks.len() as u64) >> 32) as usize
+    }
+
+    fn get_mask(hash: u64) -> Block {
+        let key = hash as u32;
+        let mut mask = [0; WORD_COUNT];
+        for (word, salt) in mask.iter_mut().zip(SALT.iter()) {
+            *word = 1 << (key.wrapping_mul(*salt) >> 27);
+        }
+        mask
+    }
+
+    /// Inserts the plain encoding of a value into the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert(&10i32.to_le_bytes());
+    /// ```
+    pub fn insert<U>(&mut self, value: &U)
+    where
+        U: AsRef<[u8]> + ?Sized,
+    {
+        self.insert_hash(Self::hash(value))
+    }
+
+    /// Inserts the xxHash64 hash of a value into the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// filter.insert_hash(SplitBlockBloomFilter::hash("foo"));
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn insert_hash(&mut self, hash: u64) {
+        let block_index = self.get_block_index(hash);
+        let block = &mut self.blocks[block_index];
+        for (word, mask) in block.iter_mut().zip(Self::get_mask(hash).iter()) {
+            *word |= mask;
+        }
+    }
+
+    /// Checks if the plain encoding of a value is possibly in the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, value: &U) -> bool
+    where
+        U: AsRef<[u8]> + ?Sized,
+    {
+        self.contains_hash(Self::hash(value))
+    }
+
+    /// Checks if the xxHash64 hash of a value is possibly in the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains_hash(SplitBlockBloomFilter::hash("foo")));
+    /// ```
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        let block = &self.blocks[self.get_block_index(hash)];
+        block
+            .iter()
+            .zip(Self::get_mask(hash).iter())
+            .all(|(word, mask)| word & mask != 0)
+    }
+
+    /// Returns the number of bits in the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::from_byte_count(1024);
+    ///
+    /// assert_eq!(filter.len(), 8192);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.blocks.len() * BLOCK_BYTE_COUNT * 8
+    }
+
+    /// Returns `true` if the split block bloom filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::from_byte_count(1024);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Returns the number of 256-bit blocks in the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let filter = SplitBlockBloomFilter::from_byte_count(1024);
+    ///
+    /// assert_eq!(filter.block_count(), 32);
+    /// ```
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Clears the split block bloom filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        for block in &mut self.blocks {
+            *block = [0; WORD_COUNT];
+        }
+    }
+
+    /// Returns the number of set bits in the split block bloom filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    /// filter.insert("foo");
+    ///
+    /// assert_eq!(filter.count_ones(), 8);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.iter())
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the estimated false positive probability of the split block bloom filter. This
+    /// value will increase as more items are added. Since each element checks one bit in every
+    /// word of a single block, the estimate is the average over all blocks of the product of the
+    /// fraction of set bits in each word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::bloom::SplitBlockBloomFilter;
+    ///
+    /// let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fpp_sum = self
+            .blocks
+            .iter()
+            .map(|block| {
+                block
+                    .iter()
+                    .map(|word| f64::from(word.count_ones()) / 32.0)
+                    .product::<f64>()
+            })
+            .sum::<f64>();
+        fpp_sum / self.blocks.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitBlockBloomFilter;
+    use crate::DecodeError;
+
+    #[test]
+    fn test_new() {
+        let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+
+        assert!(!filter.contains("foo"));
+        filter.insert("foo");
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count_ones(), 8);
+
+        filter.clear();
+        assert!(!filter.contains("foo"));
+
+        assert_eq!(filter.len(), 1024);
+        assert_eq!(filter.block_count(), 4);
+    }
+
+    #[test]
+    fn test_new_clamped() {
+        assert_eq!(SplitBlockBloomFilter::new(0, 0.01).to_bytes().len(), 32);
+        assert_eq!(
+            SplitBlockBloomFilter::new(1000, 0.01).to_bytes().len(),
+            2048
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(SplitBlockBloomFilter::hash(""), 0xef46_db37_51d8_e999);
+        assert_eq!(SplitBlockBloomFilter::hash("a"), 0xd24e_c4f1_a98c_6e5b);
+        assert_eq!(SplitBlockBloomFilter::hash("abc"), 0x44bc_2cf5_ad77_0999);
+    }
+
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = SplitBlockBloomFilter::from_byte_count(64);
+
+        filter.insert_hash(0x0000_0000_0000_0000);
+        filter.insert_hash(0xffff_ffff_0000_0001);
+        assert!(filter.contains_hash(0x0000_0000_0000_0000));
+        assert!(filter.contains_hash(0xffff_ffff_0000_0001));
+        assert!(!filter.contains_hash(0x0000_0000_0000_0001));
+
+        let mut expected_bytes = Vec::new();
+        for _ in 0..8 {
+            expected_bytes.extend_from_slice(&1u32.to_le_bytes());
+        }
+        for bit in &[8, 8, 17, 20, 14, 5, 19, 11] {
+            expected_bytes.extend_from_slice(&(1u32 << bit).to_le_bytes());
+        }
+        assert_eq!(filter.to_bytes(), expected_bytes);
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+        filter.insert("foo");
+        filter.insert("bar");
+
+        let bytes = filter.to_bytes();
+        let de_filter = SplitBlockBloomFilter::from_bytes(&bytes).unwrap();
+
+        assert_eq!(filter, de_filter);
+        assert_eq!(de_filter.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_from_bytes_invalid() {
+        assert_eq!(
+            SplitBlockBloomFilter::from_bytes(&[]),
+            Err(DecodeError::InvalidLength),
+        );
+        assert_eq!(
+            SplitBlockBloomFilter::from_bytes(&[0; 33]),
+            Err(DecodeError::InvalidLength),
+        );
+    }
+
+    #[test]
+    fn test_fpp() {
+        let mut filter = SplitBlockBloomFilter::new(1000, 0.01);
+        for item in 0u32..1000 {
+            filter.insert(&item.to_le_bytes());
+        }
+
+        let false_positive_count = (1000u32..11000)
+            .filter(|item| filter.contains(&item.to_le_bytes()))
+            .count();
+        assert!(false_positive_count < 150);
+        assert!(filter.estimated_fpp() < 0.015);
+    }
+
+    #[test]
+    fn test_estimated_fpp() {
+        let mut filter = SplitBlockBloomFilter::from_byte_count(64);
+        assert!(filter.estimated_fpp() < std::f64::EPSILON);
+
+        filter.insert("foo");
+
+        let expected_fpp = (1f64 / 32f64).powi(8) / 2f64;
+        assert!((filter.estimated_fpp() - expected_fpp).abs() < std::f64::EPSILON);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = SplitBlockBloomFilter::new(100, 0.01);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: SplitBlockBloomFilter = bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert_eq!(filter, de_filter);
+    }
+}
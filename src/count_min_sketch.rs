@@ -237,8 +237,28 @@ where
         U: Borrow<V>,
         V: Hash + ?Sized,
     {
+        self.insert_hashes(self.hasher.hash(item), value);
+    }
+
+    /// Inserts an element into the count-min sketch `value` times using its precomputed 128-bit
+    /// hash. The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::count_min_sketch::{CountMinSketch, CountMinStrategy};
+    ///
+    /// let mut count_min_sketch = CountMinSketch::<CountMinStrategy, String>::from_error(0.1, 0.05);
+    /// count_min_sketch.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210, 3);
+    /// assert_eq!(count_min_sketch.count_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210), 3);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128, value: i64) {
+        self.insert_hashes(HashIter::from_hash(hash), value);
+    }
+
+    fn insert_hashes(&mut self, hashes: HashIter, value: i64) {
         self.items += value;
-        for (row, hash) in self.hasher.hash(item).take(self.rows).enumerate() {
+        for (row, hash) in hashes.take(self.rows).enumerate() {
             let offset = hash % self.cols as u64;
             self.grid[row * self.cols + offset as usize] += value;
         }
@@ -264,6 +284,23 @@ where
         self.insert(item, -value);
     }
 
+    /// Removes an element from the count-min sketch `value` times using its precomputed 128-bit
+    /// hash. The upper and lower 64 bits of `hash` are used as the two hashes for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::count_min_sketch::{CountMinSketch, CountMinStrategy};
+    ///
+    /// let mut count_min_sketch = CountMinSketch::<CountMinStrategy, String>::from_error(0.1, 0.05);
+    /// count_min_sketch.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210, 3);
+    /// count_min_sketch.remove_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210, 2);
+    /// assert_eq!(count_min_sketch.count_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210), 1);
+    /// ```
+    pub fn remove_hash(&mut self, hash: u128, value: i64) {
+        self.insert_hash(hash, -value);
+    }
+
     /// Returns the estimated number of times `item` is in the count-min sketch.
     ///
     /// # Examples
@@ -280,11 +317,32 @@ where
         U: Borrow<V>,
         V: Hash + ?Sized,
     {
+        self.count_hashes(self.hasher.hash(item))
+    }
+
+    /// Returns the estimated number of times an element is in the count-min sketch using its
+    /// precomputed 128-bit hash. The upper and lower 64 bits of `hash` are used as the two hashes
+    /// for double hashing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::count_min_sketch::{CountMinSketch, CountMinStrategy};
+    ///
+    /// let mut count_min_sketch = CountMinSketch::<CountMinStrategy, String>::from_error(0.1, 0.05);
+    /// count_min_sketch.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210, 3);
+    /// assert_eq!(count_min_sketch.count_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210), 3);
+    /// ```
+    pub fn count_hash(&self, hash: u128) -> i64 {
+        self.count_hashes(HashIter::from_hash(hash))
+    }
+
+    fn count_hashes(&self, hashes: HashIter) -> i64 {
         let iter = ItemValueIter {
             row: 0,
             rows: self.rows,
             cols: self.cols,
-            hash_iter: self.hasher.hash(item),
+            hash_iter: hashes,
             grid: &self.grid,
         };
         T::get_estimate(self.items, self.rows, self.cols, iter)
@@ -413,6 +471,7 @@ mod tests {
             $(
                 mod $name {
                     use super::super::{CountMinSketch, $strategy};
+                    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
                     use crate::SipHasherBuilder;
 
                     #[test]
@@ -450,6 +509,16 @@ mod tests {
                         assert_eq!(cms.count("foo"), 0);
                     }
 
+                    #[test]
+                    fn test_insert_hash() {
+                        let mut cms = CountMinSketch::<$strategy, String>::from_error_with_hashers(0.1, 0.05, [hash_builder_1(), hash_builder_2()]);
+                        cms.insert_hash(hash_item("foo"), 3);
+                        cms.insert("foo", 2);
+                        assert_eq!(cms.count("foo"), 5);
+                        cms.remove_hash(hash_item("foo"), 4);
+                        assert_eq!(cms.count_hash(hash_item("foo")), 1);
+                    }
+
                     #[test]
                     fn test_clear() {
                         let mut cms = CountMinSketch::<$strategy, String, SipHasherBuilder>::from_error(0.1, 0.05);
@@ -0,0 +1,1276 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::cuckoo::{
+    self, Buckets, DEFAULT_ENTRIES_PER_INDEX, DEFAULT_FINGERPRINT_BIT_COUNT, DEFAULT_MAX_KICKS,
+};
+use crate::util;
+use crate::SipHasherBuilder;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const SELECTOR_BIT_COUNT: usize = 2;
+const SELECTOR_COUNT: usize = 1 << SELECTOR_BIT_COUNT;
+const HASH_BIT_COUNT: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+struct ExtraItem {
+    fingerprint: u64,
+    index: usize,
+    selector: u64,
+    hash: u64,
+}
+
+/// A space-efficient probabilistic data structure to test for membership in a set that adapts to
+/// false positives.
+///
+/// An adaptive cuckoo filter is a cuckoo filter where each entry also stores which of four hash
+/// functions, or selectors, was used to compute its fingerprint. When a query for an item that is
+/// not in the set is reported as a false positive, calling `adapt` with the item changes the
+/// selector of every entry that collided with it, so repeating the query is unlikely to be a
+/// false positive again.
+///
+/// Recomputing the fingerprint of an entry requires its item, so the adaptive cuckoo filter keeps
+/// the 64-bit hash of each inserted item in a separate table, which corresponds to the backing
+/// store of the original paper. This table is only read when items are inserted, removed,
+/// displaced, or adapted, and is never read by `contains`. Because of this table, removing an item
+/// that was not inserted never removes the fingerprint of another item.
+///
+/// The table is stored in the adaptive cuckoo filter, so every entry uses the fingerprint bits,
+/// two selector bits, and 64 hash bits. With the default 8-bit fingerprints, this is 74 bits per
+/// entry, which is more than nine times the memory of a `CuckooFilter` with the same parameters.
+/// `bits_per_entry` reports the total, and `filter_bits_per_entry` reports the part that is read
+/// by `contains`. A `CuckooFilter` should be preferred unless repeated false positives are costly.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+///
+/// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+///
+/// assert!(!filter.contains("foo"));
+/// filter.insert("foo");
+/// assert!(filter.contains("foo"));
+///
+/// // "foo" is in the filter, so there is nothing to adapt
+/// assert!(!filter.adapt("foo"));
+///
+/// filter.remove("foo");
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.len(), 0);
+/// assert_eq!(filter.capacity(), 128);
+/// assert_eq!(filter.bucket_len(), 32);
+/// assert_eq!(filter.fingerprint_bit_count(), 8);
+/// assert_eq!(filter.bits_per_entry(), 74);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct AdaptiveCuckooFilter<T, B = SipHasherBuilder> {
+    max_kicks: usize,
+    entries_per_index: usize,
+    fingerprint_vec: BitstringVec,
+    selector_vec: BitstringVec,
+    hash_vec: Vec<u64>,
+    extra_items: Vec<ExtraItem>,
+    hash_builders: [B; 2],
+    rng: XorShiftRng,
+    _marker: PhantomData<T>,
+}
+
+impl<T> AdaptiveCuckooFilter<T> {
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`. By default, the adaptive cuckoo filter will have 8 bits per item fingerprint,
+    /// 4 entries per index, and a maximum of 512 item displacements before terminating the
+    /// insertion process. The adaptive cuckoo filter will have an estimated maximum false
+    /// positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    /// ```
+    pub fn new(item_count: usize) -> Self {
+        Self::with_hashers(
+            item_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, a fingerprint bit count of `fingerprint_bit_count`, `entries_per_index`
+    /// entries per index, and a maximum of 512 item displacements before terminating the
+    /// insertion process. This method provides no guarantees on the false positive probability of
+    /// the adaptive cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, or
+    /// if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_parameters(100, 16, 8);
+    /// ```
+    pub fn from_parameters(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+    ) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`,
+    /// `entries_per_index` entries per index, and a maximum of 512 item displacements before
+    /// terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0 or if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_entries_per_index(100, 0.01, 4);
+    /// ```
+    pub fn from_entries_per_index(item_count: usize, fpp: f64, entries_per_index: usize) -> Self {
+        Self::from_entries_per_index_with_hashers(
+            item_count,
+            fpp,
+            entries_per_index,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`, a fingerprint bit
+    /// count of `fingerprint_bit_count`, and a maximum of 512 item displacements before
+    /// terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// or if it is impossible to achieve the given maximum false positive probability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_fingerprint_bit_count(100, 0.01, 10);
+    /// ```
+    pub fn from_fingerprint_bit_count(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+    ) -> Self {
+        Self::from_fingerprint_bit_count_with_hashers(
+            item_count,
+            fpp,
+            fingerprint_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> AdaptiveCuckooFilter<T, B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, and two hasher builders for double hashing. By default, the adaptive cuckoo
+    /// filter will have 8 bits per item fingerprint, 4 entries per index, and a maximum of 512
+    /// item displacements before terminating the insertion process. The adaptive cuckoo filter
+    /// will have an estimated maximum false positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::with_hashers(
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(item_count: usize, hash_builders: [B; 2]) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            DEFAULT_FINGERPRINT_BIT_COUNT,
+            DEFAULT_ENTRIES_PER_INDEX,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, two hasher builders for double hashing, and a seed for the random number
+    /// generator used to displace items. Filters constructed with the same parameters and seed
+    /// behave identically when given the same sequence of operations. By default, the adaptive
+    /// cuckoo filter will have a fingerprint bit count of 8, 4 entries per index, and a maximum
+    /// of 512 item displacements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::with_hashers_and_seed(
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(item_count: usize, hash_builders: [B; 2], seed: u64) -> Self {
+        let mut filter = Self::with_hashers(item_count, hash_builders);
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, a fingerprint bit count of `fingerprint_bit_count`, `entries_per_index`
+    /// entries per index, a maximum of 512 item displacements before terminating the insertion
+    /// process, and two hasher builders for double hashing. This method provides no guarantees on
+    /// the false positive probability of the adaptive cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, or
+    /// if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_parameters_with_hashers(
+    ///     100,
+    ///     16,
+    ///     8,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(
+            item_count > 0
+                && fingerprint_bit_count > 1
+                && fingerprint_bit_count <= 64
+                && entries_per_index > 0
+        );
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
+        AdaptiveCuckooFilter {
+            max_kicks: DEFAULT_MAX_KICKS,
+            entries_per_index,
+            fingerprint_vec: BitstringVec::new(
+                fingerprint_bit_count,
+                bucket_len * entries_per_index,
+            ),
+            selector_vec: BitstringVec::new(SELECTOR_BIT_COUNT, bucket_len * entries_per_index),
+            hash_vec: vec![0; bucket_len * entries_per_index],
+            extra_items: Vec::new(),
+            hash_builders,
+            rng: XorShiftRng::from_entropy(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, a fingerprint bit count of `fingerprint_bit_count`, `entries_per_index`
+    /// entries per index, a maximum of 512 item displacements before terminating the insertion
+    /// process, two hasher builders for double hashing, and a seed for the random number
+    /// generator used to displace items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, or
+    /// if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_parameters_with_hashers_and_seed(
+    ///     100,
+    ///     16,
+    ///     8,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers_and_seed(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            hash_builders,
+        );
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`,
+    /// `entries_per_index` entries per index, a maximum of 512 item displacements before
+    /// terminating the insertion process, and two hasher builders for double hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0 or if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_entries_per_index_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_entries_per_index_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(entries_per_index > 0);
+        Self::from_parameters_with_hashers(
+            item_count,
+            cuckoo::get_fingerprint_bit_count(fpp, entries_per_index),
+            entries_per_index,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `AdaptiveCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`, a fingerprint bit
+    /// count of `fingerprint_bit_count`, a maximum of 512 item displacements before terminating
+    /// the insertion process, and two hasher builders for double hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// or if it is impossible to achieve the given maximum false positive probability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::from_fingerprint_bit_count_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     10,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_fingerprint_bit_count_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(fingerprint_bit_count > 1 && fingerprint_bit_count <= 64);
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            cuckoo::get_entries_per_index(fpp, fingerprint_bit_count),
+            hash_builders,
+        )
+    }
+
+    // Returns the fingerprint of an item hash using the given selector. Selector 0 uses the
+    // lowest bits of the hash, and the other selectors rehash the hash with the selector.
+    fn get_fingerprint(&self, hash: u64, selector: u64) -> u64 {
+        let hash = if selector == 0 {
+            hash
+        } else {
+            util::hash(&self.hash_builders[0], &(hash, selector))
+        };
+        cuckoo::get_fingerprint(&self.hash_builders[0], hash, self.fingerprint_bit_count())
+    }
+
+    // Returns the fingerprint of an item hash using the given selector, computing it at most once
+    // per selector for each query.
+    fn get_cached_fingerprint(
+        &self,
+        hash: u64,
+        selector: u64,
+        fingerprints: &mut [u64; SELECTOR_COUNT],
+    ) -> u64 {
+        let fingerprint = &mut fingerprints[selector as usize];
+        if *fingerprint == 0 {
+            *fingerprint = self.get_fingerprint(hash, selector);
+        }
+        *fingerprint
+    }
+
+    // The alternate index only depends on the fingerprint of selector 0 so that it does not
+    // change when an entry is adapted.
+    fn get_alternate_index(&self, hash: u64, index: usize) -> usize {
+        let fingerprint = self.get_fingerprint(hash, 0);
+        cuckoo::get_alternate_index(
+            &self.hash_builders[1],
+            fingerprint,
+            index,
+            self.bucket_len(),
+        )
+    }
+
+    #[inline]
+    fn get_vec_index(&self, index: usize, bucket_index: usize) -> usize {
+        index * self.entries_per_index + bucket_index
+    }
+
+    fn get_hash_and_indexes<U>(&self, item: &U) -> (u64, usize, usize)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = util::hash(&self.hash_builders[0], &item);
+        let index_1 = util::hash(&self.hash_builders[1], &item) as usize % self.bucket_len();
+        let index_2 = self.get_alternate_index(hash, index_1);
+        (hash, index_1, index_2)
+    }
+
+    // Returns the indexes into the fingerprint vector of the entries in the two indexes, visiting
+    // each entry once if both indexes are the same.
+    fn get_vec_indexes(&self, index_1: usize, index_2: usize) -> Vec<usize> {
+        let mut indexes = vec![index_1];
+        if index_2 != index_1 {
+            indexes.push(index_2);
+        }
+        indexes
+            .into_iter()
+            .flat_map(|index| {
+                (0..self.entries_per_index)
+                    .map(move |bucket_index| index * self.entries_per_index + bucket_index)
+            })
+            .collect()
+    }
+
+    // Returns the index into the fingerprint vector of the entry of an inserted item.
+    fn find_vec_index(&self, hash: u64, index_1: usize, index_2: usize) -> Option<usize> {
+        self.get_vec_indexes(index_1, index_2)
+            .into_iter()
+            .find(|vec_index| {
+                self.fingerprint_vec.get(*vec_index) != 0 && self.hash_vec[*vec_index] == hash
+            })
+    }
+
+    // Returns the position in the extra items vector of the entry of an inserted item.
+    fn find_extra_item(&self, hash: u64, index_1: usize, index_2: usize) -> Option<usize> {
+        let min_index = cmp::min(index_1, index_2);
+        self.extra_items
+            .iter()
+            .position(|item| item.index == min_index && item.hash == hash)
+    }
+
+    /// Inserts an element into the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (hash, index_1, index_2) = self.get_hash_and_indexes(item);
+        if self.find_vec_index(hash, index_1, index_2).is_some()
+            || self.find_extra_item(hash, index_1, index_2).is_some()
+        {
+            return;
+        }
+        let fingerprint = self.get_fingerprint(hash, 0);
+        if let Some(((fingerprint, selector, hash), index)) =
+            self.insert_entry_with_kicks((fingerprint, 0, hash), index_1, index_2)
+        {
+            self.extra_items.push(ExtraItem {
+                fingerprint,
+                index,
+                selector,
+                hash,
+            });
+        }
+    }
+
+    fn set_entry(&mut self, vec_index: usize, (fingerprint, selector, hash): (u64, u64, u64)) {
+        self.fingerprint_vec.set(vec_index, fingerprint);
+        self.selector_vec.set(vec_index, selector);
+        self.hash_vec[vec_index] = hash;
+    }
+
+    /// Removes an element from the adaptive cuckoo filter. Elements that were not inserted are
+    /// never removed, even if they are false positives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    ///
+    /// filter.remove("foo");
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (hash, index_1, index_2) = self.get_hash_and_indexes(item);
+        if let Some(vec_index) = self.find_vec_index(hash, index_1, index_2) {
+            self.set_entry(vec_index, (0, 0, 0));
+        } else if let Some(position) = self.find_extra_item(hash, index_1, index_2) {
+            self.extra_items.swap_remove(position);
+        }
+    }
+
+    /// Checks if an element is possibly in the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (hash, index_1, index_2) = self.get_hash_and_indexes(item);
+        let min_index = cmp::min(index_1, index_2);
+        let mut fingerprints = [0; SELECTOR_COUNT];
+        let in_extra_items = self.extra_items.iter().any(|item| {
+            item.index == min_index
+                && item.fingerprint
+                    == self.get_cached_fingerprint(hash, item.selector, &mut fingerprints)
+        });
+        in_extra_items
+            || [index_1, index_2].iter().any(|index| {
+                (0..self.entries_per_index).any(|bucket_index| {
+                    let vec_index = self.get_vec_index(*index, bucket_index);
+                    let fingerprint = self.fingerprint_vec.get(vec_index);
+                    let selector = self.selector_vec.get(vec_index);
+                    fingerprint != 0
+                        && fingerprint
+                            == self.get_cached_fingerprint(hash, selector, &mut fingerprints)
+                })
+            })
+    }
+
+    /// Adapts the adaptive cuckoo filter to an element that was reported as a false positive,
+    /// and returns `true` if any entries were adapted. Every entry whose fingerprint collides with
+    /// the element is switched to the next selector and its fingerprint is recomputed, so the
+    /// element will likely not be reported as present afterwards. Nothing is adapted if the
+    /// element was inserted into the adaptive cuckoo filter.
+    ///
+    /// Each entry cycles through four selectors, so an entry that is adapted repeatedly may
+    /// eventually collide with earlier false positives again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<u32>::from_parameters(1, 8, 4);
+    /// for item in 0..4 {
+    ///     filter.insert(&item);
+    /// }
+    ///
+    /// let false_positive = (4..).find(|item| filter.contains(item)).unwrap();
+    /// assert!(filter.adapt(&false_positive));
+    /// assert!((0..4).all(|item| filter.contains(&item)));
+    /// ```
+    pub fn adapt<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (hash, index_1, index_2) = self.get_hash_and_indexes(item);
+        if self.find_vec_index(hash, index_1, index_2).is_some()
+            || self.find_extra_item(hash, index_1, index_2).is_some()
+        {
+            return false;
+        }
+
+        let min_index = cmp::min(index_1, index_2);
+        let mut fingerprints = [0; SELECTOR_COUNT];
+        let mut is_adapted = false;
+        for vec_index in self.get_vec_indexes(index_1, index_2) {
+            let fingerprint = self.fingerprint_vec.get(vec_index);
+            let selector = self.selector_vec.get(vec_index);
+            if fingerprint == 0
+                || fingerprint != self.get_cached_fingerprint(hash, selector, &mut fingerprints)
+            {
+                continue;
+            }
+            let selector = (selector + 1) % SELECTOR_COUNT as u64;
+            let entry_hash = self.hash_vec[vec_index];
+            let fingerprint = self.get_fingerprint(entry_hash, selector);
+            self.set_entry(vec_index, (fingerprint, selector, entry_hash));
+            is_adapted = true;
+        }
+        for position in 0..self.extra_items.len() {
+            let ExtraItem {
+                fingerprint,
+                index,
+                selector,
+                hash: entry_hash,
+            } = self.extra_items[position];
+            if index != min_index
+                || fingerprint != self.get_cached_fingerprint(hash, selector, &mut fingerprints)
+            {
+                continue;
+            }
+            let selector = (selector + 1) % SELECTOR_COUNT as u64;
+            let fingerprint = self.get_fingerprint(entry_hash, selector);
+            self.extra_items[position].fingerprint = fingerprint;
+            self.extra_items[position].selector = selector;
+            is_adapted = true;
+        }
+        is_adapted
+    }
+
+    /// Clears the adaptive cuckoo filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.fingerprint_vec.clear();
+        self.selector_vec.clear();
+        for hash in &mut self.hash_vec {
+            *hash = 0;
+        }
+        self.extra_items.clear();
+    }
+
+    /// Returns the number of occupied entries in the adaptive cuckoo filter. It does not account
+    /// for items in the extra items vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.fingerprint_vec.occupied_len()
+    }
+
+    /// Returns `true` if there are no occupied entries in the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum capacity of the adaptive cuckoo filter. Items may spill into the extra
+    /// items vector even though the length of the adaptive cuckoo filter is less than the
+    /// capacity.
+    ///
+    /// Every entry of the capacity uses `bits_per_entry` bits whether or not it is occupied, so
+    /// the adaptive cuckoo filter uses `capacity * bits_per_entry` bits in total.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.capacity(), 128);
+    /// assert_eq!(filter.capacity() * filter.bits_per_entry(), 9472);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.fingerprint_vec.len()
+    }
+
+    /// Returns the number of buckets in the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.bucket_len(), 32);
+    /// ```
+    pub fn bucket_len(&self) -> usize {
+        self.capacity() / self.entries_per_index
+    }
+
+    /// Returns the number of entries per index in the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.entries_per_index(), 4);
+    /// ```
+    pub fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    /// Returns the number of items that could not be inserted into the adaptive cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::from_parameters_with_hashers(
+    ///     1,
+    ///     8,
+    ///     1,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foobar");
+    /// assert_eq!(filter.extra_items_len(), 1);
+    /// ```
+    pub fn extra_items_len(&self) -> usize {
+        self.extra_items.len()
+    }
+
+    /// Returns `true` if there are any items that could not be inserted into the adaptive cuckoo
+    /// filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::from_parameters(1, 8, 1);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foobar");
+    /// assert!(filter.is_nearly_full());
+    /// ```
+    pub fn is_nearly_full(&self) -> bool {
+        !self.extra_items.is_empty()
+    }
+
+    /// Returns the number of bits in each item fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_vec.bit_count()
+    }
+
+    /// Returns the number of bits used to store each entry of the adaptive cuckoo filter,
+    /// including the 64-bit item hash that is kept to recompute fingerprints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.bits_per_entry(), 74);
+    /// ```
+    pub fn bits_per_entry(&self) -> usize {
+        self.filter_bits_per_entry() + HASH_BIT_COUNT
+    }
+
+    /// Returns the number of bits of each entry that are read by `contains`, which are the
+    /// fingerprint bits and the selector bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.filter_bits_per_entry(), 10);
+    /// ```
+    pub fn filter_bits_per_entry(&self) -> usize {
+        self.fingerprint_bit_count() + SELECTOR_BIT_COUNT
+    }
+
+    /// Returns the estimated false positive probability of the adaptive cuckoo filter before any
+    /// entries are adapted. This value will increase as more items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fingerprints_count = 2.0f64.powi(self.fingerprint_bit_count() as i32);
+        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
+        let occupied_ratio = self.len() as f64 / self.capacity() as f64;
+        1.0 - single_fpp.powf(2.0 * self.entries_per_index() as f64 * occupied_ratio)
+    }
+
+    /// Returns a reference to the adaptive cuckoo filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::AdaptiveCuckooFilter;
+    ///
+    /// let filter = AdaptiveCuckooFilter::<String>::new(100);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        &self.hash_builders
+    }
+}
+
+impl<T, B> Buckets for AdaptiveCuckooFilter<T, B>
+where
+    B: BuildHasher,
+{
+    // a fingerprint, its selector, and the hash of its item
+    type Entry = (u64, u64, u64);
+
+    fn max_kicks(&self) -> usize {
+        self.max_kicks
+    }
+
+    fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    fn entry_alternate_index(&self, (_, _, hash): (u64, u64, u64), index: usize) -> usize {
+        self.get_alternate_index(hash, index)
+    }
+
+    fn insert_entry(&mut self, entry: (u64, u64, u64), index: usize) -> bool {
+        for bucket_index in 0..self.entries_per_index {
+            let vec_index = self.get_vec_index(index, bucket_index);
+            if self.fingerprint_vec.get(vec_index) == 0 {
+                self.set_entry(vec_index, entry);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn replace_entry(
+        &mut self,
+        index: usize,
+        bucket_index: usize,
+        entry: (u64, u64, u64),
+    ) -> (u64, u64, u64) {
+        let vec_index = self.get_vec_index(index, bucket_index);
+        let old_entry = (
+            self.fingerprint_vec.get(vec_index),
+            self.selector_vec.get(vec_index),
+            self.hash_vec[vec_index],
+        );
+        self.set_entry(vec_index, entry);
+        old_entry
+    }
+}
+
+impl<T> PartialEq for AdaptiveCuckooFilter<T> {
+    fn eq(&self, other: &AdaptiveCuckooFilter<T>) -> bool {
+        self.max_kicks == other.max_kicks
+            && self.entries_per_index == other.entries_per_index
+            && self.fingerprint_vec == other.fingerprint_vec
+            && self.selector_vec == other.selector_vec
+            && self.hash_vec == other.hash_vec
+            && self.extra_items == other.extra_items
+            && self.hash_builders == other.hash_builders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdaptiveCuckooFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2};
+
+    #[test]
+    fn test_new() {
+        let filter =
+            AdaptiveCuckooFilter::<String>::with_hashers(100, [hash_builder_1(), hash_builder_2()]);
+
+        assert_eq!(filter.len(), 0);
+        assert!(filter.is_empty());
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.bucket_len(), 32);
+        assert_eq!(filter.fingerprint_bit_count(), 8);
+        assert_eq!(filter.entries_per_index(), 4);
+        assert_eq!(filter.bits_per_entry(), 74);
+        assert_eq!(filter.filter_bits_per_entry(), 10);
+    }
+
+    #[test]
+    fn test_from_parameters() {
+        let filter = AdaptiveCuckooFilter::<String>::from_parameters(100, 16, 8);
+
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.bucket_len(), 16);
+        assert_eq!(filter.fingerprint_bit_count(), 16);
+        assert_eq!(filter.entries_per_index(), 8);
+        assert_eq!(filter.bits_per_entry(), 82);
+        assert_eq!(filter.filter_bits_per_entry(), 18);
+    }
+
+    #[test]
+    fn test_from_entries_per_index() {
+        let filter = AdaptiveCuckooFilter::<String>::from_entries_per_index(100, 0.01, 4);
+
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.fingerprint_bit_count(), 11);
+        assert_eq!(filter.entries_per_index(), 4);
+    }
+
+    #[test]
+    fn test_from_fingerprint_bit_count() {
+        let filter = AdaptiveCuckooFilter::<String>::from_fingerprint_bit_count(100, 0.01, 10);
+
+        assert_eq!(filter.capacity(), 160);
+        assert_eq!(filter.fingerprint_bit_count(), 10);
+        assert_eq!(filter.entries_per_index(), 5);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+        filter.insert("foo");
+
+        assert_eq!(filter.len(), 1);
+        assert!(!filter.is_empty());
+        assert!(filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_insert_existing_item() {
+        let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+        filter.insert("foo");
+        filter.insert("foo");
+
+        assert_eq!(filter.len(), 1);
+        assert!(filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_insert_extra_items() {
+        let mut filter = AdaptiveCuckooFilter::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.insert("foobar");
+
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.extra_items_len(), 1);
+        assert!(filter.is_nearly_full());
+        assert!(filter.contains("foo"));
+        assert!(filter.contains("foobar"));
+
+        filter.remove("foo");
+        filter.remove("foobar");
+
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+        filter.insert("foo");
+        filter.remove("foo");
+
+        assert!(filter.is_empty());
+        assert!(!filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_remove_false_positive() {
+        let mut filter = AdaptiveCuckooFilter::<u32>::from_parameters_with_hashers(
+            1,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..4 {
+            filter.insert(&item);
+        }
+
+        let false_positive = (4..).find(|item| filter.contains(item)).unwrap();
+        filter.remove(&false_positive);
+
+        assert_eq!(filter.len(), 4);
+        assert!((0..4).all(|item| filter.contains(&item)));
+    }
+
+    #[test]
+    fn test_adapt() {
+        let mut filter = AdaptiveCuckooFilter::<u32>::from_parameters_with_hashers(
+            1,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..4 {
+            filter.insert(&item);
+        }
+
+        let false_positive = (4..).find(|item| filter.contains(item)).unwrap();
+        assert!(filter.adapt(&false_positive));
+        assert!(!filter.contains(&false_positive));
+        assert!((0..4).all(|item| filter.contains(&item)));
+    }
+
+    #[test]
+    fn test_adapt_inserted_item() {
+        let mut filter = AdaptiveCuckooFilter::<String>::new(100);
+        filter.insert("foo");
+
+        assert!(!filter.adapt("foo"));
+        assert!(!filter.adapt("bar"));
+        assert!(filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_adapt_extra_items() {
+        let mut filter = AdaptiveCuckooFilter::<u32>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..8 {
+            filter.insert(&item);
+        }
+        assert_eq!(filter.extra_items_len(), 7);
+
+        let false_positive = (8..).find(|item| filter.contains(item)).unwrap();
+        assert!(filter.adapt(&false_positive));
+        assert!(!filter.contains(&false_positive));
+        assert!((0..8).all(|item| filter.contains(&item)));
+    }
+
+    #[test]
+    fn test_adapt_false_positives() {
+        let mut filter = AdaptiveCuckooFilter::<u32>::from_parameters_with_hashers_and_seed(
+            1 << 10,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+            0,
+        );
+        for item in 0..900 {
+            filter.insert(&item);
+        }
+
+        let queries = 1000..21000;
+        let false_positives: Vec<u32> = queries
+            .clone()
+            .filter(|item| filter.contains(item))
+            .collect();
+        for item in &false_positives {
+            filter.adapt(item);
+        }
+
+        // displacements after adapting must not lose any items
+        for item in 900..950 {
+            filter.insert(&item);
+        }
+        assert!((0..950).all(|item| filter.contains(&item)));
+
+        let repeated_false_positives = false_positives
+            .iter()
+            .filter(|item| filter.contains(*item))
+            .count();
+        assert!(repeated_false_positives * 4 < false_positives.len());
+
+        for item in 0..950 {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut filter = AdaptiveCuckooFilter::<String>::from_parameters_with_hashers(
+            2,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foobar");
+        filter.insert("barfoo");
+        filter.insert("baz");
+        filter.insert("qux");
+
+        filter.clear();
+
+        assert!(filter.is_empty());
+        assert!(!filter.contains("baz"));
+        assert!(!filter.contains("qux"));
+        assert!(!filter.contains("foobar"));
+        assert!(!filter.contains("barfoo"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = AdaptiveCuckooFilter::<String>::from_entries_per_index(100, 0.01, 4);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: AdaptiveCuckooFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert!(filter == de_filter);
+    }
+}
@@ -0,0 +1,1154 @@
+use crate::cuckoo::{
+    self, FingerprintAndIndexes, DEFAULT_ENTRIES_PER_INDEX, DEFAULT_FINGERPRINT_BIT_COUNT,
+    DEFAULT_MAX_KICKS,
+};
+use crate::SipHasherBuilder;
+use rand::Rng;
+use std::borrow::Borrow;
+use std::cmp;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// The maximum number of times an insertion searches for a new displacement path after a
+// concurrent operation invalidated the previous one.
+const MAX_PATH_ATTEMPTS: usize = 16;
+
+// The number of displacement counters. Each pair of buckets shares a counter with the other pairs
+// whose smaller index is the same modulo this value.
+const DISPLACEMENT_COUNT_LEN: usize = 256;
+
+/// A space-efficient probabilistic data structure to test for membership in a set that can be
+/// shared between threads. Concurrent cuckoo filters also provide the flexibility to remove items.
+///
+/// A concurrent cuckoo filter is a cuckoo filter whose buckets are each packed into a single
+/// atomic 64-bit word, so every entry in a bucket is read and updated at once without locks.
+/// Queries read two words and never block. Insertions first search for a path of displacements
+/// that ends in a bucket with an empty entry without modifying the cuckoo filter, and then move
+/// the fingerprints along the path starting from its end. If a concurrent operation changes a
+/// bucket on the path, the insertion searches for a new path.
+///
+/// Each fingerprint is copied to its alternate bucket before it is removed from its current
+/// bucket, and a displacement counter shared by the two buckets is incremented in between. A
+/// query that reads the first bucket before the copy and the second bucket after the removal
+/// observes the counter change and reads both buckets again, so concurrent queries do not miss a
+/// fingerprint that is being displaced.
+///
+/// Fingerprints that cannot be inserted are stored in an unbounded stash that is protected by a
+/// lock. The stash is only locked by queries when it is not empty.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let filter = Arc::new(ConcurrentCuckooFilter::<u32>::new(1000));
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|thread_index| {
+///         let filter = Arc::clone(&filter);
+///         thread::spawn(move || {
+///             for item in thread_index * 100..(thread_index + 1) * 100 {
+///                 filter.insert(&item);
+///             }
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+///
+/// assert!(filter.contains(&0));
+/// assert!(filter.contains(&399));
+///
+/// filter.remove(&0);
+/// assert!(!filter.contains(&0));
+///
+/// assert_eq!(filter.capacity(), 1024);
+/// assert_eq!(filter.bucket_len(), 256);
+/// assert_eq!(filter.fingerprint_bit_count(), 8);
+/// ```
+#[derive(Debug)]
+pub struct ConcurrentCuckooFilter<T, B = SipHasherBuilder> {
+    max_kicks: usize,
+    entries_per_index: usize,
+    fingerprint_bit_count: usize,
+    bucket_vec: Vec<AtomicU64>,
+    displacement_counts: Vec<AtomicUsize>,
+    extra_items: Mutex<Vec<(u64, usize)>>,
+    extra_items_len: AtomicUsize,
+    hash_builders: [B; 2],
+    _marker: PhantomData<T>,
+}
+
+impl<T> ConcurrentCuckooFilter<T> {
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`. By default, the concurrent cuckoo filter will have 8 bits per item
+    /// fingerprint, 4 entries per index, and a maximum of 512 item displacements before
+    /// terminating the insertion process. The concurrent cuckoo filter will have an estimated
+    /// maximum false positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    /// ```
+    pub fn new(item_count: usize) -> Self {
+        Self::with_hashers(
+            item_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, a fingerprint bit count of `fingerprint_bit_count`, `entries_per_index`
+    /// entries per index, and a maximum of 512 item displacements before terminating the
+    /// insertion process. This method provides no guarantees on the false positive probability of
+    /// the concurrent cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1, if `entries_per_index`
+    /// is 0, or if the fingerprints of a bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_parameters(100, 16, 4);
+    /// ```
+    pub fn from_parameters(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+    ) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`,
+    /// `entries_per_index` entries per index, and a maximum of 512 item displacements before
+    /// terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `entries_per_index` is 0, or if the fingerprints of a
+    /// bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_entries_per_index(100, 0.01, 4);
+    /// ```
+    pub fn from_entries_per_index(item_count: usize, fpp: f64, entries_per_index: usize) -> Self {
+        Self::from_entries_per_index_with_hashers(
+            item_count,
+            fpp,
+            entries_per_index,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`, a fingerprint bit
+    /// count of `fingerprint_bit_count`, and a maximum of 512 item displacements before
+    /// terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// if it is impossible to achieve the given maximum false positive probability, or if the
+    /// fingerprints of a bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_fingerprint_bit_count(100, 0.01, 10);
+    /// ```
+    pub fn from_fingerprint_bit_count(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+    ) -> Self {
+        Self::from_fingerprint_bit_count_with_hashers(
+            item_count,
+            fpp,
+            fingerprint_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> ConcurrentCuckooFilter<T, B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, and two hasher builders for double hashing. By default, the concurrent cuckoo
+    /// filter will have 8 bits per item fingerprint, 4 entries per index, and a maximum of 512
+    /// item displacements before terminating the insertion process. The concurrent cuckoo filter
+    /// will have an estimated maximum false positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::with_hashers(
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(item_count: usize, hash_builders: [B; 2]) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            DEFAULT_FINGERPRINT_BIT_COUNT,
+            DEFAULT_ENTRIES_PER_INDEX,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, a fingerprint bit count of `fingerprint_bit_count`, `entries_per_index`
+    /// entries per index, a maximum of 512 item displacements before terminating the insertion
+    /// process, and two hasher builders for double hashing. This method provides no guarantees on
+    /// the false positive probability of the concurrent cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1, if `entries_per_index`
+    /// is 0, or if the fingerprints of a bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_parameters_with_hashers(
+    ///     100,
+    ///     16,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(item_count > 0 && fingerprint_bit_count > 1 && entries_per_index > 0);
+        assert!(fingerprint_bit_count * entries_per_index <= 64);
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
+        ConcurrentCuckooFilter {
+            max_kicks: DEFAULT_MAX_KICKS,
+            entries_per_index,
+            fingerprint_bit_count,
+            bucket_vec: (0..bucket_len).map(|_| AtomicU64::new(0)).collect(),
+            displacement_counts: (0..DISPLACEMENT_COUNT_LEN)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            extra_items: Mutex::new(Vec::new()),
+            extra_items_len: AtomicUsize::new(0),
+            hash_builders,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`,
+    /// `entries_per_index` entries per index, a maximum of 512 item displacements before
+    /// terminating the insertion process, and two hasher builders for double hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `entries_per_index` is 0, or if the fingerprints of a
+    /// bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_entries_per_index_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_entries_per_index_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(entries_per_index > 0);
+        Self::from_parameters_with_hashers(
+            item_count,
+            cuckoo::get_fingerprint_bit_count(fpp, entries_per_index),
+            entries_per_index,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `ConcurrentCuckooFilter` with an estimated max capacity of
+    /// `item_count`, an estimated maximum false positive probability of `fpp`, a fingerprint bit
+    /// count of `fingerprint_bit_count`, a maximum of 512 item displacements before terminating
+    /// the insertion process, and two hasher builders for double hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// if it is impossible to achieve the given maximum false positive probability, or if the
+    /// fingerprints of a bucket do not fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_fingerprint_bit_count_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     10,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_fingerprint_bit_count_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(fingerprint_bit_count > 1 && fingerprint_bit_count <= 64);
+        let entries_per_index = cuckoo::get_entries_per_index(fpp, fingerprint_bit_count);
+        assert!(entries_per_index > 0);
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            hash_builders,
+        )
+    }
+
+    fn get_alternate_index(&self, fingerprint: u64, index: usize) -> usize {
+        cuckoo::get_alternate_index(
+            &self.hash_builders[1],
+            fingerprint,
+            index,
+            self.bucket_len(),
+        )
+    }
+
+    fn get_fingerprint_and_indexes<U>(&self, item: &U) -> (u64, usize, usize)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let FingerprintAndIndexes {
+            fingerprint,
+            index_1,
+            index_2,
+        } = cuckoo::get_fingerprint_and_indexes(
+            &self.hash_builders,
+            cuckoo::hash_item(&self.hash_builders, item),
+            self.fingerprint_bit_count,
+            self.bucket_len(),
+            self.bucket_len(),
+        );
+        (fingerprint, index_1, index_2)
+    }
+
+    #[inline]
+    fn get_entry(&self, bucket: u64, bucket_index: usize) -> u64 {
+        let trailing_zeros = 64 - self.fingerprint_bit_count;
+        bucket >> (bucket_index * self.fingerprint_bit_count) << trailing_zeros >> trailing_zeros
+    }
+
+    #[inline]
+    fn set_entry(&self, bucket: u64, bucket_index: usize, fingerprint: u64) -> u64 {
+        let offset = bucket_index * self.fingerprint_bit_count;
+        let mask = u64::MAX >> (64 - self.fingerprint_bit_count) << offset;
+        (bucket & !mask) | (fingerprint << offset)
+    }
+
+    fn bucket_contains(&self, index: usize, fingerprint: u64) -> bool {
+        let bucket = self.bucket_vec[index].load(Ordering::Acquire);
+        (0..self.entries_per_index)
+            .any(|bucket_index| self.get_entry(bucket, bucket_index) == fingerprint)
+    }
+
+    #[inline]
+    fn get_displacement_count(&self, index_1: usize, index_2: usize) -> &AtomicUsize {
+        &self.displacement_counts[cmp::min(index_1, index_2) % DISPLACEMENT_COUNT_LEN]
+    }
+
+    // Atomically replaces the first entry in a bucket that satisfies `predicate` with
+    // `fingerprint`. Returns the position of the replaced entry in the bucket, or `None` if there
+    // is no such entry.
+    fn replace_entry<P>(&self, index: usize, fingerprint: u64, predicate: P) -> Option<usize>
+    where
+        P: Fn(usize, u64) -> bool,
+    {
+        let mut bucket = self.bucket_vec[index].load(Ordering::Acquire);
+        loop {
+            let bucket_index = (0..self.entries_per_index).find(|bucket_index| {
+                predicate(*bucket_index, self.get_entry(bucket, *bucket_index))
+            })?;
+            let new_bucket = self.set_entry(bucket, bucket_index, fingerprint);
+            match self.bucket_vec[index].compare_exchange_weak(
+                bucket,
+                new_bucket,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(bucket_index),
+                Err(current_bucket) => bucket = current_bucket,
+            }
+        }
+    }
+
+    // Searches for a path of displacements that starts at one of the two indexes and ends at a
+    // bucket with an empty entry. Each step of the path is an index, an entry in the bucket at
+    // that index, and the fingerprint in the entry.
+    fn find_path<R>(
+        &self,
+        index_1: usize,
+        index_2: usize,
+        rng: &mut R,
+    ) -> Option<Vec<(usize, usize, u64)>>
+    where
+        R: Rng,
+    {
+        let mut index = if rng.gen::<bool>() { index_1 } else { index_2 };
+        let mut path = Vec::new();
+        for _ in 0..self.max_kicks {
+            let bucket_index = rng.gen_range(0, self.entries_per_index);
+            let bucket = self.bucket_vec[index].load(Ordering::Acquire);
+            let fingerprint = self.get_entry(bucket, bucket_index);
+            if fingerprint == 0 {
+                return Some(path);
+            }
+            path.push((index, bucket_index, fingerprint));
+            index = self.get_alternate_index(fingerprint, index);
+            if self.bucket_contains(index, 0) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    // Moves the fingerprints along a path starting from its end. Returns `false` if a concurrent
+    // operation changed a bucket on the path.
+    fn move_path(&self, path: &[(usize, usize, u64)]) -> bool {
+        for &(index, bucket_index, fingerprint) in path.iter().rev() {
+            let alternate_index = self.get_alternate_index(fingerprint, index);
+            let alternate_bucket_index =
+                match self.replace_entry(alternate_index, fingerprint, |_, entry| entry == 0) {
+                    Some(alternate_bucket_index) => alternate_bucket_index,
+                    None => return false,
+                };
+
+            // queries that read both buckets while the fingerprint is only in the alternate
+            // bucket see the incremented count and read the buckets again
+            self.get_displacement_count(index, alternate_index)
+                .fetch_add(1, Ordering::AcqRel);
+            let is_moved = self
+                .replace_entry(index, 0, |entry_index, entry| {
+                    entry_index == bucket_index && entry == fingerprint
+                })
+                .is_some();
+            if !is_moved {
+                // the fingerprint was removed or displaced by another thread, so the copy in the
+                // entry written above is removed again
+                self.replace_entry(alternate_index, 0, |entry_index, entry| {
+                    entry_index == alternate_bucket_index && entry == fingerprint
+                });
+                return false;
+            }
+        }
+        true
+    }
+
+    fn insert_fingerprint(&self, fingerprint: u64, index_1: usize, index_2: usize) {
+        let mut rng = rand::thread_rng();
+        for _ in 0..MAX_PATH_ATTEMPTS {
+            if self
+                .replace_entry(index_1, fingerprint, |_, entry| entry == 0)
+                .is_some()
+                || self
+                    .replace_entry(index_2, fingerprint, |_, entry| entry == 0)
+                    .is_some()
+            {
+                return;
+            }
+            match self.find_path(index_1, index_2, &mut rng) {
+                Some(path) => {
+                    self.move_path(&path);
+                }
+                None => break,
+            }
+        }
+
+        let mut extra_items = self
+            .extra_items
+            .lock()
+            .expect("Expected lock to not be poisoned.");
+        extra_items.push((fingerprint, cmp::min(index_1, index_2)));
+        self.extra_items_len
+            .store(extra_items.len(), Ordering::Release);
+    }
+
+    /// Inserts an element into the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.check_and_insert(item);
+    }
+
+    /// Inserts an element into the concurrent cuckoo filter and returns `true` if it is distinct.
+    /// The element is distinct if it was definitely not in the concurrent cuckoo filter before
+    /// the insertion.
+    ///
+    /// If several threads insert the same item at the same time, more than one of them may report
+    /// that the item is distinct and store its fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
+    /// ```
+    pub fn check_and_insert<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        if self.contains_fingerprint(fingerprint, index_1, index_2) {
+            return false;
+        }
+        self.insert_fingerprint(fingerprint, index_1, index_2);
+        true
+    }
+
+    /// Removes an element from the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    ///
+    /// filter.remove("foo");
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn remove<U>(&self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        if self.extra_items_len.load(Ordering::Acquire) > 0 {
+            let mut extra_items = self
+                .extra_items
+                .lock()
+                .expect("Expected lock to not be poisoned.");
+            let min_index = cmp::min(index_1, index_2);
+            if let Some(index) = extra_items
+                .iter()
+                .position(|item| *item == (fingerprint, min_index))
+            {
+                extra_items.swap_remove(index);
+                self.extra_items_len
+                    .store(extra_items.len(), Ordering::Release);
+            }
+        }
+        while self
+            .replace_entry(index_1, 0, |_, entry| entry == fingerprint)
+            .is_some()
+        {}
+        while self
+            .replace_entry(index_2, 0, |_, entry| entry == fingerprint)
+            .is_some()
+        {}
+    }
+
+    /// Checks if an element is possibly in the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        self.contains_fingerprint(fingerprint, index_1, index_2)
+    }
+
+    fn contains_fingerprint(&self, fingerprint: u64, index_1: usize, index_2: usize) -> bool {
+        // a fingerprint can be displaced between the two buckets after the first bucket is read
+        // and before the second bucket is read, which changes the displacement count
+        let displacement_count = self.get_displacement_count(index_1, index_2);
+        loop {
+            let count = displacement_count.load(Ordering::Acquire);
+            if self.bucket_contains(index_1, fingerprint)
+                || self.bucket_contains(index_2, fingerprint)
+            {
+                return true;
+            }
+            if displacement_count.load(Ordering::Acquire) == count {
+                break;
+            }
+        }
+        self.extra_items_len.load(Ordering::Acquire) > 0
+            && self
+                .extra_items
+                .lock()
+                .expect("Expected lock to not be poisoned.")
+                .contains(&(fingerprint, cmp::min(index_1, index_2)))
+    }
+
+    /// Clears the concurrent cuckoo filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let mut filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        for bucket in &mut self.bucket_vec {
+            *bucket.get_mut() = 0;
+        }
+        self.extra_items
+            .get_mut()
+            .expect("Expected lock to not be poisoned.")
+            .clear();
+        *self.extra_items_len.get_mut() = 0;
+    }
+
+    /// Returns the number of occupied entries in the concurrent cuckoo filter. It does not account
+    /// for items in the extra items vector. This method reads every bucket, and fingerprints that
+    /// are being displaced by concurrent insertions may be counted twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert_eq!(filter.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.bucket_vec
+            .iter()
+            .map(|bucket| {
+                let bucket = bucket.load(Ordering::Acquire);
+                (0..self.entries_per_index)
+                    .filter(|bucket_index| self.get_entry(bucket, *bucket_index) != 0)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Returns `true` if there are no occupied entries in the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum capacity of the concurrent cuckoo filter. Items stay spill into the
+    /// extra items vector even through the length of the concurrent cuckoo filter is less than
+    /// the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.capacity(), 128);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.bucket_len() * self.entries_per_index
+    }
+
+    /// Returns the length of each bucket in the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.bucket_len(), 32);
+    /// ```
+    pub fn bucket_len(&self) -> usize {
+        self.bucket_vec.len()
+    }
+
+    /// Returns the number of entries per index in the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.entries_per_index(), 4);
+    /// ```
+    pub fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    /// Returns the number of items that could not be inserted into the concurrent cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_parameters_with_hashers(
+    ///     1,
+    ///     8,
+    ///     1,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foobar");
+    /// assert_eq!(filter.extra_items_len(), 1);
+    /// ```
+    pub fn extra_items_len(&self) -> usize {
+        self.extra_items_len.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if there are any items that could not be inserted into the concurrent cuckoo
+    /// filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::from_parameters(1, 8, 1);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foobar");
+    /// assert!(filter.is_nearly_full());
+    /// ```
+    pub fn is_nearly_full(&self) -> bool {
+        self.extra_items_len() > 0
+    }
+
+    /// Returns the number of bits in each item fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_bit_count
+    }
+
+    /// Returns the estimated false positive probability of the concurrent cuckoo filter. This
+    /// value will increase as more items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fingerprints_count = 2.0f64.powi(self.fingerprint_bit_count() as i32);
+        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
+        let occupied_ratio = self.len() as f64 / self.capacity() as f64;
+        1.0 - single_fpp.powf(2.0 * self.entries_per_index() as f64 * occupied_ratio)
+    }
+
+    /// Returns a reference to the concurrent cuckoo filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ConcurrentCuckooFilter;
+    ///
+    /// let filter = ConcurrentCuckooFilter::<String>::new(100);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        &self.hash_builders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcurrentCuckooFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const THREAD_COUNT: u32 = 8;
+    const THREAD_ITEM_COUNT: u32 = 1900;
+    const ROUND_COUNT: usize = 8;
+
+    fn new_filter() -> Arc<ConcurrentCuckooFilter<u32>> {
+        Arc::new(ConcurrentCuckooFilter::from_parameters_with_hashers(
+            (THREAD_COUNT * 2048) as usize,
+            16,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        ))
+    }
+
+    fn spawn_threads<F>(filter: &Arc<ConcurrentCuckooFilter<u32>>, f: F)
+    where
+        F: Fn(&ConcurrentCuckooFilter<u32>, u32) + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let handles: Vec<_> = (0..THREAD_COUNT)
+            .map(|thread_index| {
+                let filter = Arc::clone(filter);
+                let f = Arc::clone(&f);
+                thread::spawn(move || f(&filter, thread_index))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    fn thread_items(thread_index: u32) -> std::ops::Range<u32> {
+        thread_index * THREAD_ITEM_COUNT..(thread_index + 1) * THREAD_ITEM_COUNT
+    }
+
+    #[test]
+    fn test_new() {
+        let filter = ConcurrentCuckooFilter::<String>::new(100);
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.bucket_len(), 32);
+        assert_eq!(filter.entries_per_index(), 4);
+        assert_eq!(filter.fingerprint_bit_count(), 8);
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_from_entries_per_index() {
+        let filter = ConcurrentCuckooFilter::<String>::from_entries_per_index(100, 0.01, 4);
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.entries_per_index(), 4);
+        assert_eq!(filter.fingerprint_bit_count(), 11);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_parameters_wide_bucket() {
+        ConcurrentCuckooFilter::<String>::from_parameters(100, 16, 5);
+    }
+
+    #[test]
+    fn test_insert() {
+        let filter = ConcurrentCuckooFilter::<String>::with_hashers(
+            100,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        assert!(filter.check_and_insert("foo"));
+        assert!(!filter.check_and_insert("foo"));
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.len(), 1);
+        assert!(!filter.is_empty());
+    }
+
+    #[test]
+    fn test_insert_extra_items() {
+        let filter = ConcurrentCuckooFilter::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.insert("foobar");
+
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.extra_items_len(), 1);
+        assert!(filter.is_nearly_full());
+        assert!(filter.contains("foo"));
+        assert!(filter.contains("foobar"));
+
+        filter.remove("foo");
+        filter.remove("foobar");
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+    }
+
+    #[test]
+    fn test_remove() {
+        let filter = ConcurrentCuckooFilter::<String>::with_hashers(
+            100,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.remove("foo");
+
+        assert_eq!(filter.len(), 0);
+        assert!(!filter.contains("foo"));
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut filter = ConcurrentCuckooFilter::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.insert("foobar");
+        filter.clear();
+
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+        assert!(!filter.contains("foo"));
+        assert!(!filter.contains("foobar"));
+    }
+
+    #[test]
+    fn test_displacements() {
+        let filter = new_filter();
+        for item in 0..THREAD_COUNT * THREAD_ITEM_COUNT {
+            filter.insert(&item);
+        }
+        for item in 0..THREAD_COUNT * THREAD_ITEM_COUNT {
+            assert!(filter.contains(&item));
+        }
+        assert!(
+            filter.len() + filter.extra_items_len() <= (THREAD_COUNT * THREAD_ITEM_COUNT) as usize
+        );
+    }
+
+    #[test]
+    fn test_move_path_invalidated() {
+        let filter = ConcurrentCuckooFilter::<u32>::from_parameters_with_hashers(
+            128,
+            16,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let fingerprint = 1;
+        let index = 0;
+        let alternate_index = filter.get_alternate_index(fingerprint, index);
+        assert_ne!(index, alternate_index);
+
+        // the alternate bucket already has the same fingerprint of another item, and the
+        // fingerprint on the path was removed before it could be moved
+        filter.replace_entry(alternate_index, fingerprint, |_, entry| entry == 0);
+        assert!(!filter.move_path(&[(index, 0, fingerprint)]));
+
+        let bucket = filter.bucket_vec[alternate_index].load(Ordering::Acquire);
+        assert_eq!(filter.get_entry(bucket, 0), fingerprint);
+        assert_eq!(filter.get_entry(bucket, 1), 0);
+        assert_eq!(filter.len(), 1);
+        assert_eq!(
+            filter
+                .get_displacement_count(index, alternate_index)
+                .load(Ordering::Acquire),
+            1,
+        );
+    }
+
+    #[test]
+    fn test_concurrent_insert() {
+        let filter = new_filter();
+        spawn_threads(&filter, |filter, thread_index| {
+            for item in thread_items(thread_index) {
+                filter.insert(&item);
+            }
+        });
+
+        for item in 0..THREAD_COUNT * THREAD_ITEM_COUNT {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_insert_and_contains() {
+        let filter = new_filter();
+        for item in thread_items(0) {
+            filter.insert(&item);
+        }
+
+        // the items of the first thread are queried while the other threads insert items and
+        // displace their fingerprints
+        let finished_thread_count = Arc::new(AtomicU32::new(0));
+        spawn_threads(&filter, move |filter, thread_index| {
+            if thread_index == 0 {
+                while finished_thread_count.load(Ordering::Acquire) < THREAD_COUNT - 1 {
+                    for item in thread_items(0) {
+                        assert!(filter.contains(&item));
+                    }
+                }
+            } else {
+                for _ in 0..ROUND_COUNT {
+                    for item in thread_items(thread_index) {
+                        filter.insert(&item);
+                    }
+                    for item in thread_items(thread_index) {
+                        filter.remove(&item);
+                    }
+                }
+                for item in thread_items(thread_index) {
+                    filter.insert(&item);
+                }
+                finished_thread_count.fetch_add(1, Ordering::Release);
+            }
+        });
+
+        for item in 0..THREAD_COUNT * THREAD_ITEM_COUNT {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_insert_and_remove() {
+        let filter = new_filter();
+        spawn_threads(&filter, |filter, thread_index| {
+            for _ in 0..ROUND_COUNT {
+                for item in thread_items(thread_index) {
+                    filter.insert(&item);
+                }
+                for item in thread_items(thread_index) {
+                    filter.remove(&item);
+                }
+            }
+        });
+
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+    }
+}
@@ -1,8 +1,10 @@
-use crate::bitstring_vec::BitstringVec;
-use crate::cuckoo::{DEFAULT_ENTRIES_PER_INDEX, DEFAULT_FINGERPRINT_BIT_COUNT, DEFAULT_MAX_KICKS};
-use crate::util;
-use crate::SipHasherBuilder;
-use rand::{Rng, SeedableRng};
+use crate::cuckoo::fingerprint_vec::{FingerprintVec, SEMI_SORTED_ENTRIES_PER_INDEX};
+use crate::cuckoo::{
+    self, Buckets, FingerprintAndIndexes, DEFAULT_ENTRIES_PER_INDEX, DEFAULT_FINGERPRINT_BIT_COUNT,
+    DEFAULT_MAX_KICKS,
+};
+use crate::{FilterFullError, InsertError, SipHasherBuilder};
+use rand::SeedableRng;
 use rand_xorshift::XorShiftRng;
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
@@ -10,11 +12,33 @@ use std::borrow::Borrow;
 use std::cmp;
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::mem;
 
-struct FingerprintAndIndexes {
-    fingerprint: u64,
-    index_1: usize,
-    index_2: usize,
+/// The behavior of a cuckoo filter when an item cannot be inserted after the maximum number of
+/// item displacements.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub enum OverflowPolicy {
+    /// Stores the evicted fingerprint in a stash that holds at most the given number of
+    /// fingerprints. The stash is scanned on every query. Insertions fail once the stash is full.
+    Stash(usize),
+    /// Fails the insertion.
+    Reject,
+    /// Doubles the number of buckets in the cuckoo filter and reinserts the evicted fingerprint.
+    /// Each growth uses one more bit of every fingerprint to select its bucket, which doubles the
+    /// false positive probability. Insertions fail once the fingerprints have no more bits to
+    /// use.
+    Grow,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        OverflowPolicy::Stash(usize::MAX)
+    }
 }
 
 /// A space-efficient probabilistic data structure to test for membership in a set. Cuckoo filters
@@ -24,6 +48,17 @@ struct FingerprintAndIndexes {
 /// each keys' fingerprint. Cuckoo filters can be highly compact and serve as an improvement over
 /// variations of tradition Bloom filters that support deletion (E.G. counting Bloom filters).
 ///
+/// When an item cannot be inserted after the maximum number of item displacements, the cuckoo
+/// filter follows its `OverflowPolicy`. By default, evicted fingerprints are stored in an
+/// unbounded stash.
+///
+/// By default, a cuckoo filter stores each fingerprint at most once, so inserting an item whose
+/// fingerprint is already in one of its buckets has no effect, and removing an item clears every
+/// copy of its fingerprint. Removing an item can then remove another item whose fingerprint
+/// collided with it. A cuckoo filter in multiset mode stores duplicate fingerprints instead, up to
+/// `2 * entries_per_index` copies per item, and removing an item removes exactly one copy. Further
+/// insertions of an item that already has the maximum number of copies have no effect.
+///
 /// # Examples
 ///
 /// ```
@@ -43,7 +78,7 @@ struct FingerprintAndIndexes {
 /// assert_eq!(filter.bucket_len(), 32);
 /// assert_eq!(filter.fingerprint_bit_count(), 8);
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 #[cfg_attr(
     feature = "serde",
     derive(Deserialize, Serialize),
@@ -52,10 +87,12 @@ struct FingerprintAndIndexes {
 pub struct CuckooFilter<T, B = SipHasherBuilder> {
     max_kicks: usize,
     entries_per_index: usize,
-    fingerprint_vec: BitstringVec,
+    fingerprint_vec: FingerprintVec,
     pub(super) extra_items: Vec<(u64, usize)>,
+    overflow_policy: OverflowPolicy,
+    is_multiset: bool,
+    growth_count: usize,
     hash_builders: [B; 2],
-    #[cfg_attr(feature = "serde", serde(skip, default = "XorShiftRng::from_entropy"))]
     rng: XorShiftRng,
     _marker: PhantomData<T>,
 }
@@ -187,6 +224,42 @@ impl<T> CuckooFilter<T> {
             ],
         )
     }
+
+    /// Constructs a new, empty `CuckooFilter` with semi-sorted buckets, an estimated max capacity
+    /// of `item_count`, a fingerprint bit count of `fingerprint_bit_count`, 4 entries per index,
+    /// and a maximum of 512 item displacements before terminating the insertion process.
+    ///
+    /// The fingerprints in each bucket are sorted by their highest four bits, which are encoded
+    /// together in 12 bits instead of 16. Each entry uses one bit less than the fingerprint bit
+    /// count without changing the false positive probability of the cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, or if `fingerprint_bit_count` is less than 5 or greater than
+    /// 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 9);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 9);
+    /// assert_eq!(filter.bits_per_entry(), 8);
+    /// ```
+    pub fn from_parameters_semi_sorted(item_count: usize, fingerprint_bit_count: usize) -> Self {
+        Self::from_parameters_semi_sorted_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
 }
 
 impl<T, B> CuckooFilter<T, B>
@@ -218,22 +291,52 @@ where
     /// ```
     pub fn with_hashers(item_count: usize, hash_builders: [B; 2]) -> Self {
         assert!(item_count > 0);
-        let bucket_len = ((item_count + DEFAULT_ENTRIES_PER_INDEX - 1) / DEFAULT_ENTRIES_PER_INDEX)
-            .next_power_of_two();
+        let bucket_len = cuckoo::get_bucket_len(item_count, DEFAULT_ENTRIES_PER_INDEX);
         CuckooFilter {
             max_kicks: DEFAULT_MAX_KICKS,
             entries_per_index: DEFAULT_ENTRIES_PER_INDEX,
-            fingerprint_vec: BitstringVec::new(
+            fingerprint_vec: FingerprintVec::new(
                 DEFAULT_FINGERPRINT_BIT_COUNT,
                 bucket_len * DEFAULT_ENTRIES_PER_INDEX,
             ),
             extra_items: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            is_multiset: false,
+            growth_count: 0,
             hash_builders,
             rng: XorShiftRng::from_entropy(),
             _marker: PhantomData,
         }
     }
 
+    /// Constructs a new, empty `CuckooFilter` with an estimated max capacity of `item_count`, two
+    /// hasher builders for double hashing, and a seed for the random number generator used to
+    /// displace items. Filters constructed with the same parameters and seed behave identically
+    /// when given the same sequence of operations. By default, the cuckoo filter will have a
+    /// fingerprint bit count of 8, 4 entries per index, and a maximum of 512 item displacements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CuckooFilter::<String>::with_hashers_and_seed(
+    ///     100,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(item_count: usize, hash_builders: [B; 2], seed: u64) -> Self {
+        let mut filter = Self::with_hashers(item_count, hash_builders);
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
     /// Constructs a new, empty `CuckooFilter` with an estimated max capacity of `item_count`, a
     /// fingerprint bit count of `fingerprint_bit_count`, `entries_per_index` entries per index, a
     /// maximum of 512 item displacements before terminating the insertion process, and two hasher
@@ -272,16 +375,116 @@ where
                 && fingerprint_bit_count <= 64
                 && entries_per_index > 0
         );
-        let exact_bucket_len = (item_count + entries_per_index - 1) / entries_per_index;
-        let bucket_len = exact_bucket_len.next_power_of_two();
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
         CuckooFilter {
             max_kicks: DEFAULT_MAX_KICKS,
             entries_per_index,
-            fingerprint_vec: BitstringVec::new(
+            fingerprint_vec: FingerprintVec::new(
                 fingerprint_bit_count,
                 bucket_len * entries_per_index,
             ),
             extra_items: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            is_multiset: false,
+            growth_count: 0,
+            hash_builders,
+            rng: XorShiftRng::from_entropy(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `CuckooFilter` with an estimated max capacity of `item_count`, a
+    /// fingerprint bit count of `fingerprint_bit_count`, `entries_per_index` entries per index, a
+    /// maximum of 512 item displacements before terminating the insertion process, two hasher
+    /// builders for double hashing, and a seed for the random number generator used to displace
+    /// items. Filters constructed with the same parameters and seed behave identically when given
+    /// the same sequence of operations. This method provides no guarantees on the false positive
+    /// probability of the cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, or
+    /// if `entries_per_index` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_with_hashers_and_seed(
+    ///     100,
+    ///     16,
+    ///     8,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers_and_seed(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut filter = Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            hash_builders,
+        );
+        filter.rng = XorShiftRng::seed_from_u64(seed);
+        filter
+    }
+
+    /// Constructs a new, empty `CuckooFilter` with semi-sorted buckets, an estimated max capacity
+    /// of `item_count`, a fingerprint bit count of `fingerprint_bit_count`, 4 entries per index, a
+    /// maximum of 512 item displacements before terminating the insertion process, and two hasher
+    /// builders for double hashing.
+    ///
+    /// The fingerprints in each bucket are sorted by their highest four bits, which are encoded
+    /// together in 12 bits instead of 16. Each entry uses one bit less than the fingerprint bit
+    /// count without changing the false positive probability of the cuckoo filter.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, or if `fingerprint_bit_count` is less than 5 or greater than
+    /// 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_semi_sorted_with_hashers(
+    ///     100,
+    ///     9,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_parameters_semi_sorted_with_hashers(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(item_count > 0);
+        let bucket_len = cuckoo::get_bucket_len(item_count, SEMI_SORTED_ENTRIES_PER_INDEX);
+        CuckooFilter {
+            max_kicks: DEFAULT_MAX_KICKS,
+            entries_per_index: SEMI_SORTED_ENTRIES_PER_INDEX,
+            fingerprint_vec: FingerprintVec::new_semi_sorted(
+                fingerprint_bit_count,
+                bucket_len * SEMI_SORTED_ENTRIES_PER_INDEX,
+            ),
+            extra_items: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            is_multiset: false,
+            growth_count: 0,
             hash_builders,
             rng: XorShiftRng::from_entropy(),
             _marker: PhantomData,
@@ -320,18 +523,19 @@ where
     ) -> Self {
         assert!(item_count > 0);
         assert!(entries_per_index > 0);
-        let power = 2.0 / (1.0 - (1.0 - fpp).powf(1.0 / (2.0 * entries_per_index as f64)));
-        let fingerprint_bit_count = power.log2().ceil() as usize;
-        let exact_bucket_len = (item_count + entries_per_index - 1) / entries_per_index;
-        let bucket_len = exact_bucket_len.next_power_of_two();
+        let fingerprint_bit_count = cuckoo::get_fingerprint_bit_count(fpp, entries_per_index);
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
         CuckooFilter {
             max_kicks: DEFAULT_MAX_KICKS,
             entries_per_index,
-            fingerprint_vec: BitstringVec::new(
+            fingerprint_vec: FingerprintVec::new(
                 fingerprint_bit_count,
                 bucket_len * entries_per_index,
             ),
             extra_items: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            is_multiset: false,
+            growth_count: 0,
             hash_builders,
             rng: XorShiftRng::from_entropy(),
             _marker: PhantomData,
@@ -371,59 +575,161 @@ where
     ) -> Self {
         assert!(item_count > 0);
         assert!(fingerprint_bit_count > 1 && fingerprint_bit_count <= 64);
-        let fingerprints_count = 2.0f64.powi(fingerprint_bit_count as i32);
-        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
-        let entries_per_index = ((1.0 - fpp).log(single_fpp) / 2.0).floor() as usize;
+        let entries_per_index = cuckoo::get_entries_per_index(fpp, fingerprint_bit_count);
         assert!(entries_per_index > 0);
-        let exact_bucket_len = (item_count + entries_per_index - 1) / entries_per_index;
-        let bucket_len = exact_bucket_len.next_power_of_two();
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
         CuckooFilter {
             max_kicks: DEFAULT_MAX_KICKS,
             entries_per_index,
-            fingerprint_vec: BitstringVec::new(
+            fingerprint_vec: FingerprintVec::new(
                 fingerprint_bit_count,
                 bucket_len * entries_per_index,
             ),
             extra_items: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            is_multiset: false,
+            growth_count: 0,
             hash_builders,
             rng: XorShiftRng::from_entropy(),
             _marker: PhantomData,
         }
     }
 
+    /// Constructs a `CuckooFilter` with an estimated max capacity of `item_count`, a fingerprint
+    /// bit count of `fingerprint_bit_count`, `entries_per_index` entries per index, and two hasher
+    /// builders for double hashing from `(bucket index, fingerprint)` pairs of another cuckoo
+    /// filter, such as the ones returned by [`CuckooFilter::fingerprints`]. The original items are
+    /// not needed, so this method can be used to move a cuckoo filter between processes or to
+    /// resize it.
+    ///
+    /// The other cuckoo filter must use the same hasher builders and the same fingerprint bit
+    /// count, and `initial_bucket_len` must be its [`CuckooFilter::initial_bucket_len`]. The
+    /// length of each bucket will be rounded off to the next power of two. If it is greater than
+    /// `initial_bucket_len`, the cuckoo filter behaves as if it has grown from
+    /// `initial_bucket_len`. Fingerprints that cannot be placed after the maximum number of item
+    /// displacements are stored in the extra items vector.
+    ///
+    /// Fingerprints are not truncated to `fingerprint_bit_count` bits, since an item whose
+    /// truncated fingerprint is 0 would have a different fingerprint in the new cuckoo filter.
+    /// Cuckoo filters with different fingerprint bit counts, such as the cuckoo filters of a
+    /// `ScalableCuckooFilter` with a tightening ratio less than 1, must be rebuilt separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, if
+    /// `entries_per_index` is 0, if `initial_bucket_len` is not a power of two, if every bit of
+    /// the fingerprints would be used to select a region, or if a fingerprint is 0 or does not
+    /// fit in `fingerprint_bit_count` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let hash_builders = [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)];
+    /// let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+    ///     1000,
+    ///     8,
+    ///     4,
+    ///     hash_builders,
+    /// );
+    /// filter.insert("foo");
+    ///
+    /// let resized_filter = CuckooFilter::<String>::from_fingerprints_with_hashers(
+    ///     10,
+    ///     filter.fingerprint_bit_count(),
+    ///     filter.entries_per_index(),
+    ///     filter.initial_bucket_len(),
+    ///     hash_builders,
+    ///     filter.fingerprints(),
+    /// );
+    /// assert!(resized_filter.contains("foo"));
+    /// assert_eq!(resized_filter.bucket_len(), 4);
+    /// ```
+    pub fn from_fingerprints_with_hashers<I>(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        initial_bucket_len: usize,
+        hash_builders: [B; 2],
+        fingerprints: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = (usize, u64)>,
+    {
+        assert!(initial_bucket_len.is_power_of_two());
+        let mut filter = Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            hash_builders,
+        );
+        let bucket_len = filter.bucket_len();
+        let region_len = cmp::min(bucket_len, initial_bucket_len);
+        filter.growth_count = (bucket_len / region_len).trailing_zeros() as usize;
+        assert!(filter.growth_count < fingerprint_bit_count);
+
+        // both indexes of a fingerprint are the same modulo any smaller power of two, so the
+        // bucket index within the region is kept and the region is selected by the fingerprint
+        for (index, fingerprint) in fingerprints {
+            assert_ne!(fingerprint, 0);
+            assert!(fingerprint.leading_zeros() as usize >= 64 - fingerprint_bit_count);
+            let region = (fingerprint & ((1 << filter.growth_count) - 1)) as usize;
+            let index = index % region_len + region * region_len;
+            let alternate_index = filter.get_alternate_index(fingerprint, index);
+            if let Some(evicted) =
+                filter.insert_entry_with_kicks(fingerprint, index, alternate_index)
+            {
+                filter.extra_items.push(evicted);
+            }
+        }
+        filter
+    }
+
+    #[inline]
+    fn region_len(&self) -> usize {
+        self.bucket_len() >> self.growth_count
+    }
+
+    fn get_alternate_index(&self, fingerprint: u64, index: usize) -> usize {
+        cuckoo::get_alternate_index(
+            &self.hash_builders[1],
+            fingerprint,
+            index,
+            self.region_len(),
+        )
+    }
+
     #[inline]
     fn get_vec_index(&self, index: usize, bucket_index: usize) -> usize {
         index * self.entries_per_index + bucket_index
     }
 
-    fn get_fingerprint_and_indexes<U>(&self, item: &U) -> FingerprintAndIndexes
+    pub(super) fn hash<U>(&self, item: &U) -> u128
     where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let trailing_zeros = 64 - self.fingerprint_bit_count();
-        let mut h0 = util::hash(&self.hash_builders[0], &item);
-        let mut fingerprint = h0 << trailing_zeros >> trailing_zeros;
-
-        // rehash when fingerprint is all 0s
-        while fingerprint == 0 {
-            h0 = util::hash(&self.hash_builders[0], &h0.wrapping_add(1));
-            fingerprint = h0 << trailing_zeros >> trailing_zeros;
-        }
-
-        let h1 = util::hash(&self.hash_builders[1], &item);
-        let hashed_fingerprint = util::hash(&self.hash_builders[1], &fingerprint);
+        cuckoo::hash_item(&self.hash_builders, item)
+    }
 
-        let index_1 = h1 as usize % self.bucket_len();
-        let index_2 = (index_1 ^ hashed_fingerprint as usize) % self.bucket_len();
-        FingerprintAndIndexes {
-            fingerprint,
-            index_1,
-            index_2,
-        }
+    fn get_fingerprint_and_indexes(&self, hash: u128) -> FingerprintAndIndexes {
+        cuckoo::get_fingerprint_and_indexes(
+            &self.hash_builders,
+            hash,
+            self.fingerprint_bit_count(),
+            self.bucket_len(),
+            self.region_len(),
+        )
     }
 
-    /// Inserts an element into the cuckoo filter.
+    /// Inserts an element into the cuckoo filter. In multiset mode, the element is not inserted
+    /// again if its fingerprint already has `2 * entries_per_index` copies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element cannot be inserted under the overflow policy of the cuckoo filter.
     ///
     /// # Examples
     ///
@@ -438,115 +744,67 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let fingerprint_and_indexes = self.get_fingerprint_and_indexes(item);
-        if !self.contains_fingerprint(&fingerprint_and_indexes) {
-            let FingerprintAndIndexes {
-                mut fingerprint,
-                index_1,
-                index_2,
-            } = fingerprint_and_indexes;
-            if self.insert_fingerprint(fingerprint, index_1) {
-                return;
-            }
-
-            if self.insert_fingerprint(fingerprint, index_2) {
-                return;
-            }
-
-            // have to kick out an entry
-            let mut index = if self.rng.gen::<bool>() {
-                index_1
-            } else {
-                index_2
-            };
-            let mut prev_index = index;
-
-            for _ in 0..self.max_kicks {
-                let bucket_index = self.rng.gen_range(0, self.entries_per_index);
-                let vec_index = self.get_vec_index(index, bucket_index);
-                let new_fingerprint = self.fingerprint_vec.get(vec_index);
-                self.fingerprint_vec.set(vec_index, fingerprint);
-                fingerprint = new_fingerprint;
-                let hashed_fingerprint = util::hash(&self.hash_builders[1], &fingerprint);
-                prev_index = index;
-                index = (prev_index ^ hashed_fingerprint as usize) % self.bucket_len();
-                if self.insert_fingerprint(fingerprint, index) {
-                    return;
-                }
-            }
-
-            self.extra_items
-                .push((fingerprint, cmp::min(prev_index, index)));
-        }
-    }
-
-    pub(super) fn insert_fingerprint(&mut self, fingerprint: u64, index: usize) -> bool {
-        let entries_per_index = self.entries_per_index;
-        for bucket_index in 0..entries_per_index {
-            let vec_index = self.get_vec_index(index, bucket_index);
-            let is_empty = self.fingerprint_vec.get(vec_index) == 0;
-            if is_empty {
-                self.fingerprint_vec.set(vec_index, fingerprint);
-                return true;
-            }
-        }
-        false
+        self.check_and_insert(item);
     }
 
-    /// Removes an element from the cuckoo filter.
+    /// Inserts an element into the cuckoo filter, following the overflow policy of the cuckoo
+    /// filter if the element cannot be inserted after the maximum number of item displacements.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InsertError::Full` if the element cannot be inserted under the overflow policy.
+    /// Every fingerprint that was displaced is moved back, so the cuckoo filter is unchanged and
+    /// the error contains the fingerprint of the element.
+    ///
+    /// In multiset mode, returns `InsertError::TooManyCopies` if the fingerprint of the element
+    /// already has `2 * entries_per_index` copies. The cuckoo filter is unchanged.
     ///
     /// # Examples
     ///
     /// ```
-    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, OverflowPolicy};
     ///
-    /// let mut filter = CuckooFilter::<String>::new(100);
-    ///
-    /// filter.insert("foo");
-    /// assert!(filter.contains("foo"));
+    /// let mut filter = CuckooFilter::<u32>::from_parameters(1, 8, 1);
+    /// filter.set_overflow_policy(OverflowPolicy::Reject);
     ///
-    /// filter.remove("foo");
-    /// assert!(!filter.contains("foo"));
+    /// assert!(filter.try_insert(&0).is_ok());
+    /// assert!((1..10).any(|item| filter.try_insert(&item).is_err()));
     /// ```
-    pub fn remove<U>(&mut self, item: &U)
+    pub fn try_insert<U>(&mut self, item: &U) -> Result<(), InsertError>
     where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.remove_fingerprint(&self.get_fingerprint_and_indexes(item));
+        self.try_check_and_insert_hash(self.hash(item)).map(|_| ())
     }
 
-    fn remove_fingerprint(&mut self, fingerprint_and_indexes: &FingerprintAndIndexes) {
-        let FingerprintAndIndexes {
-            fingerprint,
-            index_1,
-            index_2,
-        } = *fingerprint_and_indexes;
-        let min_index = cmp::min(index_1, index_2);
-        let entries_per_index = self.entries_per_index;
-        if let Some(index) = self
-            .extra_items
-            .iter()
-            .position(|item| *item == (fingerprint, min_index))
-        {
-            self.extra_items.swap_remove(index);
-        }
-        for bucket_index in 0..entries_per_index {
-            let vec_index_1 = self.get_vec_index(index_1, bucket_index);
-            let fingerprint_1 = self.fingerprint_vec.get(vec_index_1);
-            if fingerprint_1 == fingerprint {
-                self.fingerprint_vec.set(vec_index_1, 0);
-            } else {
-                let vec_index_2 = self.get_vec_index(index_2, bucket_index);
-                let fingerprint_2 = self.fingerprint_vec.get(vec_index_2);
-                if fingerprint_2 == fingerprint {
-                    self.fingerprint_vec.set(vec_index_2, 0);
-                }
-            }
-        }
+    /// Inserts an element into the cuckoo filter using its precomputed 128-bit hash. The upper 64
+    /// bits of `hash` determine the fingerprint and the lower 64 bits determine the first index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element cannot be inserted under the overflow policy of the cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        self.check_and_insert_hash(hash);
     }
 
-    /// Checks if an element is possibly in the cuckoo filter.
+    /// Inserts an element into the cuckoo filter and returns `true` if it is distinct. The element
+    /// is distinct if it was definitely not in the cuckoo filter before the insertion. In
+    /// multiset mode, the element is not inserted again if its fingerprint already has
+    /// `2 * entries_per_index` copies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element cannot be inserted under the overflow policy of the cuckoo filter.
     ///
     /// # Examples
     ///
@@ -555,22 +813,300 @@ where
     ///
     /// let mut filter = CuckooFilter::<String>::new(100);
     ///
-    /// filter.insert("foo");
-    /// assert!(filter.contains("foo"));
+    /// assert!(filter.check_and_insert("foo"));
+    /// assert!(!filter.check_and_insert("foo"));
     /// ```
-    pub fn contains<U>(&self, item: &U) -> bool
+    pub fn check_and_insert<U>(&mut self, item: &U) -> bool
     where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.contains_fingerprint(&self.get_fingerprint_and_indexes(item))
+        self.check_and_insert_hash(self.hash(item))
     }
 
-    fn contains_fingerprint(&self, fingerprint_and_indexes: &FingerprintAndIndexes) -> bool {
-        let FingerprintAndIndexes {
-            fingerprint,
-            index_1,
-            index_2,
+    pub(super) fn check_and_insert_hash(&mut self, hash: u128) -> bool {
+        match self.try_check_and_insert_hash(hash) {
+            Ok(is_distinct) => is_distinct,
+            Err(InsertError::TooManyCopies) => false,
+            Err(InsertError::Full(_)) => panic!("Expected cuckoo filter to not be full."),
+        }
+    }
+
+    fn try_check_and_insert_hash(&mut self, hash: u128) -> Result<bool, InsertError> {
+        let fingerprint_and_indexes = self.get_fingerprint_and_indexes(hash);
+        if !self.is_multiset {
+            if self.contains_fingerprint(&fingerprint_and_indexes) {
+                return Ok(false);
+            }
+            self.insert_fingerprint_and_indexes(fingerprint_and_indexes)?;
+            return Ok(true);
+        }
+
+        // both buckets of an item can hold at most `2 * entries_per_index` copies of its
+        // fingerprint, and more copies would only be displaced between them
+        let count = self.count_fingerprint(&fingerprint_and_indexes);
+        if count >= 2 * self.entries_per_index {
+            return Err(InsertError::TooManyCopies);
+        }
+        self.insert_fingerprint_and_indexes(fingerprint_and_indexes)?;
+        Ok(count == 0)
+    }
+
+    // Inserts a fingerprint into the cuckoo filter under its overflow policy. If the fingerprint
+    // cannot be stored, every displacement is undone so that the cuckoo filter is unchanged.
+    fn insert_fingerprint_and_indexes(
+        &mut self,
+        fingerprint_and_indexes: FingerprintAndIndexes,
+    ) -> Result<(), FilterFullError> {
+        let FingerprintAndIndexes {
+            fingerprint,
+            mut index_1,
+            mut index_2,
+        } = fingerprint_and_indexes;
+
+        loop {
+            let mut path = Vec::new();
+            let (evicted_fingerprint, evicted_index) =
+                match self.insert_entry_with_path(fingerprint, index_1, index_2, &mut path) {
+                    Some(evicted) => evicted,
+                    None => return Ok(()),
+                };
+
+            match self.overflow_policy {
+                OverflowPolicy::Stash(max_len) if self.extra_items.len() < max_len => {
+                    self.extra_items.push((evicted_fingerprint, evicted_index));
+                    return Ok(());
+                }
+                OverflowPolicy::Grow if self.can_grow() => {
+                    // the fingerprint is inserted again from the start since its indexes move
+                    // when the cuckoo filter grows
+                    self.undo_kicks(evicted_fingerprint, &path);
+                    let bucket_len = self.bucket_len();
+                    let region_bit = (fingerprint >> self.growth_count) & 1;
+                    self.grow();
+                    index_1 += region_bit as usize * bucket_len;
+                    index_2 = self.get_alternate_index(fingerprint, index_1);
+                }
+                _ => {
+                    self.undo_kicks(evicted_fingerprint, &path);
+                    return Err(FilterFullError::new(
+                        fingerprint,
+                        cmp::min(index_1, index_2),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn can_grow(&self) -> bool {
+        self.growth_count + 1 < self.fingerprint_bit_count()
+    }
+
+    /// Doubles the number of buckets in the cuckoo filter without access to the original items.
+    /// The buckets of an item are confined to a region of the initial bucket length, and each
+    /// growth uses one more bit of the fingerprint to select that region. Each fingerprint either
+    /// stays in its bucket or moves to the corresponding bucket in the new half of the cuckoo
+    /// filter, and fingerprints in the stash are moved into the buckets if there is room.
+    ///
+    /// Since the bits used to select a region no longer distinguish fingerprints in the same
+    /// region, each growth doubles the false positive probability for the same fill ratio.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every bit of the fingerprints except one is already used to select a region.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::from_parameters(100, 16, 4);
+    /// filter.insert("foo");
+    ///
+    /// filter.grow();
+    /// assert_eq!(filter.bucket_len(), 64);
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn grow(&mut self) {
+        assert!(self.can_grow());
+        let bucket_len = self.bucket_len();
+        let mut fingerprint_vec = self
+            .fingerprint_vec
+            .with_len(2 * bucket_len * self.entries_per_index);
+        for vec_index in 0..self.fingerprint_vec.len() {
+            let fingerprint = self.fingerprint_vec.get(vec_index);
+            if fingerprint != 0 {
+                // semi-sorted buckets may reorder fingerprints, so each fingerprint is placed in
+                // the first empty entry of its new bucket
+                let region_bit = (fingerprint >> self.growth_count) & 1;
+                let index = vec_index / self.entries_per_index + region_bit as usize * bucket_len;
+                let new_vec_index = (0..self.entries_per_index)
+                    .map(|bucket_index| self.get_vec_index(index, bucket_index))
+                    .find(|new_vec_index| fingerprint_vec.get(*new_vec_index) == 0)
+                    .expect("Expected bucket to have an empty entry.");
+                fingerprint_vec.set(new_vec_index, fingerprint);
+            }
+        }
+        for (fingerprint, index) in &mut self.extra_items {
+            let region_bit = (*fingerprint >> self.growth_count) & 1;
+            *index += region_bit as usize * bucket_len;
+        }
+        self.fingerprint_vec = fingerprint_vec;
+        self.growth_count += 1;
+
+        for (fingerprint, index) in mem::take(&mut self.extra_items) {
+            let alternate_index = self.get_alternate_index(fingerprint, index);
+            if let Some(evicted) = self.insert_entry_with_kicks(fingerprint, index, alternate_index)
+            {
+                self.extra_items.push(evicted);
+            }
+        }
+    }
+
+    pub(super) fn insert_fingerprint(&mut self, fingerprint: u64, index: usize) -> bool {
+        let entries_per_index = self.entries_per_index;
+        for bucket_index in 0..entries_per_index {
+            let vec_index = self.get_vec_index(index, bucket_index);
+            let is_empty = self.fingerprint_vec.get(vec_index) == 0;
+            if is_empty {
+                self.fingerprint_vec.set(vec_index, fingerprint);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Removes an element from the cuckoo filter and returns `true` if its fingerprint was in the
+    /// cuckoo filter. In multiset mode, exactly one copy of the fingerprint is removed. Otherwise,
+    /// every copy of the fingerprint in the buckets of the element is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    ///
+    /// assert!(filter.remove("foo"));
+    /// assert!(!filter.contains("foo"));
+    /// assert!(!filter.remove("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.remove_hash(self.hash(item))
+    }
+
+    /// Removes an element from the cuckoo filter using its precomputed 128-bit hash and returns
+    /// `true` if its fingerprint was in the cuckoo filter. The upper 64 bits of `hash` determine
+    /// the fingerprint and the lower 64 bits determine the first index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    ///
+    /// assert!(filter.remove_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn remove_hash(&mut self, hash: u128) -> bool {
+        self.remove_fingerprint(&self.get_fingerprint_and_indexes(hash))
+    }
+
+    fn remove_fingerprint(&mut self, fingerprint_and_indexes: &FingerprintAndIndexes) -> bool {
+        let FingerprintAndIndexes {
+            fingerprint,
+            index_1,
+            index_2,
+        } = *fingerprint_and_indexes;
+        let min_index = cmp::min(index_1, index_2);
+        let entries_per_index = self.entries_per_index;
+        let mut is_removed = false;
+        if let Some(index) = self
+            .extra_items
+            .iter()
+            .position(|item| *item == (fingerprint, min_index))
+        {
+            self.extra_items.swap_remove(index);
+            if self.is_multiset {
+                return true;
+            }
+            is_removed = true;
+        }
+        for bucket_index in 0..entries_per_index {
+            let vec_index_1 = self.get_vec_index(index_1, bucket_index);
+            let fingerprint_1 = self.fingerprint_vec.get(vec_index_1);
+            if fingerprint_1 == fingerprint {
+                self.fingerprint_vec.set(vec_index_1, 0);
+                is_removed = true;
+            } else {
+                let vec_index_2 = self.get_vec_index(index_2, bucket_index);
+                let fingerprint_2 = self.fingerprint_vec.get(vec_index_2);
+                if fingerprint_2 == fingerprint {
+                    self.fingerprint_vec.set(vec_index_2, 0);
+                    is_removed = true;
+                }
+            }
+            if is_removed && self.is_multiset {
+                return true;
+            }
+        }
+        is_removed
+    }
+
+    /// Checks if an element is possibly in the cuckoo filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.contains_hash(self.hash(item))
+    }
+
+    /// Checks if an element is possibly in the cuckoo filter using its precomputed 128-bit hash.
+    /// The upper 64 bits of `hash` determine the fingerprint and the lower 64 bits determine the
+    /// first index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.contains_fingerprint(&self.get_fingerprint_and_indexes(hash))
+    }
+
+    fn contains_fingerprint(&self, fingerprint_and_indexes: &FingerprintAndIndexes) -> bool {
+        let FingerprintAndIndexes {
+            fingerprint,
+            index_1,
+            index_2,
             ..
         } = *fingerprint_and_indexes;
         let min_index = cmp::min(index_1, index_2);
@@ -586,6 +1122,106 @@ where
         })
     }
 
+    /// Returns the number of copies of the fingerprint of an element in the cuckoo filter. In
+    /// multiset mode, this is the number of times the element was inserted and not removed, plus
+    /// the number of copies of other elements whose fingerprints collide with it. Otherwise, it
+    /// is at most one unless the cuckoo filter was constructed from duplicate fingerprints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    /// filter.set_multiset(true);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foo");
+    /// assert_eq!(filter.count("foo"), 2);
+    ///
+    /// filter.remove("foo");
+    /// assert_eq!(filter.count("foo"), 1);
+    /// ```
+    pub fn count<U>(&self, item: &U) -> usize
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.count_hash(self.hash(item))
+    }
+
+    /// Returns the number of copies of the fingerprint of an element in the cuckoo filter using
+    /// its precomputed 128-bit hash. The upper 64 bits of `hash` determine the fingerprint and the
+    /// lower 64 bits determine the first index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    /// filter.set_multiset(true);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert_eq!(filter.count_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210), 2);
+    /// ```
+    pub fn count_hash(&self, hash: u128) -> usize {
+        self.count_fingerprint(&self.get_fingerprint_and_indexes(hash))
+    }
+
+    fn count_fingerprint(&self, fingerprint_and_indexes: &FingerprintAndIndexes) -> usize {
+        let FingerprintAndIndexes {
+            fingerprint,
+            index_1,
+            index_2,
+        } = *fingerprint_and_indexes;
+        let min_index = cmp::min(index_1, index_2);
+        let count_bucket = |index| {
+            (0..self.entries_per_index)
+                .filter(|bucket_index| {
+                    let vec_index = self.get_vec_index(index, *bucket_index);
+                    self.fingerprint_vec.get(vec_index) == fingerprint
+                })
+                .count()
+        };
+        let extra_items_count = self
+            .extra_items
+            .iter()
+            .filter(|item| **item == (fingerprint, min_index))
+            .count();
+
+        // both indexes are the same if the hash of the fingerprint is a multiple of the region length
+        if index_1 == index_2 {
+            count_bucket(index_1) + extra_items_count
+        } else {
+            count_bucket(index_1) + count_bucket(index_2) + extra_items_count
+        }
+    }
+
+    /// Returns an iterator over the fingerprints in the cuckoo filter as `(bucket index,
+    /// fingerprint)` pairs. The fingerprints in the buckets are visited first, followed by the
+    /// fingerprints in the extra items vector with the smaller of their two bucket indexes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("bar");
+    /// assert_eq!(filter.fingerprints().count(), 2);
+    /// ```
+    pub fn fingerprints(&self) -> Fingerprints<'_, T, B> {
+        Fingerprints {
+            filter: self,
+            vec_index: 0,
+            extra_items_index: 0,
+        }
+    }
+
     /// Clears the cuckoo filter, removing all elements.
     ///
     /// # Examples
@@ -667,6 +1303,24 @@ where
         self.fingerprint_vec.len() / self.entries_per_index
     }
 
+    /// Returns the length of each bucket in the cuckoo filter before it grew. Both indexes of an
+    /// item are in the same region of this length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    ///
+    /// filter.grow();
+    /// assert_eq!(filter.bucket_len(), 64);
+    /// assert_eq!(filter.initial_bucket_len(), 32);
+    /// ```
+    pub fn initial_bucket_len(&self) -> usize {
+        self.region_len()
+    }
+
     /// Returns the number of entries per index in the cuckoo filter.
     ///
     /// # Examples
@@ -722,45 +1376,39 @@ where
         !self.extra_items.is_empty()
     }
 
-    /// Returns the number of bits in each item fingerprint.
+    /// Returns the overflow policy of the cuckoo filter.
     ///
     /// # Examples
     ///
     /// ```
-    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, OverflowPolicy};
     ///
     /// let filter = CuckooFilter::<String>::new(100);
     ///
-    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// assert_eq!(filter.overflow_policy(), OverflowPolicy::Stash(usize::MAX));
     /// ```
-    pub fn fingerprint_bit_count(&self) -> usize {
-        self.fingerprint_vec.bit_count()
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
     }
 
-    /// Returns the estimated false positive probability of the cuckoo filter. This value will
-    /// increase as more items are added.
+    /// Sets the overflow policy of the cuckoo filter. Fingerprints that are already in the stash
+    /// are kept even if they exceed the maximum size of the new policy's stash.
     ///
     /// # Examples
     ///
     /// ```
-    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, OverflowPolicy};
     ///
     /// let mut filter = CuckooFilter::<String>::new(100);
-    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    /// filter.set_overflow_policy(OverflowPolicy::Grow);
     ///
-    /// filter.insert("foo");
-    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
-    /// assert!(filter.estimated_fpp() < 0.01);
+    /// assert_eq!(filter.overflow_policy(), OverflowPolicy::Grow);
     /// ```
-    pub fn estimated_fpp(&self) -> f64 {
-        let fingerprints_count = 2.0f64.powi(self.fingerprint_bit_count() as i32);
-        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
-        let occupied_len = self.fingerprint_vec.occupied_len();
-        let occupied_ratio = occupied_len as f64 / self.capacity() as f64;
-        1.0 - single_fpp.powf(2.0 * self.entries_per_index() as f64 * occupied_ratio)
+    pub fn set_overflow_policy(&mut self, overflow_policy: OverflowPolicy) {
+        self.overflow_policy = overflow_policy;
     }
 
-    /// Returns a reference to the cuckoo filter's hasher builders.
+    /// Returns `true` if the cuckoo filter is in multiset mode.
     ///
     /// # Examples
     ///
@@ -768,30 +1416,270 @@ where
     /// use probabilistic_collections::cuckoo::CuckooFilter;
     ///
     /// let filter = CuckooFilter::<String>::new(100);
-    /// let hashers = filter.hashers();
+    ///
+    /// assert!(!filter.is_multiset());
     /// ```
-    pub fn hashers(&self) -> &[B; 2] {
-        &self.hash_builders
+    pub fn is_multiset(&self) -> bool {
+        self.is_multiset
     }
-}
-
-impl<T> PartialEq for CuckooFilter<T> {
-    fn eq(&self, other: &CuckooFilter<T>) -> bool {
-        self.max_kicks == other.max_kicks
-            && self.entries_per_index == other.entries_per_index
-            && self.fingerprint_vec == other.fingerprint_vec
-            && self.extra_items == other.extra_items
-            && self.hash_builders == other.hash_builders
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::CuckooFilter;
-    use crate::util::tests::{hash_builder_1, hash_builder_2};
 
-    #[test]
-    fn test_new() {
+    /// Sets whether the cuckoo filter is in multiset mode. In multiset mode, inserting an element
+    /// always stores another copy of its fingerprint, up to `2 * entries_per_index` copies, and
+    /// removing an element removes exactly one copy.
+    ///
+    /// The mode can only be changed while the cuckoo filter is empty, since fingerprints inserted
+    /// under one mode would not be removed correctly under the other.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cuckoo filter or its extra items vector is not empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    /// filter.set_multiset(true);
+    ///
+    /// filter.insert("foo");
+    /// filter.insert("foo");
+    /// assert_eq!(filter.len(), 2);
+    ///
+    /// assert!(filter.remove("foo"));
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn set_multiset(&mut self, is_multiset: bool) {
+        assert!(self.is_empty() && self.extra_items.is_empty());
+        self.is_multiset = is_multiset;
+    }
+
+    /// Returns the number of bits in each item fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::new(100);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_vec.bit_count()
+    }
+
+    /// Returns the number of bits used to store each entry of the cuckoo filter. This is one less
+    /// than the fingerprint bit count if the buckets are semi-sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::new(100);
+    /// assert_eq!(filter.bits_per_entry(), 8);
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 8);
+    /// assert_eq!(filter.bits_per_entry(), 7);
+    /// ```
+    pub fn bits_per_entry(&self) -> usize {
+        self.fingerprint_vec.bits_per_entry()
+    }
+
+    /// Returns the number of bits that semi-sorted buckets save compared to storing every entry in
+    /// `fingerprint_bit_count` bits. This is 0 if the buckets are not semi-sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::new(100);
+    /// assert_eq!(filter.saved_bit_count(), 0);
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 8);
+    /// assert_eq!(filter.saved_bit_count(), 128);
+    /// ```
+    pub fn saved_bit_count(&self) -> usize {
+        self.capacity() * (self.fingerprint_bit_count() - self.bits_per_entry())
+    }
+
+    /// Returns `true` if the fingerprints in each bucket of the cuckoo filter are semi-sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::new(100);
+    /// assert!(!filter.is_semi_sorted());
+    ///
+    /// let filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 8);
+    /// assert!(filter.is_semi_sorted());
+    /// ```
+    pub fn is_semi_sorted(&self) -> bool {
+        self.fingerprint_vec.is_semi_sorted()
+    }
+
+    /// Returns the estimated false positive probability of the cuckoo filter. This value will
+    /// increase as more items are added.
+    ///
+    /// The false positive probability only depends on the fingerprint bit count, so semi-sorted
+    /// buckets have the same false positive probability while storing one bit less per entry. The
+    /// saved space is reported by [`CuckooFilter::saved_bit_count`] and
+    /// [`CuckooFilter::bits_per_entry`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let mut filter = CuckooFilter::<String>::new(100);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.01);
+    ///
+    /// let mut semi_sorted_filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 8);
+    /// semi_sorted_filter.insert("foo");
+    /// assert!((semi_sorted_filter.estimated_fpp() - filter.estimated_fpp()).abs() < 1e-12);
+    /// assert_eq!(semi_sorted_filter.bits_per_entry(), 7);
+    /// assert_eq!(semi_sorted_filter.saved_bit_count(), 128);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        // bits used to select the region of a fingerprint do not distinguish it from others
+        let fingerprints_count =
+            2.0f64.powi((self.fingerprint_bit_count() - self.growth_count) as i32);
+        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
+        let occupied_len = self.fingerprint_vec.occupied_len();
+        let occupied_ratio = occupied_len as f64 / self.capacity() as f64;
+        1.0 - single_fpp.powf(2.0 * self.entries_per_index() as f64 * occupied_ratio)
+    }
+
+    /// Returns a reference to the cuckoo filter's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooFilter;
+    ///
+    /// let filter = CuckooFilter::<String>::new(100);
+    /// let hashers = filter.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        &self.hash_builders
+    }
+}
+
+impl<T, B> Buckets for CuckooFilter<T, B>
+where
+    B: BuildHasher,
+{
+    type Entry = u64;
+
+    fn max_kicks(&self) -> usize {
+        self.max_kicks
+    }
+
+    fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    fn entry_alternate_index(&self, fingerprint: u64, index: usize) -> usize {
+        self.get_alternate_index(fingerprint, index)
+    }
+
+    fn insert_entry(&mut self, fingerprint: u64, index: usize) -> bool {
+        self.insert_fingerprint(fingerprint, index)
+    }
+
+    fn replace_entry(&mut self, index: usize, bucket_index: usize, fingerprint: u64) -> u64 {
+        let vec_index = self.get_vec_index(index, bucket_index);
+        let old_fingerprint = self.fingerprint_vec.get(vec_index);
+        self.fingerprint_vec.set(vec_index, fingerprint);
+        old_fingerprint
+    }
+}
+
+// Implemented manually so that cloning a cuckoo filter does not require `T: Clone`.
+impl<T, B> Clone for CuckooFilter<T, B>
+where
+    B: Clone,
+{
+    fn clone(&self) -> Self {
+        CuckooFilter {
+            max_kicks: self.max_kicks,
+            entries_per_index: self.entries_per_index,
+            fingerprint_vec: self.fingerprint_vec.clone(),
+            extra_items: self.extra_items.clone(),
+            overflow_policy: self.overflow_policy,
+            is_multiset: self.is_multiset,
+            growth_count: self.growth_count,
+            hash_builders: self.hash_builders.clone(),
+            rng: self.rng.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for CuckooFilter<T> {
+    fn eq(&self, other: &CuckooFilter<T>) -> bool {
+        self.max_kicks == other.max_kicks
+            && self.entries_per_index == other.entries_per_index
+            && self.fingerprint_vec == other.fingerprint_vec
+            && self.extra_items == other.extra_items
+            && self.overflow_policy == other.overflow_policy
+            && self.is_multiset == other.is_multiset
+            && self.growth_count == other.growth_count
+            && self.hash_builders == other.hash_builders
+    }
+}
+
+/// An iterator over the fingerprints in a `CuckooFilter` as `(bucket index, fingerprint)` pairs.
+///
+/// This struct is created by [`CuckooFilter::fingerprints`].
+pub struct Fingerprints<'a, T, B> {
+    filter: &'a CuckooFilter<T, B>,
+    vec_index: usize,
+    extra_items_index: usize,
+}
+
+impl<'a, T, B> Iterator for Fingerprints<'a, T, B>
+where
+    B: BuildHasher,
+{
+    type Item = (usize, u64);
+
+    fn next(&mut self) -> Option<(usize, u64)> {
+        while self.vec_index < self.filter.fingerprint_vec.len() {
+            let vec_index = self.vec_index;
+            let fingerprint = self.filter.fingerprint_vec.get(vec_index);
+            self.vec_index += 1;
+            if fingerprint != 0 {
+                return Some((vec_index / self.filter.entries_per_index, fingerprint));
+            }
+        }
+        let (fingerprint, index) = *self.filter.extra_items.get(self.extra_items_index)?;
+        self.extra_items_index += 1;
+        Some((index, fingerprint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CuckooFilter, FingerprintAndIndexes, OverflowPolicy};
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+    use crate::InsertError;
+    use std::cmp;
+
+    #[test]
+    fn test_new() {
         let filter =
             CuckooFilter::<String>::with_hashers(100, [hash_builder_1(), hash_builder_2()]);
         assert_eq!(filter.len(), 0);
@@ -860,6 +1748,23 @@ mod tests {
         assert!(filter.contains("foo"));
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut filter =
+            CuckooFilter::<String>::with_hashers(100, [hash_builder_1(), hash_builder_2()]);
+
+        filter.insert_hash(hash_item("foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(hash_item("bar")));
+        assert!(!filter.contains_hash(hash_item("baz")));
+
+        filter.remove_hash(hash_item("foo"));
+        assert!(!filter.contains("foo"));
+        assert_eq!(filter.len(), 1);
+    }
+
     #[test]
     fn test_insert_existing_item() {
         let mut filter =
@@ -872,6 +1777,17 @@ mod tests {
         assert!(filter.contains("foo"));
     }
 
+    #[test]
+    fn test_check_and_insert() {
+        let mut filter =
+            CuckooFilter::<String>::with_hashers(100, [hash_builder_1(), hash_builder_2()]);
+
+        assert!(filter.check_and_insert("foo"));
+        assert!(!filter.check_and_insert("foo"));
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.len(), 1);
+    }
+
     #[test]
     fn test_insert_extra_items() {
         let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
@@ -893,6 +1809,358 @@ mod tests {
         assert!(filter.contains("foobar"));
     }
 
+    #[test]
+    fn test_try_insert_reject() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            8,
+            8,
+            2,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_overflow_policy(OverflowPolicy::Reject);
+
+        let mut inserted = Vec::new();
+        let mut failure_count = 0;
+        for item in 0..100 {
+            let prev_filter = filter.clone();
+            match filter.try_insert(&item) {
+                Ok(()) => inserted.push(item),
+                Err(InsertError::Full(error)) => {
+                    let FingerprintAndIndexes {
+                        fingerprint,
+                        index_1,
+                        index_2,
+                    } = filter.get_fingerprint_and_indexes(filter.hash(&item));
+                    assert_eq!(error.fingerprint(), fingerprint);
+                    assert_eq!(error.index(), cmp::min(index_1, index_2));
+                    assert_eq!(filter, prev_filter);
+                    failure_count += 1;
+                }
+                Err(InsertError::TooManyCopies) => unreachable!(),
+            }
+        }
+
+        assert!(failure_count > 0);
+        assert_eq!(filter.extra_items_len(), 0);
+        assert!(!filter.is_nearly_full());
+        assert!(inserted.iter().all(|item| filter.contains(item)));
+    }
+
+    #[test]
+    fn test_try_insert_stash() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            8,
+            8,
+            2,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_overflow_policy(OverflowPolicy::Stash(2));
+
+        let mut inserted = Vec::new();
+        let mut failure_count = 0;
+        for item in 0..100 {
+            let prev_filter = filter.clone();
+            match filter.try_insert(&item) {
+                Ok(()) => inserted.push(item),
+                Err(_) => {
+                    assert_eq!(filter, prev_filter);
+                    failure_count += 1;
+                }
+            }
+        }
+
+        assert!(failure_count > 0);
+        assert_eq!(filter.extra_items_len(), 2);
+        assert!(inserted.iter().all(|item| filter.contains(item)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_reject_full() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            8,
+            8,
+            2,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_overflow_policy(OverflowPolicy::Reject);
+
+        for item in 0..100 {
+            filter.insert(&item);
+        }
+    }
+
+    #[test]
+    fn test_try_insert_grow() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            8,
+            16,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_overflow_policy(OverflowPolicy::Grow);
+
+        for item in 0..1000 {
+            assert!(filter.try_insert(&item).is_ok());
+        }
+
+        assert_eq!(filter.extra_items_len(), 0);
+        assert!(filter.bucket_len() >= 256);
+        assert!(filter.estimated_fpp() < 0.5);
+        for item in 0..1000 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positive_count = (1000..11_000).filter(|item| filter.contains(item)).count();
+        assert!((false_positive_count as f64 / 10_000.0) < 2.0 * filter.estimated_fpp());
+
+        for item in 0..1000 {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            64,
+            16,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        for item in 0..100 {
+            filter.insert(&item);
+        }
+        assert!(filter.is_nearly_full());
+
+        let len = filter.len() + filter.extra_items_len();
+        filter.grow();
+        assert_eq!(filter.bucket_len(), 32);
+        assert_eq!(filter.extra_items_len(), 0);
+        assert_eq!(filter.len(), len);
+        for item in 0..100 {
+            assert!(filter.contains(&item));
+        }
+
+        for item in 100..200 {
+            filter.insert(&item);
+        }
+        filter.grow();
+        for item in 0..200 {
+            assert!(filter.contains(&item));
+        }
+        for item in 0..200 {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.insert("foo");
+        filter.insert("foobar");
+
+        let fingerprints: Vec<_> = filter.fingerprints().collect();
+        assert_eq!(fingerprints.len(), 2);
+        assert_eq!(
+            fingerprints[1],
+            (filter.extra_items[0].1, filter.extra_items[0].0)
+        );
+    }
+
+    #[test]
+    fn test_from_fingerprints() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            64,
+            16,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_overflow_policy(OverflowPolicy::Grow);
+        for item in 0..200 {
+            filter.insert(&item);
+        }
+        assert_eq!(filter.bucket_len(), 64);
+        assert_eq!(filter.initial_bucket_len(), 16);
+
+        for &item_count in &[32, 256, 1024] {
+            let rebuilt_filter = CuckooFilter::<u32>::from_fingerprints_with_hashers(
+                item_count,
+                filter.fingerprint_bit_count(),
+                filter.entries_per_index(),
+                filter.initial_bucket_len(),
+                [hash_builder_1(), hash_builder_2()],
+                filter.fingerprints(),
+            );
+            assert_eq!(
+                rebuilt_filter.len() + rebuilt_filter.extra_items_len(),
+                filter.len() + filter.extra_items_len(),
+            );
+            for item in 0..200 {
+                assert!(rebuilt_filter.contains(&item));
+            }
+        }
+
+        let mut rebuilt_filter = CuckooFilter::<u32>::from_fingerprints_with_hashers(
+            1024,
+            filter.fingerprint_bit_count(),
+            filter.entries_per_index(),
+            filter.initial_bucket_len(),
+            [hash_builder_1(), hash_builder_2()],
+            filter.fingerprints(),
+        );
+        assert_eq!(rebuilt_filter.initial_bucket_len(), 16);
+        rebuilt_filter.grow();
+        for item in 0..200 {
+            assert!(rebuilt_filter.contains(&item));
+            rebuilt_filter.remove(&item);
+        }
+        assert!(rebuilt_filter.is_empty());
+        assert!(!rebuilt_filter.is_nearly_full());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_fingerprints_no_bits() {
+        CuckooFilter::<u32>::from_fingerprints_with_hashers(
+            1024,
+            2,
+            4,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+            Vec::new(),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_fingerprints_wide_fingerprint() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            64,
+            12,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..64 {
+            filter.insert(&item);
+        }
+
+        CuckooFilter::<u32>::from_fingerprints_with_hashers(
+            64,
+            11,
+            4,
+            filter.initial_bucket_len(),
+            [hash_builder_1(), hash_builder_2()],
+            filter.fingerprints(),
+        );
+    }
+
+    #[test]
+    fn test_semi_sorted() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_semi_sorted_with_hashers(
+            128,
+            12,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        assert!(filter.is_semi_sorted());
+        assert_eq!(filter.capacity(), 128);
+        assert_eq!(filter.entries_per_index(), 4);
+        assert_eq!(filter.fingerprint_bit_count(), 12);
+        assert_eq!(filter.bits_per_entry(), 11);
+        assert_eq!(filter.saved_bit_count(), 128);
+
+        for item in 0..120 {
+            filter.insert(&item);
+        }
+        assert_eq!(filter.len() + filter.extra_items_len(), 120);
+        assert!((0..120).all(|item| filter.contains(&item)));
+
+        for item in (0..120).step_by(2) {
+            filter.remove(&item);
+        }
+        assert_eq!(filter.len() + filter.extra_items_len(), 60);
+        assert!((1..120).step_by(2).all(|item| filter.contains(&item)));
+
+        for item in (1..120).step_by(2) {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_semi_sorted_grow() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_semi_sorted_with_hashers(
+            64,
+            16,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..100 {
+            filter.insert(&item);
+        }
+
+        let len = filter.len() + filter.extra_items_len();
+        filter.grow();
+        assert!(filter.is_semi_sorted());
+        assert_eq!(filter.bucket_len(), 32);
+        assert_eq!(filter.len() + filter.extra_items_len(), len);
+        assert!((0..100).all(|item| filter.contains(&item)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_semi_sorted_small_fingerprint() {
+        CuckooFilter::<u32>::from_parameters_semi_sorted(64, 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grow_no_bits() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+            64,
+            2,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        filter.grow();
+        filter.grow();
+    }
+
+    #[test]
+    fn test_seed() {
+        let mut filter_1 = CuckooFilter::<u32>::from_parameters_with_hashers_and_seed(
+            64,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+            0,
+        );
+        let mut filter_2 = CuckooFilter::<u32>::from_parameters_with_hashers_and_seed(
+            64,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+            0,
+        );
+
+        for item in 0..80 {
+            filter_1.insert(&item);
+            filter_2.insert(&item);
+        }
+
+        assert!(!filter_1.extra_items.is_empty());
+        assert_eq!(filter_1.fingerprint_vec, filter_2.fingerprint_vec);
+        assert_eq!(filter_1.extra_items, filter_2.extra_items);
+    }
+
     #[test]
     fn test_remove() {
         let mut filter =
@@ -952,6 +2220,141 @@ mod tests {
         assert!(!filter.contains("barfoo"));
     }
 
+    #[test]
+    fn test_multiset_insert() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            100,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_multiset(true);
+        assert!(filter.is_multiset());
+
+        assert_eq!(filter.count("foo"), 0);
+        assert!(filter.check_and_insert("foo"));
+        assert!(!filter.check_and_insert("foo"));
+        filter.insert("foo");
+        assert_eq!(filter.count("foo"), 3);
+        assert_eq!(filter.len(), 3);
+    }
+
+    #[test]
+    fn test_multiset_remove() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            100,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_multiset(true);
+
+        for _ in 0..8 {
+            filter.insert("foo");
+        }
+        for count in (0..8).rev() {
+            assert!(filter.remove("foo"));
+            assert_eq!(filter.count("foo"), count);
+            assert_eq!(filter.contains("foo"), count > 0);
+        }
+        assert!(!filter.remove("foo"));
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_multiset_limit() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            100,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_multiset(true);
+
+        for _ in 0..8 {
+            assert!(filter.try_insert("foo").is_ok());
+        }
+        assert_eq!(filter.try_insert("foo"), Err(InsertError::TooManyCopies));
+        assert_eq!(filter.count("foo"), 8);
+        assert_eq!(filter.extra_items_len(), 0);
+    }
+
+    #[test]
+    fn test_multiset_limit_saturate() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            100,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_multiset(true);
+
+        for _ in 0..20 {
+            filter.insert("foo");
+        }
+        assert!(!filter.check_and_insert("foo"));
+        assert_eq!(filter.count("foo"), 8);
+        assert_eq!(filter.len(), 8);
+        assert_eq!(filter.extra_items_len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_multiset_non_empty() {
+        let mut filter = CuckooFilter::<String>::from_parameters(100, 8, 4);
+        filter.insert("foo");
+        filter.set_multiset(true);
+    }
+
+    #[test]
+    fn test_multiset_collision() {
+        // both hashes have the same fingerprint and indexes
+        let hash_1 = 0x0123_4567_89ab_cd01_fedc_ba98_7654_3210;
+        let hash_2 = 0xfedc_ba98_7654_3201_fedc_ba98_7654_3210;
+
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            100,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.insert_hash(hash_1);
+        filter.insert_hash(hash_2);
+        assert_eq!(filter.count_hash(hash_1), 1);
+        assert!(filter.remove_hash(hash_1));
+        assert!(!filter.contains_hash(hash_2));
+
+        filter.set_multiset(true);
+        filter.insert_hash(hash_1);
+        filter.insert_hash(hash_2);
+        assert_eq!(filter.count_hash(hash_1), 2);
+        assert!(filter.remove_hash(hash_1));
+        assert!(filter.contains_hash(hash_2));
+        assert_eq!(filter.count_hash(hash_2), 1);
+    }
+
+    #[test]
+    fn test_multiset_extra_items() {
+        let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        filter.set_multiset(true);
+
+        filter.insert("foo");
+        filter.insert("foo");
+        assert_eq!(filter.count("foo"), 2);
+        assert_eq!(filter.len() + filter.extra_items_len(), 2);
+
+        assert!(filter.remove("foo"));
+        assert!(filter.remove("foo"));
+        assert!(!filter.contains("foo"));
+        assert!(filter.is_empty());
+        assert!(!filter.is_nearly_full());
+    }
+
     #[test]
     fn test_clear() {
         let mut filter = CuckooFilter::<String>::from_parameters_with_hashers(
@@ -1006,4 +2409,44 @@ mod tests {
         assert_eq!(filter.extra_items, de_filter.extra_items);
         assert_eq!(filter.hashers(), de_filter.hashers());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de_semi_sorted() {
+        let mut filter = CuckooFilter::<String>::from_parameters_semi_sorted(100, 8);
+        filter.insert("foo");
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: CuckooFilter<String> = bincode::deserialize(&serialized_filter).unwrap();
+
+        assert!(de_filter.contains("foo"));
+        assert!(de_filter.is_semi_sorted());
+        assert!(filter == de_filter);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de_rng() {
+        let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers_and_seed(
+            64,
+            8,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+            0,
+        );
+        for item in 0..40 {
+            filter.insert(&item);
+        }
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let mut de_filter: CuckooFilter<u32> = bincode::deserialize(&serialized_filter).unwrap();
+
+        for item in 40..80 {
+            filter.insert(&item);
+            de_filter.insert(&item);
+        }
+
+        assert_eq!(filter.fingerprint_vec, de_filter.fingerprint_vec);
+        assert_eq!(filter.extra_items, de_filter.extra_items);
+    }
 }
@@ -0,0 +1,1161 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::cuckoo::{
+    self, Buckets, FingerprintAndIndexes, DEFAULT_ENTRIES_PER_INDEX, DEFAULT_FINGERPRINT_BIT_COUNT,
+    DEFAULT_MAX_KICKS,
+};
+use crate::SipHasherBuilder;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::mem;
+
+/// A space-efficient probabilistic map from items to small values based on cuckoo hashing.
+///
+/// A cuckoo map is a cuckoo filter that stores a fixed-width value beside each fingerprint. Values
+/// are moved with their fingerprints when fingerprints are displaced, so looking up an item
+/// returns the value of its fingerprint. An item that is not in the map may share its fingerprint
+/// and indexes with an item that is, in which case the value of the other item is returned. This
+/// happens with the same probability as a false positive in a `CuckooFilter`.
+///
+/// Fingerprints that cannot be inserted after the maximum number of item displacements are stored
+/// with their values in an unbounded stash.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::cuckoo::CuckooMap;
+///
+/// let mut map = CuckooMap::<String>::new(100, 4);
+///
+/// assert_eq!(map.get("foo"), None);
+/// map.insert("foo", 3);
+/// assert_eq!(map.get("foo"), Some(3));
+///
+/// map.update("foo", 5);
+/// assert_eq!(map.get("foo"), Some(5));
+///
+/// assert_eq!(map.remove("foo"), Some(5));
+/// assert_eq!(map.get("foo"), None);
+///
+/// assert_eq!(map.capacity(), 128);
+/// assert_eq!(map.fingerprint_bit_count(), 8);
+/// assert_eq!(map.value_bit_count(), 4);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct CuckooMap<T, B = SipHasherBuilder> {
+    max_kicks: usize,
+    entries_per_index: usize,
+    fingerprint_vec: BitstringVec,
+    value_vec: BitstringVec,
+    extra_items: Vec<(u64, usize, u64)>,
+    hash_builders: [B; 2],
+    rng: XorShiftRng,
+    _marker: PhantomData<T>,
+}
+
+impl<T> CuckooMap<T> {
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count` and
+    /// values of `value_bit_count` bits. By default, the cuckoo map will have 8 bits per item
+    /// fingerprint, 4 entries per index, and a maximum of 512 item displacements before
+    /// terminating the insertion process. The cuckoo map will have an estimated maximum false
+    /// positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    /// ```
+    pub fn new(item_count: usize, value_bit_count: usize) -> Self {
+        Self::with_hashers(
+            item_count,
+            value_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, a
+    /// fingerprint bit count of `fingerprint_bit_count`, `entries_per_index` entries per index,
+    /// values of `value_bit_count` bits, and a maximum of 512 item displacements before
+    /// terminating the insertion process. This method provides no guarantees on the false
+    /// positive probability of the cuckoo map.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, if
+    /// `entries_per_index` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::from_parameters(100, 16, 8, 4);
+    /// ```
+    pub fn from_parameters(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        value_bit_count: usize,
+    ) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            value_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, an
+    /// estimated maximum false positive probability of `fpp`, `entries_per_index` entries per
+    /// index, values of `value_bit_count` bits, and a maximum of 512 item displacements before
+    /// terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `entries_per_index` is 0, or if `value_bit_count` is 0 or
+    /// greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::from_entries_per_index(100, 0.01, 4, 4);
+    /// ```
+    pub fn from_entries_per_index(
+        item_count: usize,
+        fpp: f64,
+        entries_per_index: usize,
+        value_bit_count: usize,
+    ) -> Self {
+        Self::from_entries_per_index_with_hashers(
+            item_count,
+            fpp,
+            entries_per_index,
+            value_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, an
+    /// estimated maximum false positive probability of `fpp`, a fingerprint bit count of
+    /// `fingerprint_bit_count`, values of `value_bit_count` bits, and a maximum of 512 item
+    /// displacements before terminating the insertion process.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// if it is impossible to achieve the given maximum false positive probability, or if
+    /// `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::from_fingerprint_bit_count(100, 0.01, 10, 4);
+    /// ```
+    pub fn from_fingerprint_bit_count(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+        value_bit_count: usize,
+    ) -> Self {
+        Self::from_fingerprint_bit_count_with_hashers(
+            item_count,
+            fpp,
+            fingerprint_bit_count,
+            value_bit_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<T, B> CuckooMap<T, B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, values
+    /// of `value_bit_count` bits, and two hasher builders for double hashing. By default, the
+    /// cuckoo map will have 8 bits per item fingerprint, 4 entries per index, and a maximum of 512
+    /// item displacements before terminating the insertion process. The cuckoo map will have an
+    /// estimated maximum false positive probability of 3%.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::with_hashers(
+    ///     100,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(item_count: usize, value_bit_count: usize, hash_builders: [B; 2]) -> Self {
+        Self::from_parameters_with_hashers(
+            item_count,
+            DEFAULT_FINGERPRINT_BIT_COUNT,
+            DEFAULT_ENTRIES_PER_INDEX,
+            value_bit_count,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, values
+    /// of `value_bit_count` bits, two hasher builders for double hashing, and a seed for the
+    /// random number generator used to displace items. Maps constructed with the same parameters
+    /// and seed behave identically when given the same sequence of operations. By default, the
+    /// cuckoo map will have a fingerprint bit count of 8, 4 entries per index, and a maximum of
+    /// 512 item displacements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::with_hashers_and_seed(
+    ///     100,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn with_hashers_and_seed(
+        item_count: usize,
+        value_bit_count: usize,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut map = Self::with_hashers(item_count, value_bit_count, hash_builders);
+        map.rng = XorShiftRng::seed_from_u64(seed);
+        map
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, a
+    /// fingerprint bit count of `fingerprint_bit_count`, `entries_per_index` entries per index,
+    /// values of `value_bit_count` bits, a maximum of 512 item displacements before terminating
+    /// the insertion process, and two hasher builders for double hashing. This method provides no
+    /// guarantees on the false positive probability of the cuckoo map.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, if
+    /// `entries_per_index` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::from_parameters_with_hashers(
+    ///     100,
+    ///     16,
+    ///     8,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        value_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(
+            item_count > 0
+                && fingerprint_bit_count > 1
+                && fingerprint_bit_count <= 64
+                && entries_per_index > 0
+                && value_bit_count > 0
+                && value_bit_count <= 64
+        );
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
+        CuckooMap {
+            max_kicks: DEFAULT_MAX_KICKS,
+            entries_per_index,
+            fingerprint_vec: BitstringVec::new(
+                fingerprint_bit_count,
+                bucket_len * entries_per_index,
+            ),
+            value_vec: BitstringVec::new(value_bit_count, bucket_len * entries_per_index),
+            extra_items: Vec::new(),
+            hash_builders,
+            rng: XorShiftRng::from_entropy(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, a
+    /// fingerprint bit count of `fingerprint_bit_count`, `entries_per_index` entries per index,
+    /// values of `value_bit_count` bits, a maximum of 512 item displacements before terminating
+    /// the insertion process, two hasher builders for double hashing, and a seed for the random
+    /// number generator used to displace items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` less than 1 or greater than 64, if
+    /// `entries_per_index` is 0, or if `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::from_parameters_with_hashers_and_seed(
+    ///     100,
+    ///     16,
+    ///     8,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    ///     0,
+    /// );
+    /// ```
+    pub fn from_parameters_with_hashers_and_seed(
+        item_count: usize,
+        fingerprint_bit_count: usize,
+        entries_per_index: usize,
+        value_bit_count: usize,
+        hash_builders: [B; 2],
+        seed: u64,
+    ) -> Self {
+        let mut map = Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            entries_per_index,
+            value_bit_count,
+            hash_builders,
+        );
+        map.rng = XorShiftRng::seed_from_u64(seed);
+        map
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, an
+    /// estimated maximum false positive probability of `fpp`, `entries_per_index` entries per
+    /// index, values of `value_bit_count` bits, a maximum of 512 item displacements before
+    /// terminating the insertion process, and two hasher builders for double hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `entries_per_index` is 0, or if `value_bit_count` is 0 or
+    /// greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::from_entries_per_index_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     4,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_entries_per_index_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        entries_per_index: usize,
+        value_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(entries_per_index > 0);
+        Self::from_parameters_with_hashers(
+            item_count,
+            cuckoo::get_fingerprint_bit_count(fpp, entries_per_index),
+            entries_per_index,
+            value_bit_count,
+            hash_builders,
+        )
+    }
+
+    /// Constructs a new, empty `CuckooMap` with an estimated max capacity of `item_count`, an
+    /// estimated maximum false positive probability of `fpp`, a fingerprint bit count of
+    /// `fingerprint_bit_count`, values of `value_bit_count` bits, a maximum of 512 item
+    /// displacements before terminating the insertion process, and two hasher builders for double
+    /// hashing.
+    ///
+    /// The length of each bucket will be rounded off to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `item_count` is 0, if `fingerprint_bit_count` is less than 1 or greater than 64,
+    /// if it is impossible to achieve the given maximum false positive probability, or if
+    /// `value_bit_count` is 0 or greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = CuckooMap::<String>::from_fingerprint_bit_count_with_hashers(
+    ///     100,
+    ///     0.01,
+    ///     10,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn from_fingerprint_bit_count_with_hashers(
+        item_count: usize,
+        fpp: f64,
+        fingerprint_bit_count: usize,
+        value_bit_count: usize,
+        hash_builders: [B; 2],
+    ) -> Self {
+        assert!(fingerprint_bit_count > 1 && fingerprint_bit_count <= 64);
+        Self::from_parameters_with_hashers(
+            item_count,
+            fingerprint_bit_count,
+            cuckoo::get_entries_per_index(fpp, fingerprint_bit_count),
+            value_bit_count,
+            hash_builders,
+        )
+    }
+
+    #[inline]
+    fn get_vec_index(&self, index: usize, bucket_index: usize) -> usize {
+        index * self.entries_per_index + bucket_index
+    }
+
+    fn get_fingerprint_and_indexes<U>(&self, item: &U) -> (u64, usize, usize)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let FingerprintAndIndexes {
+            fingerprint,
+            index_1,
+            index_2,
+        } = cuckoo::get_fingerprint_and_indexes(
+            &self.hash_builders,
+            cuckoo::hash_item(&self.hash_builders, item),
+            self.fingerprint_bit_count(),
+            self.bucket_len(),
+            self.bucket_len(),
+        );
+        (fingerprint, index_1, index_2)
+    }
+
+    // Returns the index into the fingerprint vector of an entry with the given fingerprint in one
+    // of the two indexes.
+    fn find_vec_index(&self, fingerprint: u64, index_1: usize, index_2: usize) -> Option<usize> {
+        let bucket_indexes = 0..self.entries_per_index;
+        bucket_indexes
+            .clone()
+            .map(|bucket_index| self.get_vec_index(index_1, bucket_index))
+            .chain(bucket_indexes.map(|bucket_index| self.get_vec_index(index_2, bucket_index)))
+            .find(|vec_index| self.fingerprint_vec.get(*vec_index) == fingerprint)
+    }
+
+    // Returns the position in the extra items vector of an entry with the given fingerprint and
+    // indexes.
+    fn find_extra_item(&self, fingerprint: u64, index_1: usize, index_2: usize) -> Option<usize> {
+        let min_index = cmp::min(index_1, index_2);
+        self.extra_items
+            .iter()
+            .position(|item| (item.0, item.1) == (fingerprint, min_index))
+    }
+
+    fn assert_value_fits(&self, value: u64) {
+        let value_mask = u64::MAX >> (64 - self.value_bit_count());
+        assert!(value & !value_mask == 0);
+    }
+
+    /// Inserts an element with a value into the cuckoo map, and returns the previous value of the
+    /// element's fingerprint if it was in the cuckoo map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` does not fit in `value_bit_count` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.insert("foo", 3), None);
+    /// assert_eq!(map.insert("foo", 5), Some(3));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U, value: u64) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.assert_value_fits(value);
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        if let Some(old_value) = self.update_entry(fingerprint, index_1, index_2, value) {
+            return Some(old_value);
+        }
+        if let Some(((fingerprint, value), index)) =
+            self.insert_entry_with_kicks((fingerprint, value), index_1, index_2)
+        {
+            self.extra_items.push((fingerprint, index, value));
+        }
+        None
+    }
+
+    /// Updates the value of an element if it is possibly in the cuckoo map, and returns its
+    /// previous value. Elements that are not in the cuckoo map are not inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` does not fit in `value_bit_count` bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.update("foo", 3), None);
+    /// assert_eq!(map.get("foo"), None);
+    ///
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.update("foo", 5), Some(3));
+    /// assert_eq!(map.get("foo"), Some(5));
+    /// ```
+    pub fn update<U>(&mut self, item: &U, value: u64) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.assert_value_fits(value);
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        self.update_entry(fingerprint, index_1, index_2, value)
+    }
+
+    fn update_entry(
+        &mut self,
+        fingerprint: u64,
+        index_1: usize,
+        index_2: usize,
+        value: u64,
+    ) -> Option<u64> {
+        if let Some(vec_index) = self.find_vec_index(fingerprint, index_1, index_2) {
+            let old_value = self.value_vec.get(vec_index);
+            self.value_vec.set(vec_index, value);
+            return Some(old_value);
+        }
+        let position = self.find_extra_item(fingerprint, index_1, index_2)?;
+        Some(mem::replace(&mut self.extra_items[position].2, value))
+    }
+
+    /// Returns the value of an element if it is possibly in the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.get("foo"), None);
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.get("foo"), Some(3));
+    /// ```
+    pub fn get<U>(&self, item: &U) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        if let Some(vec_index) = self.find_vec_index(fingerprint, index_1, index_2) {
+            return Some(self.value_vec.get(vec_index));
+        }
+        self.find_extra_item(fingerprint, index_1, index_2)
+            .map(|position| self.extra_items[position].2)
+    }
+
+    /// Checks if an element is possibly in the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// assert!(map.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Removes an element from the cuckoo map, and returns its value if it was possibly in the
+    /// cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.remove("foo"), Some(3));
+    /// assert_eq!(map.remove("foo"), None);
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (fingerprint, index_1, index_2) = self.get_fingerprint_and_indexes(item);
+        if let Some(vec_index) = self.find_vec_index(fingerprint, index_1, index_2) {
+            let value = self.value_vec.get(vec_index);
+            self.fingerprint_vec.set(vec_index, 0);
+            self.value_vec.set(vec_index, 0);
+            return Some(value);
+        }
+        let position = self.find_extra_item(fingerprint, index_1, index_2)?;
+        Some(self.extra_items.swap_remove(position).2)
+    }
+
+    /// Clears the cuckoo map, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// map.clear();
+    ///
+    /// assert_eq!(map.get("foo"), None);
+    /// ```
+    pub fn clear(&mut self) {
+        self.fingerprint_vec.clear();
+        self.value_vec.clear();
+        self.extra_items.clear();
+    }
+
+    /// Returns the number of occupied entries in the cuckoo map. It does not account for items in
+    /// the extra items vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.len(), 0);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.fingerprint_vec.occupied_len()
+    }
+
+    /// Returns `true` if there are no occupied entries in the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum capacity of the cuckoo map. Items may spill into the extra items vector
+    /// even though the length of the cuckoo map is less than the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.capacity(), 128);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.fingerprint_vec.len()
+    }
+
+    /// Returns the number of buckets in the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.bucket_len(), 32);
+    /// ```
+    pub fn bucket_len(&self) -> usize {
+        self.capacity() / self.entries_per_index
+    }
+
+    /// Returns the number of entries per index in the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.entries_per_index(), 4);
+    /// ```
+    pub fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    /// Returns the number of items that could not be inserted into the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let mut map = CuckooMap::<String>::from_parameters_with_hashers(
+    ///     1,
+    ///     8,
+    ///     1,
+    ///     4,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    ///
+    /// map.insert("foo", 1);
+    /// map.insert("foobar", 2);
+    /// assert_eq!(map.extra_items_len(), 1);
+    /// ```
+    pub fn extra_items_len(&self) -> usize {
+        self.extra_items.len()
+    }
+
+    /// Returns `true` if there are any items that could not be inserted into the cuckoo map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::from_parameters(1, 8, 1, 4);
+    ///
+    /// map.insert("foo", 1);
+    /// map.insert("foobar", 2);
+    /// assert!(map.is_nearly_full());
+    /// ```
+    pub fn is_nearly_full(&self) -> bool {
+        !self.extra_items.is_empty()
+    }
+
+    /// Returns the number of bits in each item fingerprint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_vec.bit_count()
+    }
+
+    /// Returns the number of bits in each value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    ///
+    /// assert_eq!(map.value_bit_count(), 4);
+    /// ```
+    pub fn value_bit_count(&self) -> usize {
+        self.value_vec.bit_count()
+    }
+
+    /// Returns the estimated false positive probability of the cuckoo map. This value will
+    /// increase as more items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let mut map = CuckooMap::<String>::new(100, 4);
+    /// assert!(map.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// map.insert("foo", 3);
+    /// assert!(map.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(map.estimated_fpp() < 0.01);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fingerprints_count = 2.0f64.powi(self.fingerprint_bit_count() as i32);
+        let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
+        let occupied_ratio = self.len() as f64 / self.capacity() as f64;
+        1.0 - single_fpp.powf(2.0 * self.entries_per_index() as f64 * occupied_ratio)
+    }
+
+    /// Returns a reference to the cuckoo map's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::CuckooMap;
+    ///
+    /// let map = CuckooMap::<String>::new(100, 4);
+    /// let hashers = map.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        &self.hash_builders
+    }
+}
+
+impl<T, B> Buckets for CuckooMap<T, B>
+where
+    B: BuildHasher,
+{
+    // a fingerprint and its value
+    type Entry = (u64, u64);
+
+    fn max_kicks(&self) -> usize {
+        self.max_kicks
+    }
+
+    fn entries_per_index(&self) -> usize {
+        self.entries_per_index
+    }
+
+    fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    fn entry_alternate_index(&self, (fingerprint, _): (u64, u64), index: usize) -> usize {
+        cuckoo::get_alternate_index(
+            &self.hash_builders[1],
+            fingerprint,
+            index,
+            self.bucket_len(),
+        )
+    }
+
+    fn insert_entry(&mut self, (fingerprint, value): (u64, u64), index: usize) -> bool {
+        for bucket_index in 0..self.entries_per_index {
+            let vec_index = self.get_vec_index(index, bucket_index);
+            if self.fingerprint_vec.get(vec_index) == 0 {
+                self.fingerprint_vec.set(vec_index, fingerprint);
+                self.value_vec.set(vec_index, value);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn replace_entry(
+        &mut self,
+        index: usize,
+        bucket_index: usize,
+        (fingerprint, value): (u64, u64),
+    ) -> (u64, u64) {
+        let vec_index = self.get_vec_index(index, bucket_index);
+        let old_entry = (
+            self.fingerprint_vec.get(vec_index),
+            self.value_vec.get(vec_index),
+        );
+        self.fingerprint_vec.set(vec_index, fingerprint);
+        self.value_vec.set(vec_index, value);
+        old_entry
+    }
+}
+
+impl<T> PartialEq for CuckooMap<T> {
+    fn eq(&self, other: &CuckooMap<T>) -> bool {
+        self.max_kicks == other.max_kicks
+            && self.entries_per_index == other.entries_per_index
+            && self.fingerprint_vec == other.fingerprint_vec
+            && self.value_vec == other.value_vec
+            && self.extra_items == other.extra_items
+            && self.hash_builders == other.hash_builders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CuckooMap;
+    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_new() {
+        let map = CuckooMap::<String>::with_hashers(100, 4, [hash_builder_1(), hash_builder_2()]);
+
+        assert_eq!(map.capacity(), 128);
+        assert_eq!(map.bucket_len(), 32);
+        assert_eq!(map.entries_per_index(), 4);
+        assert_eq!(map.fingerprint_bit_count(), 8);
+        assert_eq!(map.value_bit_count(), 4);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_from_parameters() {
+        let map = CuckooMap::<String>::from_parameters(100, 16, 8, 64);
+
+        assert_eq!(map.capacity(), 128);
+        assert_eq!(map.bucket_len(), 16);
+        assert_eq!(map.entries_per_index(), 8);
+        assert_eq!(map.fingerprint_bit_count(), 16);
+        assert_eq!(map.value_bit_count(), 64);
+    }
+
+    #[test]
+    fn test_from_entries_per_index() {
+        let map = CuckooMap::<String>::from_entries_per_index(100, 0.01, 4, 4);
+
+        assert_eq!(map.capacity(), 128);
+        assert_eq!(map.entries_per_index(), 4);
+        assert_eq!(map.fingerprint_bit_count(), 11);
+    }
+
+    #[test]
+    fn test_from_fingerprint_bit_count() {
+        let map = CuckooMap::<String>::from_fingerprint_bit_count(100, 0.01, 10, 4);
+
+        assert_eq!(map.capacity(), 160);
+        assert_eq!(map.entries_per_index(), 5);
+        assert_eq!(map.fingerprint_bit_count(), 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_zero_value_bit_count() {
+        CuckooMap::<String>::new(100, 0);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut map = CuckooMap::<String>::new(100, 4);
+        assert_eq!(map.insert("foo", 15), None);
+
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+        assert!(map.contains("foo"));
+        assert_eq!(map.get("foo"), Some(15));
+    }
+
+    #[test]
+    fn test_insert_existing_item() {
+        let mut map = CuckooMap::<String>::new(100, 4);
+        map.insert("foo", 1);
+
+        assert_eq!(map.insert("foo", 2), Some(1));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("foo"), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_large_value() {
+        let mut map = CuckooMap::<String>::new(100, 4);
+        map.insert("foo", 16);
+    }
+
+    #[test]
+    fn test_insert_extra_items() {
+        let mut map = CuckooMap::<String>::from_parameters_with_hashers(
+            1,
+            8,
+            1,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        map.insert("foo", 1);
+        map.insert("foobar", 2);
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.extra_items_len(), 1);
+        assert!(map.is_nearly_full());
+
+        assert_eq!(map.get("foo"), Some(1));
+        assert_eq!(map.get("foobar"), Some(2));
+
+        assert_eq!(map.update("foo", 3), Some(1));
+        assert_eq!(map.update("foobar", 4), Some(2));
+        assert_eq!(map.get("foo"), Some(3));
+        assert_eq!(map.get("foobar"), Some(4));
+
+        assert_eq!(map.remove("foo"), Some(3));
+        assert_eq!(map.remove("foobar"), Some(4));
+        assert!(map.is_empty());
+        assert!(!map.is_nearly_full());
+    }
+
+    #[test]
+    fn test_update() {
+        let mut map = CuckooMap::<String>::new(100, 4);
+
+        assert_eq!(map.update("foo", 1), None);
+        assert!(map.is_empty());
+
+        map.insert("foo", 1);
+        assert_eq!(map.update("foo", 2), Some(1));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("foo"), Some(2));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = CuckooMap::<String>::new(100, 4);
+        map.insert("foo", 1);
+
+        assert_eq!(map.remove("foo"), Some(1));
+        assert_eq!(map.remove("foo"), None);
+        assert!(map.is_empty());
+        assert_eq!(map.get("foo"), None);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut map = CuckooMap::<String>::from_parameters_with_hashers(
+            2,
+            8,
+            1,
+            4,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        map.insert("foobar", 1);
+        map.insert("barfoo", 2);
+        map.insert("baz", 3);
+        map.insert("qux", 4);
+
+        map.clear();
+
+        assert!(map.is_empty());
+        assert!(!map.is_nearly_full());
+        assert_eq!(map.get("foobar"), None);
+        assert_eq!(map.get("barfoo"), None);
+        assert_eq!(map.get("baz"), None);
+        assert_eq!(map.get("qux"), None);
+    }
+
+    #[test]
+    fn test_stress() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let item_count = 1 << 12;
+        let value_bit_count = 8;
+
+        // large fingerprint to decrease chance of false positives
+        let mut map = CuckooMap::<u64>::from_parameters(item_count, 48, 4, value_bit_count);
+        let mut values = HashMap::new();
+        while values.len() < item_count * 9 / 10 {
+            let item = rng.gen::<u64>();
+            let value = rng.gen_range(0, 1 << value_bit_count);
+            assert_eq!(map.insert(&item, value), values.insert(item, value));
+        }
+        assert_eq!(map.len() + map.extra_items_len(), values.len());
+
+        for (item, value) in &values {
+            assert_eq!(map.get(item), Some(*value));
+        }
+
+        let mut items: Vec<_> = values.keys().cloned().collect();
+        items.shuffle(&mut rng);
+        for item in items {
+            assert_eq!(map.remove(&item), values.remove(&item));
+            assert_eq!(map.get(&item), None);
+            for item in values.keys().take(16) {
+                assert_eq!(map.get(item), values.get(item).cloned());
+            }
+        }
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut map = CuckooMap::<String>::from_entries_per_index(100, 0.01, 4, 4);
+        map.insert("foo", 3);
+
+        let serialized_map = bincode::serialize(&map).unwrap();
+        let de_map: CuckooMap<String> = bincode::deserialize(&serialized_map).unwrap();
+
+        assert_eq!(de_map.get("foo"), Some(3));
+        assert!(map == de_map);
+    }
+}
@@ -0,0 +1,335 @@
+//! Storage for the fingerprints of a cuckoo filter.
+
+use crate::bitstring_vec::BitstringVec;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+
+// The number of entries in each semi-sorted bucket.
+pub const SEMI_SORTED_ENTRIES_PER_INDEX: usize = 4;
+
+// The number of high bits of each fingerprint that are encoded together in a semi-sorted bucket.
+const NIBBLE_BIT_COUNT: usize = 4;
+
+// The number of bits used to encode the sorted nibbles of a semi-sorted bucket.
+const CODE_BIT_COUNT: usize = 12;
+
+// The number of ways to choose four nibbles with repetition, which is C(16 + 4 - 1, 4).
+const SORTED_NIBBLES_LEN: usize = 3876;
+
+// The nibbles of every sorted bucket packed into 16 bits with the smallest nibble in the highest
+// bits, in increasing order. The code of a bucket is the position of its packed nibbles.
+static SORTED_NIBBLES: [u16; SORTED_NIBBLES_LEN] = get_sorted_nibbles();
+
+const fn get_sorted_nibbles() -> [u16; SORTED_NIBBLES_LEN] {
+    let mut sorted_nibbles = [0; SORTED_NIBBLES_LEN];
+    let mut index = 0;
+    let mut packed_nibbles = 0;
+    while packed_nibbles < 1 << 16 {
+        let nibble_0 = packed_nibbles >> 12;
+        let nibble_1 = (packed_nibbles >> 8) & 0xF;
+        let nibble_2 = (packed_nibbles >> 4) & 0xF;
+        let nibble_3 = packed_nibbles & 0xF;
+        if nibble_0 <= nibble_1 && nibble_1 <= nibble_2 && nibble_2 <= nibble_3 {
+            sorted_nibbles[index] = packed_nibbles as u16;
+            index += 1;
+        }
+        packed_nibbles += 1;
+    }
+    sorted_nibbles
+}
+
+/// The fingerprints of a cuckoo filter, stored either as fixed-width bitstrings or in
+/// semi-sorted buckets.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub enum FingerprintVec {
+    Bitstring(BitstringVec),
+    SemiSorted(SemiSortedVec),
+}
+
+impl FingerprintVec {
+    pub fn new(fingerprint_bit_count: usize, len: usize) -> Self {
+        FingerprintVec::Bitstring(BitstringVec::new(fingerprint_bit_count, len))
+    }
+
+    pub fn new_semi_sorted(fingerprint_bit_count: usize, len: usize) -> Self {
+        FingerprintVec::SemiSorted(SemiSortedVec::new(fingerprint_bit_count, len))
+    }
+
+    // Returns an empty fingerprint vector with the same encoding and fingerprint bit count.
+    pub fn with_len(&self, len: usize) -> Self {
+        match self {
+            FingerprintVec::Bitstring(vec) => Self::new(vec.bit_count(), len),
+            FingerprintVec::SemiSorted(vec) => Self::new_semi_sorted(vec.bit_count(), len),
+        }
+    }
+
+    // Sets the fingerprint at an index. Setting a fingerprint in a semi-sorted bucket may reorder
+    // the other fingerprints in the bucket. Removing a fingerprint only moves the fingerprints at
+    // lower indexes in its bucket, so scanning a bucket in increasing order while removing
+    // fingerprints visits every fingerprint once.
+    pub fn set(&mut self, index: usize, fingerprint: u64) {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.set(index, fingerprint),
+            FingerprintVec::SemiSorted(vec) => vec.set(index, fingerprint),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> u64 {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.get(index),
+            FingerprintVec::SemiSorted(vec) => vec.get(index),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.clear(),
+            FingerprintVec::SemiSorted(vec) => vec.clear(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.capacity(),
+            FingerprintVec::SemiSorted(vec) => vec.len(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.len(),
+            FingerprintVec::SemiSorted(vec) => vec.len(),
+        }
+    }
+
+    pub fn occupied_len(&self) -> usize {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.occupied_len(),
+            FingerprintVec::SemiSorted(vec) => vec.occupied_len(),
+        }
+    }
+
+    pub fn bit_count(&self) -> usize {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.bit_count(),
+            FingerprintVec::SemiSorted(vec) => vec.bit_count(),
+        }
+    }
+
+    // Returns the average number of bits used to store each fingerprint.
+    pub fn bits_per_entry(&self) -> usize {
+        match self {
+            FingerprintVec::Bitstring(vec) => vec.bit_count(),
+            FingerprintVec::SemiSorted(vec) => {
+                let bucket_bit_count = CODE_BIT_COUNT
+                    + SEMI_SORTED_ENTRIES_PER_INDEX * (vec.bit_count() - NIBBLE_BIT_COUNT);
+                bucket_bit_count / SEMI_SORTED_ENTRIES_PER_INDEX
+            }
+        }
+    }
+
+    pub fn is_semi_sorted(&self) -> bool {
+        matches!(self, FingerprintVec::SemiSorted(_))
+    }
+}
+
+/// Fingerprints stored in buckets of four entries that are sorted by their highest four bits, as
+/// described by Fan et al. The sorted nibbles of a bucket have 3876 possible values, so they are
+/// encoded in 12 bits instead of 16, which saves one bit per entry. The remaining bits of each
+/// fingerprint are stored in the same order as the nibbles.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SemiSortedVec {
+    code_vec: BitstringVec,
+    remainder_vec: BitstringVec,
+    occupied_len: usize,
+}
+
+impl SemiSortedVec {
+    pub fn new(fingerprint_bit_count: usize, len: usize) -> Self {
+        assert!(fingerprint_bit_count > NIBBLE_BIT_COUNT && fingerprint_bit_count <= 64);
+        assert_eq!(len % SEMI_SORTED_ENTRIES_PER_INDEX, 0);
+        SemiSortedVec {
+            code_vec: BitstringVec::new(CODE_BIT_COUNT, len / SEMI_SORTED_ENTRIES_PER_INDEX),
+            remainder_vec: BitstringVec::new(fingerprint_bit_count - NIBBLE_BIT_COUNT, len),
+            occupied_len: 0,
+        }
+    }
+
+    fn get_bucket(&self, bucket: usize) -> [u64; SEMI_SORTED_ENTRIES_PER_INDEX] {
+        let remainder_bit_count = self.remainder_vec.bit_count();
+        let packed_nibbles = u64::from(SORTED_NIBBLES[self.code_vec.get(bucket) as usize]);
+        let mut fingerprints = [0; SEMI_SORTED_ENTRIES_PER_INDEX];
+        for (bucket_index, fingerprint) in fingerprints.iter_mut().enumerate() {
+            let shift = (SEMI_SORTED_ENTRIES_PER_INDEX - 1 - bucket_index) * NIBBLE_BIT_COUNT;
+            let nibble = (packed_nibbles >> shift) & 0xF;
+            let remainder = self
+                .remainder_vec
+                .get(bucket * SEMI_SORTED_ENTRIES_PER_INDEX + bucket_index);
+            *fingerprint = (nibble << remainder_bit_count) | remainder;
+        }
+        fingerprints
+    }
+
+    fn set_bucket(
+        &mut self,
+        bucket: usize,
+        mut fingerprints: [u64; SEMI_SORTED_ENTRIES_PER_INDEX],
+    ) {
+        // sorting the fingerprints also sorts their highest bits
+        fingerprints.sort_unstable();
+        let remainder_bit_count = self.remainder_vec.bit_count();
+        let remainder_mask = (1 << remainder_bit_count) - 1;
+        let mut packed_nibbles = 0;
+        for (bucket_index, fingerprint) in fingerprints.iter().enumerate() {
+            packed_nibbles =
+                (packed_nibbles << NIBBLE_BIT_COUNT) | fingerprint >> remainder_bit_count;
+            self.remainder_vec.set(
+                bucket * SEMI_SORTED_ENTRIES_PER_INDEX + bucket_index,
+                fingerprint & remainder_mask,
+            );
+        }
+        let code = SORTED_NIBBLES
+            .binary_search(&(packed_nibbles as u16))
+            .expect("Expected nibbles to be sorted.");
+        self.code_vec.set(bucket, code as u64);
+    }
+
+    pub fn set(&mut self, index: usize, fingerprint: u64) {
+        let bucket = index / SEMI_SORTED_ENTRIES_PER_INDEX;
+        let mut fingerprints = self.get_bucket(bucket);
+        let prev_fingerprint = fingerprints[index % SEMI_SORTED_ENTRIES_PER_INDEX];
+        if prev_fingerprint == 0 && fingerprint != 0 {
+            self.occupied_len += 1;
+        } else if prev_fingerprint != 0 && fingerprint == 0 {
+            self.occupied_len -= 1;
+        }
+        fingerprints[index % SEMI_SORTED_ENTRIES_PER_INDEX] = fingerprint;
+        self.set_bucket(bucket, fingerprints);
+    }
+
+    pub fn get(&self, index: usize) -> u64 {
+        self.get_bucket(index / SEMI_SORTED_ENTRIES_PER_INDEX)
+            [index % SEMI_SORTED_ENTRIES_PER_INDEX]
+    }
+
+    pub fn clear(&mut self) {
+        self.code_vec.clear();
+        self.remainder_vec.clear();
+        self.occupied_len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.remainder_vec.len()
+    }
+
+    pub fn occupied_len(&self) -> usize {
+        self.occupied_len
+    }
+
+    pub fn bit_count(&self) -> usize {
+        self.remainder_vec.bit_count() + NIBBLE_BIT_COUNT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FingerprintVec, SORTED_NIBBLES, SORTED_NIBBLES_LEN};
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_sorted_nibbles() {
+        assert_eq!(SORTED_NIBBLES[0], 0x0000);
+        assert_eq!(SORTED_NIBBLES[1], 0x0001);
+        assert_eq!(SORTED_NIBBLES[SORTED_NIBBLES_LEN - 1], 0xFFFF);
+        assert!(SORTED_NIBBLES.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    fn test_semi_sorted_with_bit_count(bit_count: usize) {
+        let len = 32;
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut vec = FingerprintVec::new_semi_sorted(bit_count, len);
+        let mut buckets = vec![Vec::new(); len / 4];
+
+        assert!(vec.is_semi_sorted());
+        assert_eq!(vec.len(), len);
+        assert_eq!(vec.bit_count(), bit_count);
+        assert_eq!(vec.bits_per_entry(), bit_count - 1);
+
+        for _ in 0..1000 {
+            let index = rng.gen_range(0, len);
+            let fingerprint = if rng.gen::<bool>() {
+                0
+            } else {
+                // use few distinct fingerprints to exercise repeated nibbles
+                let high = rng.gen_range(0, 4) << (bit_count - 4);
+                let low = rng.gen_range(0, 4);
+                let mask = if bit_count == 64 {
+                    !0
+                } else {
+                    (1 << bit_count) - 1
+                };
+                (high | low | (rng.gen::<u64>() & 0b100)) & mask
+            };
+            let prev_fingerprint = vec.get(index);
+            vec.set(index, fingerprint);
+
+            let bucket = &mut buckets[index / 4];
+            if prev_fingerprint != 0 {
+                let position = bucket.iter().position(|f| *f == prev_fingerprint).unwrap();
+                bucket.swap_remove(position);
+            }
+            if fingerprint != 0 {
+                bucket.push(fingerprint);
+            }
+
+            for (bucket_index, bucket) in buckets.iter().enumerate() {
+                let mut expected = bucket.clone();
+                expected.resize(4, 0);
+                expected.sort_unstable();
+                let actual: Vec<u64> = (0..4).map(|i| vec.get(bucket_index * 4 + i)).collect();
+                assert_eq!(actual, expected);
+            }
+            let occupied_len: usize = buckets.iter().map(|bucket| bucket.len()).sum();
+            assert_eq!(vec.occupied_len(), occupied_len);
+        }
+
+        vec.clear();
+        assert_eq!(vec.occupied_len(), 0);
+        assert!((0..len).all(|index| vec.get(index) == 0));
+    }
+
+    #[test]
+    fn test_semi_sorted() {
+        for bit_count in &[5, 8, 13, 32, 64] {
+            test_semi_sorted_with_bit_count(*bit_count);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_semi_sorted_small_bit_count() {
+        FingerprintVec::new_semi_sorted(4, 32);
+    }
+
+    #[test]
+    fn test_bitstring() {
+        let mut vec = FingerprintVec::new(8, 32);
+        vec.set(3, 7);
+
+        assert!(!vec.is_semi_sorted());
+        assert_eq!(vec.get(3), 7);
+        assert_eq!(vec.occupied_len(), 1);
+        assert_eq!(vec.bits_per_entry(), 8);
+        assert_eq!(vec.with_len(64).len(), 64);
+    }
+}
@@ -1,12 +1,207 @@
-//! Space-efficient probabilistic data structure for approximate membership queries in a set with
-//! the ability to remove items.
+//! Space-efficient probabilistic data structures for approximate membership queries in a set and
+//! approximate maps from items to small values with the ability to remove items.
 
+mod adaptive_cuckoo_filter;
+mod concurrent_cuckoo_filter;
 mod cuckoo_filter;
+mod cuckoo_map;
+mod fingerprint_vec;
 mod scalable_cuckoo_filter;
 
+use crate::util;
+use rand::Rng;
+use rand_xorshift::XorShiftRng;
+use std::cmp;
+use std::hash::{BuildHasher, Hash};
+
 const DEFAULT_ENTRIES_PER_INDEX: usize = 4;
 const DEFAULT_FINGERPRINT_BIT_COUNT: usize = 8;
 const DEFAULT_MAX_KICKS: usize = 512;
 
-pub use self::cuckoo_filter::CuckooFilter;
+pub use self::adaptive_cuckoo_filter::AdaptiveCuckooFilter;
+pub use self::concurrent_cuckoo_filter::ConcurrentCuckooFilter;
+pub use self::cuckoo_filter::{CuckooFilter, Fingerprints, OverflowPolicy};
+pub use self::cuckoo_map::CuckooMap;
 pub use self::scalable_cuckoo_filter::ScalableCuckooFilter;
+
+// Returns the number of buckets needed to store `item_count` items, rounded up to the next power
+// of two.
+fn get_bucket_len(item_count: usize, entries_per_index: usize) -> usize {
+    ((item_count + entries_per_index - 1) / entries_per_index).next_power_of_two()
+}
+
+// Returns the number of fingerprint bits needed to achieve a maximum false positive probability
+// of `fpp` with `entries_per_index` entries per index.
+fn get_fingerprint_bit_count(fpp: f64, entries_per_index: usize) -> usize {
+    let power = 2.0 / (1.0 - (1.0 - fpp).powf(1.0 / (2.0 * entries_per_index as f64)));
+    power.log2().ceil() as usize
+}
+
+// Returns the maximum number of entries per index that achieves a maximum false positive
+// probability of `fpp` with fingerprints of `fingerprint_bit_count` bits.
+fn get_entries_per_index(fpp: f64, fingerprint_bit_count: usize) -> usize {
+    let fingerprints_count = 2.0f64.powi(fingerprint_bit_count as i32);
+    let single_fpp = (fingerprints_count - 2.0) / (fingerprints_count - 1.0);
+    ((1.0 - fpp).log(single_fpp) / 2.0).floor() as usize
+}
+
+struct FingerprintAndIndexes {
+    fingerprint: u64,
+    index_1: usize,
+    index_2: usize,
+}
+
+// Returns the hash of an item, with the hash of the first hasher in the upper 64 bits and the
+// hash of the second hasher in the lower 64 bits.
+fn hash_item<B, U>(hash_builders: &[B; 2], item: &U) -> u128
+where
+    B: BuildHasher,
+    U: Hash + ?Sized,
+{
+    let h0 = util::hash(&hash_builders[0], &item);
+    let h1 = util::hash(&hash_builders[1], &item);
+    (u128::from(h0) << 64) | u128::from(h1)
+}
+
+// Returns the lowest `fingerprint_bit_count` bits of `hash`, rehashing until they are not all 0s
+// since a fingerprint of 0 marks an empty entry.
+fn get_fingerprint<B: BuildHasher>(
+    hash_builder: &B,
+    mut hash: u64,
+    fingerprint_bit_count: usize,
+) -> u64 {
+    let trailing_zeros = 64 - fingerprint_bit_count;
+    let mut fingerprint = hash << trailing_zeros >> trailing_zeros;
+    while fingerprint == 0 {
+        hash = util::hash(hash_builder, &hash.wrapping_add(1));
+        fingerprint = hash << trailing_zeros >> trailing_zeros;
+    }
+    fingerprint
+}
+
+// Returns the other index of a fingerprint at `index`. Both indexes are in the same region of
+// `region_len` buckets.
+fn get_alternate_index<B: BuildHasher>(
+    hash_builder: &B,
+    fingerprint: u64,
+    index: usize,
+    region_len: usize,
+) -> usize {
+    let hashed_fingerprint = util::hash(hash_builder, &fingerprint);
+    index ^ (hashed_fingerprint as usize % region_len)
+}
+
+// Returns the fingerprint and the two indexes of an item with the given hash in a table of
+// `bucket_len` buckets that is split into regions of `region_len` buckets.
+fn get_fingerprint_and_indexes<B: BuildHasher>(
+    hash_builders: &[B; 2],
+    hash: u128,
+    fingerprint_bit_count: usize,
+    bucket_len: usize,
+    region_len: usize,
+) -> FingerprintAndIndexes {
+    let fingerprint = get_fingerprint(
+        &hash_builders[0],
+        (hash >> 64) as u64,
+        fingerprint_bit_count,
+    );
+
+    // the lowest bits of the fingerprint select the region that contains both indexes
+    let region_mask = (bucket_len / region_len - 1) as u64;
+    let region = (fingerprint & region_mask) as usize;
+    let index_1 = hash as u64 as usize % region_len + region * region_len;
+    let index_2 = get_alternate_index(&hash_builders[1], fingerprint, index_1, region_len);
+    FingerprintAndIndexes {
+        fingerprint,
+        index_1,
+        index_2,
+    }
+}
+
+// The buckets of a cuckoo table, where each entry has two indexes and can be displaced from one
+// to the other.
+trait Buckets {
+    type Entry: Copy;
+
+    fn max_kicks(&self) -> usize;
+
+    fn entries_per_index(&self) -> usize;
+
+    fn rng(&mut self) -> &mut XorShiftRng;
+
+    // Returns the other index of an entry at `index`.
+    fn entry_alternate_index(&self, entry: Self::Entry, index: usize) -> usize;
+
+    // Inserts an entry into the first empty position of the bucket at `index`. Returns `false` if
+    // the bucket is full.
+    fn insert_entry(&mut self, entry: Self::Entry, index: usize) -> bool;
+
+    // Replaces the entry at `bucket_index` in the bucket at `index` and returns the old entry.
+    fn replace_entry(
+        &mut self,
+        index: usize,
+        bucket_index: usize,
+        entry: Self::Entry,
+    ) -> Self::Entry;
+
+    // Inserts an entry into one of its two indexes, displacing other entries if both indexes are
+    // full. Returns the entry that was evicted after the maximum number of displacements, along
+    // with the smaller of its two indexes.
+    fn insert_entry_with_kicks(
+        &mut self,
+        entry: Self::Entry,
+        index_1: usize,
+        index_2: usize,
+    ) -> Option<(Self::Entry, usize)> {
+        self.insert_entry_with_path(entry, index_1, index_2, &mut Vec::new())
+    }
+
+    // Inserts an entry like `insert_entry_with_kicks`, and records the index and bucket index of
+    // every displaced entry in `path` so that the displacements can be undone with `undo_kicks`.
+    fn insert_entry_with_path(
+        &mut self,
+        mut entry: Self::Entry,
+        index_1: usize,
+        index_2: usize,
+        path: &mut Vec<(usize, usize)>,
+    ) -> Option<(Self::Entry, usize)> {
+        if self.insert_entry(entry, index_1) {
+            return None;
+        }
+
+        if self.insert_entry(entry, index_2) {
+            return None;
+        }
+
+        // have to kick out an entry
+        let mut index = if self.rng().gen::<bool>() {
+            index_1
+        } else {
+            index_2
+        };
+        let mut prev_index = index;
+
+        for _ in 0..self.max_kicks() {
+            let entries_per_index = self.entries_per_index();
+            let bucket_index = self.rng().gen_range(0, entries_per_index);
+            entry = self.replace_entry(index, bucket_index, entry);
+            path.push((index, bucket_index));
+            prev_index = index;
+            index = self.entry_alternate_index(entry, prev_index);
+            if self.insert_entry(entry, index) {
+                return None;
+            }
+        }
+
+        Some((entry, cmp::min(prev_index, index)))
+    }
+
+    // Moves every displaced entry in `path` back to its original position, starting from the
+    // evicted entry, and returns the entry whose insertion started the displacements.
+    fn undo_kicks(&mut self, mut entry: Self::Entry, path: &[(usize, usize)]) -> Self::Entry {
+        for &(index, bucket_index) in path.iter().rev() {
+            entry = self.replace_entry(index, bucket_index, entry);
+        }
+        entry
+    }
+}
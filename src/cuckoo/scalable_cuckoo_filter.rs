@@ -1,9 +1,12 @@
-use crate::cuckoo::{CuckooFilter, DEFAULT_ENTRIES_PER_INDEX};
+use crate::cuckoo::{self, CuckooFilter, DEFAULT_ENTRIES_PER_INDEX};
 use crate::SipHasherBuilder;
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::cmp;
 use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::sync::Arc;
 
 /// A growable, space-efficient probabilistic data structure to test for membership in a set.
 /// Scalable cuckoo filters also provide the flexibility to remove items.
@@ -16,11 +19,18 @@ use std::hash::{BuildHasher, Hash};
 /// `ScalableBloomFilter`. Currently, the scalable cuckoo filter will naively insert into the last
 /// inserted cuckoo filter despite the fact that deletions could free space in previously inserted
 /// cuckoo filters. Checking if there is space in previously inserted cuckoo filters is fairly
-/// expensive and would significantly slow down the scalable cuckoo filter.
+/// expensive and would significantly slow down the scalable cuckoo filter. Instead, `compact` can
+/// be used to migrate the remaining fingerprints into fewer cuckoo filters after many removals.
 ///
 /// The overall false positive probability of the scalable cuckoo filter will be `initial_fpp * 1 /
 /// (1 - tightening_ratio)`.
 ///
+/// Cloning a scalable cuckoo filter is cheap because the clone shares its cuckoo filters with the
+/// original. A shared cuckoo filter is only copied when the clone or the original modifies it,
+/// which is usually just the newest cuckoo filter. A clone can therefore be used as a snapshot
+/// that many threads query while a single writer inserts into the original and periodically
+/// publishes a new snapshot.
+///
 /// # Examples
 ///
 /// ```
@@ -39,18 +49,52 @@ use std::hash::{BuildHasher, Hash};
 /// assert_eq!(filter.capacity(), 128);
 /// assert_eq!(filter.filter_count(), 1);
 /// ```
-#[derive(Clone)]
+///
+/// Querying snapshots from many threads while one thread inserts:
+///
+/// ```
+/// use probabilistic_collections::cuckoo::ScalableCuckooFilter;
+/// use std::sync::{Arc, RwLock};
+/// use std::thread;
+///
+/// let mut filter = ScalableCuckooFilter::<u32>::new(100, 0.01, 2.0, 0.5);
+/// filter.insert(&0);
+/// let snapshot = Arc::new(RwLock::new(Arc::new(filter.clone())));
+///
+/// let readers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let snapshot = Arc::clone(&snapshot);
+///         thread::spawn(move || {
+///             let filter = Arc::clone(&snapshot.read().unwrap());
+///             assert!(filter.contains(&0));
+///         })
+///     })
+///     .collect();
+///
+/// for item in 1..1000 {
+///     filter.insert(&item);
+/// }
+/// *snapshot.write().unwrap() = Arc::new(filter.clone());
+///
+/// for reader in readers {
+///     reader.join().unwrap();
+/// }
+/// assert!(snapshot.read().unwrap().contains(&999));
+/// ```
 #[cfg_attr(
     feature = "serde",
     derive(Deserialize, Serialize),
     serde(crate = "serde_crate")
 )]
 pub struct ScalableCuckooFilter<T, B = SipHasherBuilder> {
-    filters: Vec<CuckooFilter<T, B>>,
+    filters: Vec<Arc<CuckooFilter<T, B>>>,
     initial_item_count: usize,
     initial_fpp: f64,
     growth_ratio: f64,
     tightening_ratio: f64,
+    // the exponent of the tightening ratio for the false positive probability of the next cuckoo
+    // filter, which is kept when cuckoo filters are merged by `compact`
+    fpp_exponent: i32,
 }
 
 impl<T> ScalableCuckooFilter<T> {
@@ -71,15 +115,16 @@ impl<T> ScalableCuckooFilter<T> {
     /// ```
     pub fn new(item_count: usize, fpp: f64, growth_ratio: f64, tightening_ratio: f64) -> Self {
         ScalableCuckooFilter {
-            filters: vec![CuckooFilter::from_entries_per_index(
+            filters: vec![Arc::new(CuckooFilter::from_entries_per_index(
                 item_count,
                 fpp,
                 DEFAULT_ENTRIES_PER_INDEX,
-            )],
+            ))],
             initial_item_count: item_count,
             initial_fpp: fpp,
             growth_ratio,
             tightening_ratio,
+            fpp_exponent: 1,
         }
     }
 
@@ -107,15 +152,16 @@ impl<T> ScalableCuckooFilter<T> {
         tightening_ratio: f64,
     ) -> Self {
         ScalableCuckooFilter {
-            filters: vec![CuckooFilter::from_entries_per_index(
+            filters: vec![Arc::new(CuckooFilter::from_entries_per_index(
                 item_count,
                 fpp,
                 entries_per_index,
-            )],
+            ))],
             initial_item_count: item_count,
             initial_fpp: fpp,
             growth_ratio,
             tightening_ratio,
+            fpp_exponent: 1,
         }
     }
 }
@@ -155,16 +201,17 @@ where
         hash_builders: [B; 2],
     ) -> Self {
         ScalableCuckooFilter {
-            filters: vec![CuckooFilter::from_entries_per_index_with_hashers(
+            filters: vec![Arc::new(CuckooFilter::from_entries_per_index_with_hashers(
                 item_count,
                 fpp,
                 DEFAULT_ENTRIES_PER_INDEX,
                 hash_builders,
-            )],
+            ))],
             initial_item_count: item_count,
             initial_fpp: fpp,
             growth_ratio,
             tightening_ratio,
+            fpp_exponent: 1,
         }
     }
 
@@ -201,32 +248,112 @@ where
         hash_builders: [B; 2],
     ) -> Self {
         ScalableCuckooFilter {
-            filters: vec![CuckooFilter::from_entries_per_index_with_hashers(
+            filters: vec![Arc::new(CuckooFilter::from_entries_per_index_with_hashers(
                 item_count,
                 fpp,
                 entries_per_index,
                 hash_builders,
-            )],
+            ))],
             initial_item_count: item_count,
             initial_fpp: fpp,
             growth_ratio,
             tightening_ratio,
+            fpp_exponent: 1,
+        }
+    }
+
+    /// Constructs a `ScalableCuckooFilter` from its cuckoo filters, such as the ones returned by
+    /// [`ScalableCuckooFilter::filters`] after they were rebuilt with
+    /// [`CuckooFilter::from_fingerprints_with_hashers`]. `item_count` and `fpp` are the estimated
+    /// initial item capacity and the initial maximum false positive probability the scalable
+    /// cuckoo filter was constructed with. Every time a new cuckoo filter is added, the size will
+    /// be approximately `growth_ratio` multiplied by the previous size, and the false positive
+    /// probability will be `tightening_ratio` multipled by the previous false positive
+    /// probability. The first cuckoo filter that is added has a false positive probability that
+    /// is tightened at least once per cuckoo filter in `filters`, and enough times to use at least
+    /// as many fingerprint bits as any of them, so compacted cuckoo filters can be rebuilt.
+    ///
+    /// All cuckoo filters must use the same hasher builders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `filters` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, ScalableCuckooFilter};
+    ///
+    /// let mut filter = ScalableCuckooFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    /// filter.insert("foo");
+    ///
+    /// let filters = filter
+    ///     .filters()
+    ///     .iter()
+    ///     .map(|filter| {
+    ///         CuckooFilter::<String>::from_fingerprints_with_hashers(
+    ///             filter.capacity(),
+    ///             filter.fingerprint_bit_count(),
+    ///             filter.entries_per_index(),
+    ///             filter.initial_bucket_len(),
+    ///             *filter.hashers(),
+    ///             filter.fingerprints(),
+    ///         )
+    ///     })
+    ///     .collect();
+    /// let rebuilt_filter = ScalableCuckooFilter::from_filters(100, 0.01, 2.0, 0.5, filters);
+    /// assert!(rebuilt_filter.contains("foo"));
+    /// ```
+    pub fn from_filters(
+        item_count: usize,
+        fpp: f64,
+        growth_ratio: f64,
+        tightening_ratio: f64,
+        filters: Vec<CuckooFilter<T, B>>,
+    ) -> Self {
+        assert!(!filters.is_empty());
+        let fpp_exponent = Self::get_fpp_exponent(&filters, fpp, tightening_ratio);
+        ScalableCuckooFilter {
+            filters: filters.into_iter().map(Arc::new).collect(),
+            initial_item_count: item_count,
+            initial_fpp: fpp,
+            growth_ratio,
+            tightening_ratio,
+            fpp_exponent,
+        }
+    }
+
+    // Returns the exponent of the tightening ratio for the next cuckoo filter after `filters`. It
+    // is at least the number of cuckoo filters, and is increased until the next cuckoo filter uses
+    // at least as many fingerprint bits as any of the cuckoo filters, which may have been merged.
+    fn get_fpp_exponent(filters: &[CuckooFilter<T, B>], fpp: f64, tightening_ratio: f64) -> i32 {
+        let max_fingerprint_bit_count = filters
+            .iter()
+            .map(|filter| filter.fingerprint_bit_count())
+            .max()
+            .expect("Expected non-empty filters.");
+        let entries_per_index = filters[filters.len() - 1].entries_per_index();
+        let mut fpp_exponent = filters.len() as i32;
+        while tightening_ratio < 1.0
+            && cuckoo::get_fingerprint_bit_count(
+                fpp * tightening_ratio.powi(fpp_exponent),
+                entries_per_index,
+            ) < max_fingerprint_bit_count
+        {
+            fpp_exponent += 1;
         }
+        fpp_exponent
     }
 
     fn try_grow(&mut self) {
         let mut new_filter_opt = None;
         {
-            let exponent = self.filters.len() as i32;
-            let filter = self
-                .filters
-                .last_mut()
-                .expect("Expected non-empty filters.");
+            let filter = self.filters.last().expect("Expected non-empty filters.");
 
             if filter.is_nearly_full() || filter.len() == filter.capacity() {
                 new_filter_opt = Some(CuckooFilter::from_entries_per_index_with_hashers(
                     (filter.capacity() as f64 * self.growth_ratio).ceil() as usize,
-                    self.initial_fpp * self.tightening_ratio.powi(exponent),
+                    self.initial_fpp * self.tightening_ratio.powi(self.fpp_exponent),
                     filter.entries_per_index(),
                     *filter.hashers(),
                 ));
@@ -234,7 +361,8 @@ where
         }
 
         if let Some(new_filter) = new_filter_opt {
-            self.filters.push(new_filter);
+            self.filters.push(Arc::new(new_filter));
+            self.fpp_exponent += 1;
         }
     }
 
@@ -254,12 +382,30 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        if !self.filters.iter().any(|filter| filter.contains(item)) {
+        let hash = self.filters[0].hash(item);
+        self.insert_hash(hash);
+    }
+
+    /// Inserts an element into the scalable cuckoo filter using its precomputed 128-bit hash. The
+    /// upper 64 bits of `hash` determine the fingerprint and the lower 64 bits determine the first
+    /// index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter = ScalableCuckooFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u128) {
+        if !self.contains_hash(hash) {
             let filter = self
                 .filters
                 .last_mut()
                 .expect("Expected non-empty filters.");
-            filter.insert(item);
+            Arc::make_mut(filter).check_and_insert_hash(hash);
         }
         self.try_grow();
     }
@@ -277,12 +423,32 @@ where
     /// filter.insert("foo");
     /// assert!(filter.contains("foo"));
     /// ```
-    pub fn contains<U>(&mut self, item: &U) -> bool
+    pub fn contains<U>(&self, item: &U) -> bool
     where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        self.filters.iter().any(|filter| filter.contains(item))
+        let hash = self.filters[0].hash(item);
+        self.contains_hash(hash)
+    }
+
+    /// Checks if an element is possibly in the scalable cuckoo filter using its precomputed
+    /// 128-bit hash. The upper 64 bits of `hash` determine the fingerprint and the lower 64 bits
+    /// determine the first index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter = ScalableCuckooFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn contains_hash(&self, hash: u128) -> bool {
+        self.filters.iter().any(|filter| filter.contains_hash(hash))
     }
 
     /// Removes an element from the scalable cuckoo filter.
@@ -306,8 +472,31 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
+        let hash = self.filters[0].hash(item);
+        self.remove_hash(hash);
+    }
+
+    /// Removes an element from the scalable cuckoo filter using its precomputed 128-bit hash. The
+    /// upper 64 bits of `hash` determine the fingerprint and the lower 64 bits determine the first
+    /// index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter = ScalableCuckooFilter::<String>::new(100, 0.01, 2.0, 0.5);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// filter.remove_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210));
+    /// ```
+    pub fn remove_hash(&mut self, hash: u128) {
+        // only the cuckoo filters that contain the item are copied if they are shared
         for filter in &mut self.filters {
-            filter.remove(item);
+            if filter.contains_hash(hash) {
+                Arc::make_mut(filter).remove_hash(hash);
+            }
         }
     }
 
@@ -391,6 +580,39 @@ where
         self.filters.len()
     }
 
+    /// Returns the cuckoo filters in the scalable cuckoo filter, from the oldest to the newest.
+    /// Their fingerprints can be used to rebuild the scalable cuckoo filter without the original
+    /// items. Since each cuckoo filter has a tighter false positive probability than the
+    /// previous one, the cuckoo filters can only be merged into a single `CuckooFilter` if they
+    /// have the same fingerprint bit count, such as when `tightening_ratio` is 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, ScalableCuckooFilter};
+    ///
+    /// let mut filter = ScalableCuckooFilter::<String>::new(4, 0.01, 2.0, 1.0);
+    /// for item in &["foo", "bar", "baz", "qux", "quux"] {
+    ///     filter.insert(*item);
+    /// }
+    /// assert_eq!(filter.filter_count(), 2);
+    ///
+    /// let filters = filter.filters();
+    /// let merged_filter = CuckooFilter::<String>::from_fingerprints_with_hashers(
+    ///     filter.len(),
+    ///     filters[0].fingerprint_bit_count(),
+    ///     filters[0].entries_per_index(),
+    ///     filters[0].initial_bucket_len(),
+    ///     *filter.hashers(),
+    ///     filters.iter().flat_map(|filter| filter.fingerprints()),
+    /// );
+    /// assert!(merged_filter.contains("foo"));
+    /// assert!(merged_filter.contains("quux"));
+    /// ```
+    pub fn filters(&self) -> &[Arc<CuckooFilter<T, B>>] {
+        &self.filters
+    }
+
     /// Clears the scalable cuckoo filter, removing all elements.
     ///
     /// # Examples
@@ -408,12 +630,114 @@ where
     pub fn clear(&mut self) {
         let initial_filter = self.filters.first().expect("Expected non-empty filters.");
 
-        self.filters = vec![CuckooFilter::from_entries_per_index_with_hashers(
+        self.filters = vec![Arc::new(CuckooFilter::from_entries_per_index_with_hashers(
             self.initial_item_count,
             self.initial_fpp,
             initial_filter.entries_per_index(),
             *initial_filter.hashers(),
-        )];
+        ))];
+        self.fpp_exponent = 1;
+    }
+
+    /// Migrates the fingerprints of the scalable cuckoo filter into fewer, right-sized cuckoo
+    /// filters. Empty cuckoo filters are dropped, and cuckoo filters with the same fingerprint bit
+    /// count are merged into a single cuckoo filter sized for their remaining items. Since every
+    /// fingerprint stays in a cuckoo filter with the same fingerprint bit count, the overall false
+    /// positive probability of the scalable cuckoo filter is still bounded by
+    /// `initial_fpp * 1 / (1 - tightening_ratio)`.
+    ///
+    /// Cuckoo filters that can only be merged by using every bit of their fingerprints to select a
+    /// region are resized separately instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::ScalableCuckooFilter;
+    ///
+    /// let mut filter = ScalableCuckooFilter::<u32>::new(100, 0.01, 2.0, 0.5);
+    /// for item in 0..1000 {
+    ///     filter.insert(&item);
+    /// }
+    /// for item in 0..990 {
+    ///     filter.remove(&item);
+    /// }
+    /// assert_eq!(filter.filter_count(), 4);
+    ///
+    /// filter.compact();
+    /// assert_eq!(filter.filter_count(), 1);
+    /// assert!(filter.capacity() < 100);
+    /// ```
+    pub fn compact(&mut self) {
+        if self.is_empty() {
+            self.clear();
+            return;
+        }
+
+        // each group contains the non-empty cuckoo filters with the same fingerprint bit count,
+        // ordered by their newest cuckoo filter so that insertions still go to the newest one
+        let mut groups: Vec<Vec<Arc<CuckooFilter<T, B>>>> = Vec::new();
+        for filter in mem::take(&mut self.filters) {
+            if filter.is_empty() && !filter.is_nearly_full() {
+                continue;
+            }
+            let group_index = groups.iter().position(|group| {
+                group[0].fingerprint_bit_count() == filter.fingerprint_bit_count()
+                    && group[0].entries_per_index() == filter.entries_per_index()
+            });
+            let mut group = match group_index {
+                Some(group_index) => groups.remove(group_index),
+                None => Vec::new(),
+            };
+            group.push(filter);
+            groups.push(group);
+        }
+
+        for group in groups {
+            self.filters.extend(Self::merge_filters(group));
+        }
+    }
+
+    fn merge_filters(filters: Vec<Arc<CuckooFilter<T, B>>>) -> Vec<Arc<CuckooFilter<T, B>>> {
+        let get_item_count =
+            |filter: &Arc<CuckooFilter<T, B>>| filter.len() + filter.extra_items_len();
+        let fingerprint_bit_count = filters[0].fingerprint_bit_count();
+        let entries_per_index = filters[0].entries_per_index();
+        let hash_builders = *filters[0].hashers();
+
+        let item_count = filters.iter().map(get_item_count).sum();
+        let initial_bucket_len = filters
+            .iter()
+            .map(|filter| filter.initial_bucket_len())
+            .min()
+            .expect("Expected non-empty filters.");
+        let bucket_len = cuckoo::get_bucket_len(item_count, entries_per_index);
+        let growth_count = (cmp::max(bucket_len, initial_bucket_len) / initial_bucket_len)
+            .trailing_zeros() as usize;
+
+        if growth_count < fingerprint_bit_count {
+            vec![Arc::new(CuckooFilter::from_fingerprints_with_hashers(
+                item_count,
+                fingerprint_bit_count,
+                entries_per_index,
+                initial_bucket_len,
+                hash_builders,
+                filters.iter().flat_map(|filter| filter.fingerprints()),
+            ))]
+        } else {
+            filters
+                .iter()
+                .map(|filter| {
+                    Arc::new(CuckooFilter::from_fingerprints_with_hashers(
+                        get_item_count(filter),
+                        fingerprint_bit_count,
+                        entries_per_index,
+                        filter.initial_bucket_len(),
+                        hash_builders,
+                        filter.fingerprints(),
+                    ))
+                })
+                .collect()
+        }
     }
 
     /// Returns the estimated false positive probability of the scalable cuckoo filter. This value
@@ -455,10 +779,28 @@ where
     }
 }
 
+// Implemented manually so that cloning a scalable cuckoo filter does not require `T: Clone`.
+impl<T, B> Clone for ScalableCuckooFilter<T, B> {
+    fn clone(&self) -> Self {
+        ScalableCuckooFilter {
+            filters: self.filters.clone(),
+            initial_item_count: self.initial_item_count,
+            initial_fpp: self.initial_fpp,
+            growth_ratio: self.growth_ratio,
+            tightening_ratio: self.tightening_ratio,
+            fpp_exponent: self.fpp_exponent,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ScalableCuckooFilter;
-    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use crate::cuckoo::CuckooFilter;
+    use crate::util::tests::{hash_builder_1, hash_builder_2, hash_item};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, RwLock};
+    use std::thread;
 
     #[test]
     pub fn test_new() {
@@ -570,6 +912,413 @@ mod tests {
         assert!(scf.is_empty());
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut scf = ScalableCuckooFilter::<String>::with_hashers(
+            100,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+
+        scf.insert_hash(hash_item("foo"));
+        assert!(scf.contains("foo"));
+
+        scf.insert("bar");
+        assert!(scf.contains_hash(hash_item("bar")));
+        assert!(!scf.contains_hash(hash_item("baz")));
+
+        scf.remove_hash(hash_item("foo"));
+        assert!(!scf.contains("foo"));
+        assert_eq!(scf.len(), 1);
+    }
+
+    #[test]
+    fn test_from_filters() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..500 {
+            scf.insert(&item);
+        }
+        assert!(scf.filter_count() > 1);
+
+        let filters = scf
+            .filters()
+            .iter()
+            .map(|filter| {
+                CuckooFilter::<u32>::from_fingerprints_with_hashers(
+                    filter.len() + filter.extra_items_len(),
+                    filter.fingerprint_bit_count(),
+                    filter.entries_per_index(),
+                    filter.initial_bucket_len(),
+                    *filter.hashers(),
+                    filter.fingerprints(),
+                )
+            })
+            .collect();
+        let mut rebuilt_scf = ScalableCuckooFilter::from_filters(64, 0.01, 2.0, 0.5, filters);
+        assert_eq!(rebuilt_scf.filter_count(), scf.filter_count());
+        assert_eq!(rebuilt_scf.len(), scf.len());
+        assert!(rebuilt_scf.capacity() <= scf.capacity());
+        for item in 0..500 {
+            assert!(rebuilt_scf.contains(&item));
+        }
+
+        for item in 500..1000 {
+            rebuilt_scf.insert(&item);
+        }
+        for item in 0..1000 {
+            assert!(rebuilt_scf.contains(&item));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_filters_merge_different_fingerprint_bit_counts() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..500 {
+            scf.insert(&item);
+        }
+        let fingerprint_bit_counts: Vec<_> = scf
+            .filters()
+            .iter()
+            .map(|filter| filter.fingerprint_bit_count())
+            .collect();
+        assert!(fingerprint_bit_counts
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+
+        let initial_filter = &scf.filters()[0];
+        CuckooFilter::<u32>::from_fingerprints_with_hashers(
+            scf.len(),
+            initial_filter.fingerprint_bit_count(),
+            initial_filter.entries_per_index(),
+            initial_filter.initial_bucket_len(),
+            *initial_filter.hashers(),
+            scf.filters()
+                .iter()
+                .flat_map(|filter| filter.fingerprints()),
+        );
+    }
+
+    #[test]
+    fn test_filters_merge() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            1.0,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..500 {
+            scf.insert(&item);
+        }
+        assert!(scf.filter_count() > 1);
+
+        let filters = scf.filters();
+        let filter = CuckooFilter::<u32>::from_fingerprints_with_hashers(
+            scf.len(),
+            filters[0].fingerprint_bit_count(),
+            filters[0].entries_per_index(),
+            filters[0].initial_bucket_len(),
+            *scf.hashers(),
+            filters.iter().flat_map(|filter| filter.fingerprints()),
+        );
+        assert_eq!(filter.len() + filter.extra_items_len(), scf.len());
+        assert!(filter.capacity() < scf.capacity());
+        for item in 0..500 {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..500 {
+            scf.insert(&item);
+        }
+        let filter_count = scf.filter_count();
+        assert!(filter_count > 1);
+
+        let snapshot = scf.clone();
+        for (filter, snapshot_filter) in scf.filters.iter().zip(&snapshot.filters) {
+            assert!(Arc::ptr_eq(filter, snapshot_filter));
+        }
+
+        scf.insert(&500);
+        assert_eq!(scf.filter_count(), filter_count);
+        assert!(scf.contains(&500));
+        assert!(!snapshot.contains(&500));
+        assert!(Arc::ptr_eq(&scf.filters[0], &snapshot.filters[0]));
+        assert!(!Arc::ptr_eq(
+            &scf.filters[filter_count - 1],
+            &snapshot.filters[filter_count - 1],
+        ));
+
+        scf.remove(&0);
+        assert!(!scf.contains(&0));
+        assert!(snapshot.contains(&0));
+        for item in 0..500 {
+            assert!(snapshot.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_concurrent_snapshots() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..500 {
+            scf.insert(&item);
+        }
+        let snapshot = Arc::new(RwLock::new(Arc::new(scf.clone())));
+        let is_finished = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let snapshot = Arc::clone(&snapshot);
+                let is_finished = Arc::clone(&is_finished);
+                thread::spawn(move || {
+                    while !is_finished.load(Ordering::Acquire) {
+                        let scf = Arc::clone(&snapshot.read().unwrap());
+                        for item in 0..500 {
+                            assert!(scf.contains(&item));
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for item in 500..5000 {
+            scf.insert(&item);
+            if item % 100 == 0 {
+                *snapshot.write().unwrap() = Arc::new(scf.clone());
+            }
+        }
+        *snapshot.write().unwrap() = Arc::new(scf.clone());
+        is_finished.store(true, Ordering::Release);
+
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        let scf = snapshot.read().unwrap();
+        for item in 0..5000 {
+            assert!(scf.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        for item in 0..900 {
+            scf.remove(&item);
+        }
+        let items: Vec<_> = (0..1000).filter(|item| scf.contains(item)).collect();
+        let filter_count = scf.filter_count();
+        let capacity = scf.capacity();
+        let len = scf.len();
+
+        scf.compact();
+        assert!(scf.filter_count() <= filter_count);
+        assert!(scf.capacity() < capacity);
+        assert_eq!(scf.len(), len);
+        for item in &items {
+            assert!(scf.contains(item));
+        }
+
+        for item in 1000..2000 {
+            scf.insert(&item);
+        }
+        for item in items.iter().chain(&(1000..2000).collect::<Vec<_>>()) {
+            assert!(scf.contains(item));
+        }
+    }
+
+    #[test]
+    fn test_compact_tightening() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        for item in 0..990 {
+            scf.remove(&item);
+        }
+        let fingerprint_bit_counts: Vec<_> = scf
+            .filters()
+            .iter()
+            .map(|filter| filter.fingerprint_bit_count())
+            .collect();
+
+        scf.compact();
+        let compacted_filter_count = scf.filter_count();
+        assert!(compacted_filter_count < fingerprint_bit_counts.len());
+
+        for item in 1000..5000 {
+            scf.insert(&item);
+        }
+        assert!(scf.filter_count() > compacted_filter_count + 1);
+
+        let max_fingerprint_bit_count = fingerprint_bit_counts.iter().max().unwrap();
+        let new_fingerprint_bit_counts: Vec<_> = scf.filters()[compacted_filter_count..]
+            .iter()
+            .map(|filter| filter.fingerprint_bit_count())
+            .collect();
+        assert!(new_fingerprint_bit_counts[0] > *max_fingerprint_bit_count);
+        assert!(new_fingerprint_bit_counts
+            .windows(2)
+            .all(|window| window[0] < window[1]));
+    }
+
+    #[test]
+    fn test_from_filters_tightening() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        for item in 0..990 {
+            scf.remove(&item);
+        }
+        scf.compact();
+        let max_fingerprint_bit_count = scf
+            .filters()
+            .iter()
+            .map(|filter| filter.fingerprint_bit_count())
+            .max()
+            .unwrap();
+
+        let filters = scf
+            .filters()
+            .iter()
+            .map(|filter| (**filter).clone())
+            .collect();
+        let mut rebuilt_scf = ScalableCuckooFilter::from_filters(64, 0.01, 2.0, 0.5, filters);
+        let filter_count = rebuilt_scf.filter_count();
+        for item in 1000..2000 {
+            rebuilt_scf.insert(&item);
+        }
+        assert!(rebuilt_scf.filter_count() > filter_count);
+        assert!(
+            rebuilt_scf.filters()[filter_count].fingerprint_bit_count()
+                >= max_fingerprint_bit_count
+        );
+    }
+
+    #[test]
+    fn test_compact_merge() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            1.0,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        for item in 0..900 {
+            scf.remove(&item);
+        }
+        let items: Vec<_> = (0..1000).filter(|item| scf.contains(item)).collect();
+        assert!(scf.filter_count() > 1);
+
+        scf.compact();
+        assert_eq!(scf.filter_count(), 1);
+        for item in &items {
+            assert!(scf.contains(item));
+        }
+    }
+
+    #[test]
+    fn test_compact_no_bits() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            4,
+            0.3,
+            2.0,
+            1.0,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        let items: Vec<_> = (0..1000).filter(|item| scf.contains(item)).collect();
+        let filter_count = scf.filter_count();
+        let len = scf.len();
+        assert!(filter_count > 1);
+
+        scf.compact();
+        assert_eq!(scf.filter_count(), filter_count);
+        assert_eq!(scf.len(), len);
+        for item in &items {
+            assert!(scf.contains(item));
+        }
+    }
+
+    #[test]
+    fn test_compact_empty() {
+        let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
+            64,
+            0.01,
+            2.0,
+            0.5,
+            [hash_builder_1(), hash_builder_2()],
+        );
+        let capacity = scf.capacity();
+        for item in 0..1000 {
+            scf.insert(&item);
+        }
+        for item in 0..1000 {
+            scf.remove(&item);
+        }
+
+        scf.compact();
+        assert_eq!(scf.filter_count(), 1);
+        assert_eq!(scf.capacity(), capacity);
+        assert!(scf.is_empty());
+    }
+
     #[test]
     fn test_estimated_fpp() {
         let mut scf = ScalableCuckooFilter::<u32>::with_hashers(
@@ -0,0 +1,169 @@
+//! Error types shared by multiple collections.
+
+use std::error::Error;
+use std::fmt;
+
+/// An error returned when two collections cannot be merged because they were not constructed with
+/// the same parameters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MergeError {
+    /// The collections have a different number of bits or slots.
+    LengthMismatch,
+    /// The collections use a different number of hash functions.
+    HasherCountMismatch,
+    /// The collections use different hasher builders.
+    HasherMismatch,
+    /// The collections were constructed with different growth or false positive parameters.
+    ParameterMismatch,
+    /// A collection has grown, so its items are no longer stored in the same positions as the
+    /// items of the other collection.
+    HasGrown,
+    /// The merged collection would hold more items than it can store.
+    CapacityExceeded,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MergeError::LengthMismatch => "collections have different lengths",
+            MergeError::HasherCountMismatch => "collections have different hasher counts",
+            MergeError::HasherMismatch => "collections have different hasher builders",
+            MergeError::ParameterMismatch => "collections have different parameters",
+            MergeError::HasGrown => "collection has grown",
+            MergeError::CapacityExceeded => "merged collection exceeds its capacity",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for MergeError {}
+
+/// An error returned when a collection cannot be reconstructed from its byte representation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The number of bytes is not valid for the collection.
+    InvalidLength,
+    /// The bytes describe parameters that are not valid for the collection.
+    InvalidHeader,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            DecodeError::InvalidLength => "invalid number of bytes",
+            DecodeError::InvalidHeader => "invalid header",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for DecodeError {}
+
+/// An error returned when an item cannot be inserted into a full cuckoo filter. The filter is
+/// unchanged and does not contain the fingerprint of the item.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FilterFullError {
+    fingerprint: u64,
+    index: usize,
+}
+
+impl FilterFullError {
+    pub(crate) fn new(fingerprint: u64, index: usize) -> Self {
+        FilterFullError { fingerprint, index }
+    }
+
+    /// Returns the fingerprint of the item that could not be inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, OverflowPolicy};
+    /// use probabilistic_collections::{InsertError, SipHasherBuilder};
+    ///
+    /// let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+    ///     1,
+    ///     8,
+    ///     1,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.set_overflow_policy(OverflowPolicy::Reject);
+    ///
+    /// let error = (0..10).find_map(|item| filter.try_insert(&item).err()).unwrap();
+    /// match error {
+    ///     InsertError::Full(error) => assert_ne!(error.fingerprint(), 0),
+    ///     InsertError::TooManyCopies => unreachable!(),
+    /// }
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// Returns the smaller of the two bucket indexes of the item that could not be inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::cuckoo::{CuckooFilter, OverflowPolicy};
+    /// use probabilistic_collections::{InsertError, SipHasherBuilder};
+    ///
+    /// let mut filter = CuckooFilter::<u32>::from_parameters_with_hashers(
+    ///     1,
+    ///     8,
+    ///     1,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// filter.set_overflow_policy(OverflowPolicy::Reject);
+    ///
+    /// let error = (0..10).find_map(|item| filter.try_insert(&item).err()).unwrap();
+    /// match error {
+    ///     InsertError::Full(error) => assert!(error.index() < filter.bucket_len()),
+    ///     InsertError::TooManyCopies => unreachable!(),
+    /// }
+    /// ```
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for FilterFullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("filter is full")
+    }
+}
+
+impl Error for FilterFullError {}
+
+/// An error returned when an item cannot be inserted into a cuckoo filter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InsertError {
+    /// The cuckoo filter is full and the overflow policy does not allow the fingerprint of the
+    /// item to be stored. The filter is unchanged.
+    Full(FilterFullError),
+    /// The cuckoo filter is in multiset mode and the fingerprint of the item already has the
+    /// maximum number of copies. The filter is unchanged.
+    TooManyCopies,
+}
+
+impl From<FilterFullError> for InsertError {
+    fn from(error: FilterFullError) -> Self {
+        InsertError::Full(error)
+    }
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::Full(error) => error.fmt(f),
+            InsertError::TooManyCopies => f.write_str("fingerprint has too many copies"),
+        }
+    }
+}
+
+impl Error for InsertError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InsertError::Full(error) => Some(error),
+            InsertError::TooManyCopies => None,
+        }
+    }
+}
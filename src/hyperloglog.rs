@@ -130,7 +130,22 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let hash = util::hash(&self.hash_builder, &item);
+        self.insert_hash(util::hash(&self.hash_builder, &item));
+    }
+
+    /// Inserts an item into the `HyperLogLog<T>` using its precomputed 64-bit hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::hyperloglog::HyperLogLog;
+    ///
+    /// let mut hhl = HyperLogLog::<u32>::new(0.1);
+    ///
+    /// hhl.insert_hash(0x0123_4567_89ab_cdef);
+    /// assert!(!hhl.is_empty());
+    /// ```
+    pub fn insert_hash(&mut self, hash: u64) {
         let register_index = hash as usize & (self.registers.len() - 1);
         let value = (!hash >> self.p).trailing_zeros() as u8;
         self.registers[register_index] = cmp::max(self.registers[register_index], value + 1);
@@ -275,7 +290,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::HyperLogLog;
-    use crate::util::tests::hash_builder_1;
+    use crate::util::{self, tests::hash_builder_1};
     use std::f64::EPSILON;
 
     #[test]
@@ -309,6 +324,19 @@ mod tests {
         assert!(hhl.is_empty());
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut hhl1 = HyperLogLog::<u32>::with_hasher(0.01, hash_builder_1());
+        let mut hhl2 = HyperLogLog::<u32>::with_hasher(0.01, hash_builder_1());
+
+        for key in 0..1000 {
+            hhl1.insert(&key);
+            hhl2.insert_hash(util::hash(&hash_builder_1(), &key));
+        }
+
+        assert_eq!(hhl1.registers, hhl2.registers);
+    }
+
     #[test]
     fn test_merge() {
         let mut hhl1 = HyperLogLog::<u32>::with_hasher(0.01, hash_builder_1());
@@ -0,0 +1,614 @@
+//! Space-efficient probabilistic data structure for reconciling the differences between two sets.
+
+use crate::util::fmix64;
+use crate::{DecodeError, DoubleHasher, MergeError, SipHasherBuilder};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::hash::BuildHasher;
+
+const CELL_BYTE_COUNT: usize = 24;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+struct Cell {
+    count: i64,
+    key_sum: u64,
+    hash_sum: u64,
+}
+
+// The check hash of a key is `fmix64` of the key because it is a bijection, so distinct keys never
+// have the same check hash, and it is independent of the hasher builders used to place keys in
+// cells.
+impl Cell {
+    fn toggle(&mut self, key: u64, count: i64) {
+        self.count = self.count.wrapping_add(count);
+        self.key_sum ^= key;
+        self.hash_sum ^= fmix64(key);
+    }
+
+    fn is_pure(&self) -> bool {
+        (self.count == 1 || self.count == -1) && fmix64(self.key_sum) == self.hash_sum
+    }
+
+    fn is_empty(&self) -> bool {
+        self.count == 0 && self.key_sum == 0 && self.hash_sum == 0
+    }
+}
+
+/// A space-efficient probabilistic data structure that stores a set of `u64` keys such that the
+/// difference between two sets can be recovered from the difference of their tables.
+///
+/// An invertible bloom lookup table is an array of cells. Each key is added to `hasher_count`
+/// cells, and each cell stores the number of keys in the cell, the XOR of the keys in the cell,
+/// and the XOR of the check hashes of the keys in the cell. Subtracting the table of one set from
+/// the table of another set cancels out the keys that are in both sets, so the remaining keys can
+/// be listed by repeatedly removing keys from cells that contain exactly one key. Listing
+/// succeeds with high probability if `hasher_count` is 3 or 4 and the number of cells is at least
+/// around 1.5 times the size of the symmetric difference for large differences, or twice the size
+/// of the symmetric difference for small differences.
+///
+/// Both tables must be constructed with the same number of cells, the same number of hash
+/// functions, and the same hasher builders. Keys of other types should be hashed or mapped to
+/// `u64` identifiers before being inserted.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+/// use probabilistic_collections::SipHasherBuilder;
+///
+/// let hash_builders = [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)];
+/// let mut table1 = InvertibleBloomLookupTable::with_hashers(30, 3, hash_builders);
+/// let mut table2 = InvertibleBloomLookupTable::with_hashers(30, 3, hash_builders);
+///
+/// for key in 0..1000 {
+///     table1.insert(key);
+///     table2.insert(key + 5);
+/// }
+///
+/// table1.subtract(&table2).unwrap();
+/// let (mut local_keys, mut remote_keys) = table1.list_entries().unwrap();
+/// local_keys.sort();
+/// remote_keys.sort();
+///
+/// assert_eq!(local_keys, vec![0, 1, 2, 3, 4]);
+/// assert_eq!(remote_keys, vec![1000, 1001, 1002, 1003, 1004]);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct InvertibleBloomLookupTable<B = SipHasherBuilder> {
+    cells: Vec<Cell>,
+    hasher: DoubleHasher<u64, B>,
+    hasher_count: usize,
+}
+
+impl InvertibleBloomLookupTable {
+    /// Constructs a new, empty `InvertibleBloomLookupTable` with at least `cell_count` cells and
+    /// `hasher_count` hash functions. The number of cells is rounded up to a multiple of
+    /// `hasher_count`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` or `hasher_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    /// ```
+    pub fn new(cell_count: usize, hasher_count: usize) -> Self {
+        Self::with_hashers(
+            cell_count,
+            hasher_count,
+            [
+                SipHasherBuilder::from_entropy(),
+                SipHasherBuilder::from_entropy(),
+            ],
+        )
+    }
+}
+
+impl<B> InvertibleBloomLookupTable<B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `InvertibleBloomLookupTable` with at least `cell_count` cells,
+    /// `hasher_count` hash functions, and two hasher builders for double hashing. The number of
+    /// cells is rounded up to a multiple of `hasher_count`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_count` or `hasher_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let table = InvertibleBloomLookupTable::with_hashers(
+    ///     100,
+    ///     3,
+    ///     [SipHasherBuilder::from_seed(0, 0), SipHasherBuilder::from_seed(1, 1)],
+    /// );
+    /// ```
+    pub fn with_hashers(cell_count: usize, hasher_count: usize, hash_builders: [B; 2]) -> Self {
+        assert!(cell_count > 0 && hasher_count > 0);
+        InvertibleBloomLookupTable {
+            cells: vec![
+                Cell::default();
+                (cell_count + hasher_count - 1) / hasher_count * hasher_count
+            ],
+            hasher: DoubleHasher::with_hashers(hash_builders),
+            hasher_count,
+        }
+    }
+
+    /// Constructs an `InvertibleBloomLookupTable` from the bytes returned by `to_bytes`, using
+    /// `hasher_count` hash functions and two hasher builders for double hashing. The number of
+    /// hash functions and the hasher builders are not part of the byte representation, so they
+    /// must match the ones used to construct the original table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of bytes is zero or if it does not correspond to a number of
+    /// cells that is a multiple of `hasher_count`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hasher_count` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table = InvertibleBloomLookupTable::new(100, 3);
+    /// table.insert(1);
+    ///
+    /// let bytes = table.to_bytes();
+    /// let de_table =
+    ///     InvertibleBloomLookupTable::from_bytes(&bytes, 3, *table.hashers()).unwrap();
+    ///
+    /// assert_eq!(de_table.list_entries(), Some((vec![1], vec![])));
+    /// ```
+    pub fn from_bytes(
+        bytes: &[u8],
+        hasher_count: usize,
+        hash_builders: [B; 2],
+    ) -> Result<Self, DecodeError> {
+        assert!(hasher_count > 0);
+        if bytes.is_empty()
+            || bytes.len() % CELL_BYTE_COUNT != 0
+            || (bytes.len() / CELL_BYTE_COUNT) % hasher_count != 0
+        {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let cells = bytes
+            .chunks(CELL_BYTE_COUNT)
+            .map(|cell_bytes| {
+                let mut buffers = [[0; 8]; 3];
+                for (buffer, word_bytes) in buffers.iter_mut().zip(cell_bytes.chunks(8)) {
+                    buffer.copy_from_slice(word_bytes);
+                }
+                Cell {
+                    count: i64::from_le_bytes(buffers[0]),
+                    key_sum: u64::from_le_bytes(buffers[1]),
+                    hash_sum: u64::from_le_bytes(buffers[2]),
+                }
+            })
+            .collect();
+        Ok(InvertibleBloomLookupTable {
+            cells,
+            hasher: DoubleHasher::with_hashers(hash_builders),
+            hasher_count,
+        })
+    }
+
+    fn get_cell_indexes(&self, key: u64) -> impl Iterator<Item = usize> {
+        // Each hash function indexes into its own partition of cells so that a key is always
+        // added to `hasher_count` distinct cells. The hashes are mixed before they are reduced
+        // because double hashing only has two degrees of freedom, so two keys that collide in the
+        // first two partitions would otherwise collide in every partition.
+        let partition_len = self.cells.len() / self.hasher_count;
+        self.hasher
+            .hash(&key)
+            .take(self.hasher_count)
+            .enumerate()
+            .map(move |(index, hash)| {
+                index * partition_len + (fmix64(hash) % partition_len as u64) as usize
+            })
+    }
+
+    fn toggle(&mut self, key: u64, count: i64) {
+        let cell_indexes: Vec<usize> = self.get_cell_indexes(key).collect();
+        for index in cell_indexes {
+            self.cells[index].toggle(key, count);
+        }
+    }
+
+    /// Inserts a key into the invertible bloom lookup table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// table.insert(1);
+    /// assert_eq!(table.list_entries(), Some((vec![1], vec![])));
+    /// ```
+    pub fn insert(&mut self, key: u64) {
+        self.toggle(key, 1);
+    }
+
+    /// Removes a key from the invertible bloom lookup table. Removing a key that was never
+    /// inserted records it as a key that is only in the other set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// table.insert(1);
+    /// table.remove(1);
+    /// assert_eq!(table.list_entries(), Some((vec![], vec![])));
+    ///
+    /// table.remove(2);
+    /// assert_eq!(table.list_entries(), Some((vec![], vec![2])));
+    /// ```
+    pub fn remove(&mut self, key: u64) {
+        self.toggle(key, -1);
+    }
+
+    /// Subtracts `other` from the invertible bloom lookup table so that it only stores the keys
+    /// that are in exactly one of the two tables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two tables do not have the same number of cells, the same number
+    /// of hash functions, and the same hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table1 = InvertibleBloomLookupTable::new(100, 3);
+    /// let mut table2 = InvertibleBloomLookupTable::with_hashers(100, 3, *table1.hashers());
+    ///
+    /// table1.insert(1);
+    /// table1.insert(2);
+    /// table2.insert(2);
+    /// table2.insert(3);
+    /// table1.subtract(&table2).unwrap();
+    ///
+    /// assert_eq!(table1.list_entries(), Some((vec![1], vec![3])));
+    /// ```
+    pub fn subtract(&mut self, other: &InvertibleBloomLookupTable<B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        if self.len() != other.len() {
+            return Err(MergeError::LengthMismatch);
+        }
+        if self.hasher_count() != other.hasher_count() {
+            return Err(MergeError::HasherCountMismatch);
+        }
+        if self.hashers() != other.hashers() {
+            return Err(MergeError::HasherMismatch);
+        }
+
+        for (cell, other_cell) in self.cells.iter_mut().zip(&other.cells) {
+            cell.count = cell.count.wrapping_sub(other_cell.count);
+            cell.key_sum ^= other_cell.key_sum;
+            cell.hash_sum ^= other_cell.hash_sum;
+        }
+        Ok(())
+    }
+
+    /// Lists the keys stored in the invertible bloom lookup table by peeling cells that contain a
+    /// single key. The first vector contains the keys that were inserted, and the second vector
+    /// contains the keys that were removed. After a subtraction, these are the keys that are only
+    /// in the table and the keys that are only in the subtracted table. Returns `None` if the
+    /// table could not be completely peeled, which happens with high probability if the table
+    /// stores too many keys for its number of cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table = InvertibleBloomLookupTable::new(3, 3);
+    ///
+    /// table.insert(1);
+    /// assert_eq!(table.list_entries(), Some((vec![1], vec![])));
+    ///
+    /// table.insert(2);
+    /// table.insert(3);
+    /// assert_eq!(table.list_entries(), None);
+    /// ```
+    pub fn list_entries(&self) -> Option<(Vec<u64>, Vec<u64>)> {
+        let mut cells = self.cells.clone();
+        let mut inserted_keys = Vec::new();
+        let mut removed_keys = Vec::new();
+        let mut pure_indexes: Vec<usize> = (0..cells.len())
+            .filter(|index| cells[*index].is_pure())
+            .collect();
+
+        while let Some(index) = pure_indexes.pop() {
+            let cell = cells[index];
+            if !cell.is_pure() {
+                continue;
+            }
+
+            if cell.count == 1 {
+                inserted_keys.push(cell.key_sum);
+            } else {
+                removed_keys.push(cell.key_sum);
+            }
+
+            for cell_index in self.get_cell_indexes(cell.key_sum) {
+                cells[cell_index].toggle(cell.key_sum, -cell.count);
+                if cells[cell_index].is_pure() {
+                    pure_indexes.push(cell_index);
+                }
+            }
+        }
+
+        if cells.iter().all(Cell::is_empty) {
+            Some((inserted_keys, removed_keys))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the byte representation of the invertible bloom lookup table. Each cell is encoded
+    /// as its count, key sum, and check hash sum in little-endian order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// assert_eq!(table.to_bytes().len(), 102 * 24);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.cells.len() * CELL_BYTE_COUNT);
+        for cell in &self.cells {
+            bytes.extend_from_slice(&cell.count.to_le_bytes());
+            bytes.extend_from_slice(&cell.key_sum.to_le_bytes());
+            bytes.extend_from_slice(&cell.hash_sum.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Returns the number of cells in the invertible bloom lookup table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// assert_eq!(table.len(), 102);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns `true` if the invertible bloom lookup table is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// assert!(!table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the number of hash functions used by the invertible bloom lookup table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// assert_eq!(table.hasher_count(), 3);
+    /// ```
+    pub fn hasher_count(&self) -> usize {
+        self.hasher_count
+    }
+
+    /// Clears the invertible bloom lookup table, removing all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let mut table = InvertibleBloomLookupTable::new(100, 3);
+    ///
+    /// table.insert(1);
+    /// table.clear();
+    ///
+    /// assert_eq!(table.list_entries(), Some((vec![], vec![])));
+    /// ```
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
+    }
+
+    /// Returns a reference to the invertible bloom lookup table's hasher builders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::iblt::InvertibleBloomLookupTable;
+    ///
+    /// let table = InvertibleBloomLookupTable::new(100, 3);
+    /// let hashers = table.hashers();
+    /// ```
+    pub fn hashers(&self) -> &[B; 2] {
+        self.hasher.hashers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvertibleBloomLookupTable;
+    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use crate::{DecodeError, MergeError};
+
+    #[test]
+    fn test_new() {
+        let table = InvertibleBloomLookupTable::new(100, 4);
+
+        assert_eq!(table.len(), 100);
+        assert!(!table.is_empty());
+        assert_eq!(table.hasher_count(), 4);
+        assert_eq!(table.list_entries(), Some((vec![], vec![])));
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut table =
+            InvertibleBloomLookupTable::with_hashers(60, 3, [hash_builder_1(), hash_builder_2()]);
+
+        for key in 0..1000 {
+            table.insert(key);
+        }
+        for key in 20..1000 {
+            table.remove(key);
+        }
+
+        let (mut inserted_keys, removed_keys) = table.list_entries().unwrap();
+        inserted_keys.sort();
+        assert_eq!(inserted_keys, (0..20).collect::<Vec<_>>());
+        assert!(removed_keys.is_empty());
+    }
+
+    #[test]
+    fn test_subtract() {
+        let mut table1 =
+            InvertibleBloomLookupTable::with_hashers(200, 3, [hash_builder_1(), hash_builder_2()]);
+        let mut table2 =
+            InvertibleBloomLookupTable::with_hashers(200, 3, [hash_builder_1(), hash_builder_2()]);
+
+        for key in 0..10_000 {
+            table1.insert(key);
+            table2.insert(key + 50);
+        }
+        table1.subtract(&table2).unwrap();
+
+        let (mut local_keys, mut remote_keys) = table1.list_entries().unwrap();
+        local_keys.sort();
+        remote_keys.sort();
+        assert_eq!(local_keys, (0..50).collect::<Vec<_>>());
+        assert_eq!(remote_keys, (10_000..10_050).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_subtract_mismatch() {
+        let mut table1 =
+            InvertibleBloomLookupTable::with_hashers(150, 3, [hash_builder_1(), hash_builder_2()]);
+
+        let table2 =
+            InvertibleBloomLookupTable::with_hashers(300, 3, [hash_builder_1(), hash_builder_2()]);
+        assert_eq!(table1.subtract(&table2), Err(MergeError::LengthMismatch));
+
+        let table2 =
+            InvertibleBloomLookupTable::with_hashers(150, 5, [hash_builder_1(), hash_builder_2()]);
+        assert_eq!(
+            table1.subtract(&table2),
+            Err(MergeError::HasherCountMismatch)
+        );
+
+        let table2 =
+            InvertibleBloomLookupTable::with_hashers(150, 3, [hash_builder_2(), hash_builder_1()]);
+        assert_eq!(table1.subtract(&table2), Err(MergeError::HasherMismatch));
+    }
+
+    #[test]
+    fn test_list_entries_failure() {
+        let mut table =
+            InvertibleBloomLookupTable::with_hashers(30, 3, [hash_builder_1(), hash_builder_2()]);
+
+        for key in 0..100 {
+            table.insert(key);
+        }
+
+        assert_eq!(table.list_entries(), None);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut table =
+            InvertibleBloomLookupTable::with_hashers(30, 3, [hash_builder_1(), hash_builder_2()]);
+        table.insert(1);
+        table.remove(2);
+
+        let bytes = table.to_bytes();
+        assert_eq!(bytes.len(), 30 * 24);
+
+        let de_table =
+            InvertibleBloomLookupTable::from_bytes(&bytes, 3, [hash_builder_1(), hash_builder_2()])
+                .unwrap();
+        assert_eq!(table.cells, de_table.cells);
+        assert_eq!(de_table.list_entries(), Some((vec![1], vec![2])));
+
+        assert_eq!(
+            InvertibleBloomLookupTable::from_bytes(&[], 3, [hash_builder_1(), hash_builder_2()])
+                .err(),
+            Some(DecodeError::InvalidLength),
+        );
+        assert_eq!(
+            InvertibleBloomLookupTable::from_bytes(
+                &bytes[..29 * 24],
+                3,
+                [hash_builder_1(), hash_builder_2()],
+            )
+            .err(),
+            Some(DecodeError::InvalidLength),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut table = InvertibleBloomLookupTable::new(100, 3);
+        table.insert(1);
+
+        let serialized_table = bincode::serialize(&table).unwrap();
+        let de_table: InvertibleBloomLookupTable = bincode::deserialize(&serialized_table).unwrap();
+
+        assert_eq!(table.cells, de_table.cells);
+        assert_eq!(table.hasher, de_table.hasher);
+        assert_eq!(table.hasher_count, de_table.hasher_count);
+    }
+}
@@ -14,13 +14,18 @@
 //!
 //! The following types of collections are implemented:
 //!
-//! - Approximate Membership in Set: `BloomFilter`, `PartitionedBloomFilter`, `CuckooFilter`,
-//!   `QuotientFilter`
+//! - Approximate Membership in Set: `BloomFilter`, `PartitionedBloomFilter`,
+//!   `CountingBloomFilter`, `BlockedBloomFilter`, `SplitBlockBloomFilter`, `CuckooFilter`,
+//!   `AdaptiveCuckooFilter`, `ConcurrentCuckooFilter`, `QuotientFilter`
 //! - Scalable Approximate Membership in Set: `ScalableBloomFilter`, `ScalableCuckooFilter`
-//! - Approximate Membership in Stream: `BSBloomFilter`, `BSSDBloomFilter`, `RLBSBloomFilter`
-//! - Approximate Item Count: `CountMinSketch`
+//! - Approximate Membership in Static Set: `XorFilter`, `BinaryFuseFilter`, `RibbonFilter`
+//! - Approximate Membership in Stream: `BSBloomFilter`, `BSSDBloomFilter`, `RLBSBloomFilter`,
+//!   `StableBloomFilter`, `SlidingWindowBloomFilter`
+//! - Approximate Map to Small Values: `QuotientMap`, `CuckooMap`
+//! - Approximate Item Count: `CountMinSketch`, `CountingQuotientFilter`
 //! - Approximate Distinct Item Count: `HyperLogLog`
 //! - Set similarity: `MinHash`, `SimHash`
+//! - Set reconciliation: `InvertibleBloomLookupTable`
 //!
 //! ## Usage
 //!
@@ -63,10 +68,18 @@
 //!
 //! ## References
 //!
+//! - [A General-Purpose Counting Filter: Making Every Bit Count](https://dl.acm.org/citation.cfm?id=3035963)
+//!   > Pandey, Prashant, Michael A. Bender, Rob Johnson, and Rob Patro. 2017. "A General-Purpose Counting Filter: Making Every Bit Count." In _Proceedings of the 2017 Acm International Conference on Management of Data_, 775--87. SIGMOD '17. New York, NY, USA: ACM. <https://doi.org/10.1145/3035918.3035963>.
+//! - [Adaptive Cuckoo Filters](https://arxiv.org/abs/1704.06818)
+//!   > Mitzenmacher, Michael, Salvatore Pontarelli, and Pedro Reviriego. 2018. "Adaptive Cuckoo Filters." In _2018 Proceedings of the Twentieth Workshop on Algorithm Engineering and Experiments (Alenex)_, 36--47. SIAM. <https://doi.org/10.1137/1.9781611975055.4>.
 //! - [Advanced Bloom Filter Based Algorithms for Efficient Approximate Data De-Duplication in Streams](https://arxiv.org/abs/1212.3964)
 //!   > Bera, Suman K., Sourav Dutta, Ankur Narang, and Souvik Bhattacherjee. 2012. "Advanced Bloom Filter Based Algorithms for Efficient Approximate Data de-Duplication in Streams." _CoRR_ abs/1212.3964. <http://arxiv.org/abs/1212.3964>.
 //! - [An improved data stream summary: the count-min sketch and its applications](https://dl.acm.org/citation.cfm?id=1073718)
 //!   > Cormode, Graham, and S. Muthukrishnan. 2005. "An Improved Data Stream Summary: The Count-Min Sketch and Its Applications." _J. Algorithms_ 55 (1). Duluth, MN, USA: Academic Press, Inc.: 58--75. <https://doi.org/10.1016/j.jalgor.2003.12.001>.
+//! - [Approximately detecting duplicates for streaming data using stable bloom filters](https://dl.acm.org/citation.cfm?id=1142477)
+//!   > Deng, Fan, and Davood Rafiei. 2006. "Approximately Detecting Duplicates for Streaming Data Using Stable Bloom Filters." In _Proceedings of the 2006 Acm Sigmod International Conference on Management of Data_, 25--36. SIGMOD '06. New York, NY, USA: ACM. <https://doi.org/10.1145/1142473.1142477>.
+//! - [Binary Fuse Filters: Fast and Smaller Than Xor Filters](https://arxiv.org/abs/2201.01174)
+//!   > Graf, Thomas Mueller, and Daniel Lemire. 2022. "Binary Fuse Filters: Fast and Smaller Than Xor Filters." _J. Exp. Algorithmics_ 27. New York, NY, USA: ACM. <https://doi.org/10.1145/3510449>.
 //! - [Cuckoo Filter: Practically Better Than Bloom](https://dl.acm.org/citation.cfm?id=2674994)
 //!   > Fan, Bin, Dave G. Andersen, Michael Kaminsky, and Michael D. Mitzenmacher. 2014. "Cuckoo Filter: Practically Better Than Bloom." In _Proceedings of the 10th Acm International on Conference on Emerging Networking Experiments and Technologies_, 75--88. CoNEXT '14. New York, NY, USA: ACM. <https://doi.org/10.1145/2674005.2674994>.
 //! - [Don't thrash: how to cache your hash on flash](https://dl.acm.org/citation.cfm?id=2350275)
@@ -75,14 +88,20 @@
 //!   > Heule, Stefan, Marc Nunkesser, and Alexander Hall. 2013. "HyperLogLog in Practice: Algorithmic Engineering of a State of the Art Cardinality Estimation Algorithm." In _Proceedings of the 16th International Conference on Extending Database Technology_, 683--92. EDBT '13. New York, NY, USA: ACM. <https://doi.org/10.1145/2452376.2452456>.
 //! - [HyperLogLog: the analysis of a near-optimal cardinality estimation algorithm](http://algo.inria.fr/flajolet/Publications/FlFuGaMe07.pdf)
 //!   > Flajolet, Philippe, Éric Fusy, Olivier Gandouet, and Frédéric Meunier. 2007. "Hyperloglog: The Analysis of a Near-Optimal Cardinality Estimation Algorithm." In _IN Aofa '07: PROCEEDINGS of the 2007 International Conference on Analysis of Algorithms_.
+//! - [Invertible Bloom Lookup Tables](https://arxiv.org/abs/1101.2245)
+//!   > Goodrich, Michael T., and Michael Mitzenmacher. 2011. "Invertible Bloom Lookup Tables." In _49th Annual Allerton Conference on Communication, Control, and Computing_, 792--99. <https://doi.org/10.1109/Allerton.2011.6120248>.
 //! - [Less hashing, same performance: Building a better Bloom filter](https://dl.acm.org/citation.cfm?id=1400125)
 //!   > Kirsch, Adam, and Michael Mitzenmacher. 2008. "Less Hashing, Same Performance: Building a Better Bloom Filter." _Random Struct. Algorithms_ 33 (2). New York, NY, USA: John Wiley & Sons, Inc.: 187--218. <https://doi.org/10.1002/rsa.v33:2>.
 //! - [Min-wise independent permutations (extended abstract)](https://dl.acm.org/citation.cfm?id=276781)
 //!   > Broder, Andrei Z., Moses Charikar, Alan M. Frieze, and Michael Mitzenmacher. 1998. "Min-Wise Independent Permutations (Extended Abstract)." In _Proceedings of the Thirtieth Annual Acm Symposium on Theory of Computing_, 327--36. STOC '98. New York, NY, USA: ACM. <https://doi.org/10.1145/276698.276781>.
 //! - [Probabilistic near-duplicate detection using simhash](https://dl.acm.org/citation.cfm?id=2063737)
 //!   > Sood, Sadhan, and Dmitri Loguinov. 2011. "Probabilistic Near-Duplicate Detection Using Simhash." In _Proceedings of the 20th Acm International Conference on Information and Knowledge Management_, 1117--26. CIKM '11. New York, NY, USA: ACM. <https://doi.org/10.1145/2063576.2063737>.
+//! - [Ribbon filter: practically smaller than Bloom and Xor](https://arxiv.org/abs/2103.02515)
+//!   > Dillinger, Peter C., and Stefan Walzer. 2021. "Ribbon Filter: Practically Smaller Than Bloom and Xor." _CoRR_ abs/2103.02515. <http://arxiv.org/abs/2103.02515>.
 //! - [Scalable Bloom Filters](https://dl.acm.org/citation.cfm?id=1224501)
 //!   > Almeida, Paulo Sérgio, Carlos Baquero, Nuno Preguiça, and David Hutchison. 2007. "Scalable Bloom Filters." _Inf. Process. Lett._ 101 (6). Amsterdam, The Netherlands, The Netherlands: Elsevier North-Holland, Inc.: 255--61. <https://doi.org/10.1016/j.ipl.2006.10.007>.
+//! - [Xor Filters: Faster and Smaller Than Bloom and Cuckoo Filters](https://arxiv.org/abs/1912.08258)
+//!   > Graf, Thomas Mueller, and Daniel Lemire. 2020. "Xor Filters: Faster and Smaller Than Bloom and Cuckoo Filters." _J. Exp. Algorithmics_ 25. New York, NY, USA: ACM. <https://doi.org/10.1145/3376122>.
 //!
 //! ## License
 //!
@@ -98,10 +117,15 @@ mod bitstring_vec;
 pub mod bloom;
 pub mod count_min_sketch;
 pub mod cuckoo;
+mod error;
 pub mod hyperloglog;
+pub mod iblt;
 pub mod quotient;
+pub mod ribbon;
 pub mod similarity;
 mod util;
+pub mod xor;
 
+pub use self::error::{DecodeError, FilterFullError, InsertError, MergeError};
 pub use self::util::SipHasherBuilder;
 use self::util::{DoubleHasher, HashIter};
@@ -0,0 +1,1187 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::quotient::{
+    self, CONTINUATION_MASK, METADATA_BITS, METADATA_MASK, OCCUPIED_MASK, SHIFTED_MASK,
+};
+use crate::util;
+use crate::{MergeError, SipHasherBuilder};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp;
+use std::collections::VecDeque;
+use std::f64::consts;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+// Returns the number of values that a digit of the counter of `remainder` can take. The digits of
+// a nonzero remainder's counter use every nonzero value other than the remainder, and the digits
+// of a zero remainder's counter use every nonzero value.
+fn get_counter_base(remainder: u64, remainder_bits: u8) -> u64 {
+    if remainder == 0 {
+        (1 << remainder_bits) - 1
+    } else {
+        (1 << remainder_bits) - 2
+    }
+}
+
+fn get_digit_slot(remainder: u64, digit: u64) -> u64 {
+    if remainder == 0 || digit + 1 < remainder {
+        digit + 1
+    } else {
+        digit + 2
+    }
+}
+
+fn get_slot_digit(remainder: u64, slot: u64) -> u64 {
+    if remainder == 0 || slot < remainder {
+        slot - 1
+    } else {
+        slot - 2
+    }
+}
+
+fn encode_counter(slots: &mut Vec<u64>, remainder: u64, count: u64, remainder_bits: u8) {
+    match count {
+        1 => slots.push(remainder),
+        2 => slots.extend_from_slice(&[remainder, remainder]),
+        _ => {
+            let base = get_counter_base(remainder, remainder_bits);
+            let mut digits = Vec::new();
+            let mut value = count - 3;
+            while value > 0 || (remainder == 0 && digits.is_empty()) {
+                digits.push(get_digit_slot(remainder, value % base));
+                value /= base;
+            }
+
+            slots.push(remainder);
+            if remainder != 0 {
+                slots.push(0);
+            }
+            slots.extend(digits.into_iter().rev());
+            slots.push(remainder);
+            if remainder == 0 {
+                slots.push(0);
+            }
+        }
+    }
+}
+
+fn decode_counter(digits: &[u64], remainder: u64, remainder_bits: u8) -> u64 {
+    let base = get_counter_base(remainder, remainder_bits);
+    digits.iter().fold(0, |value, slot| {
+        value * base + get_slot_digit(remainder, *slot)
+    }) + 3
+}
+
+// Encodes the remainders and counts of a run, which must be sorted by remainder.
+fn encode_run(counters: &[(u64, u64)], remainder_bits: u8) -> Vec<u64> {
+    let mut slots = Vec::with_capacity(counters.len());
+    for (remainder, count) in counters {
+        encode_counter(&mut slots, *remainder, *count, remainder_bits);
+    }
+    slots
+}
+
+// Decodes the remainders and counts of a run. Since remainders in a run are sorted, a slot after
+// a nonzero remainder that is smaller than the remainder must be the start of a counter. A zero
+// remainder is always first in its run, and the terminating pair of zeros of its counter cannot
+// appear in the encoding of any other remainder.
+fn decode_run(slots: &[u64], remainder_bits: u8) -> Vec<(u64, u64)> {
+    let mut counters = Vec::new();
+    let mut index = 0;
+    while index < slots.len() {
+        let remainder = slots[index];
+        let next_slot = slots.get(index + 1);
+        if next_slot == Some(&remainder) {
+            counters.push((remainder, 2));
+            index += 2;
+        } else if remainder == 0 && next_slot.is_some() {
+            let end = (index + 2..slots.len().saturating_sub(1))
+                .find(|end| slots[*end] == 0 && slots[*end + 1] == 0);
+            match end {
+                Some(end) => {
+                    let count = decode_counter(&slots[index + 1..end], remainder, remainder_bits);
+                    counters.push((remainder, count));
+                    index = end + 2;
+                }
+                None => {
+                    counters.push((remainder, 1));
+                    index += 1;
+                }
+            }
+        } else if matches!(next_slot, Some(next_slot) if *next_slot < remainder) {
+            let end = (index + 2..slots.len())
+                .find(|end| slots[*end] == remainder)
+                .expect("Expected end of counter.");
+            let count = decode_counter(&slots[index + 2..end], remainder, remainder_bits);
+            counters.push((remainder, count));
+            index = end + 1;
+        } else {
+            counters.push((remainder, 1));
+            index += 1;
+        }
+    }
+    counters
+}
+
+/// A space-efficient probabilistic data structure to count the number of occurrences of an item
+/// in a multiset.
+///
+/// A counting quotient filter is a quotient filter that stores the number of times each
+/// fingerprint was inserted inline in the fingerprint's run. Counters are encoded in the remainder
+/// slots that follow the remainder, so fingerprints that were only inserted once take a single
+/// slot, as they do in a `QuotientFilter`. With `r` remainder bits, a remainder `x` that was
+/// inserted `c` times is encoded as:
+///
+/// - `x` if `c` is 1.
+/// - `x, x` if `c` is 2.
+/// - `x, 0, d..., x` if `c` is greater than 2 and `x` is nonzero, where the digits `d` encode
+///   `c - 3` in base `2^r - 2` using the nonzero values other than `x`.
+/// - `0, d..., 0, 0` if `c` is greater than 2 and `x` is zero, where the digits `d` encode `c - 3`
+///   in base `2^r - 1` using the nonzero values.
+///
+/// Since remainders in a run are sorted, these encodings can be decoded unambiguously. The count
+/// of an item may be overestimated if it shares its fingerprint with another item, but it is never
+/// underestimated.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::quotient::CountingQuotientFilter;
+///
+/// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+///
+/// assert_eq!(filter.count("foo"), 0);
+/// filter.insert("foo");
+/// filter.insert_count("foo", 99);
+/// assert_eq!(filter.count("foo"), 100);
+///
+/// filter.remove_count("foo", 50);
+/// assert_eq!(filter.count("foo"), 50);
+///
+/// filter.clear();
+/// assert!(!filter.contains("foo"));
+///
+/// assert_eq!(filter.quotient_bits(), 8);
+/// assert_eq!(filter.remainder_bits(), 4);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct CountingQuotientFilter<T, B = SipHasherBuilder> {
+    quotient_bits: u8,
+    remainder_bits: u8,
+    // Defined as RR...RRMMM where R are remainder bits and M are metadata bits. The remainder bits
+    // of a slot either contain a remainder or a digit of a counter.
+    quotient_mask: u64,
+    remainder_mask: u64,
+    slot_vec: BitstringVec,
+    hash_builder: B,
+    len: usize,
+    used_slot_count: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> CountingQuotientFilter<T> {
+    /// Constructs a new, empty `CountingQuotientFilter` with the specified number of quotient and
+    /// remainder bits. `quotient_bits` must be a positive integer, `remainder_bits` must be at
+    /// least `2`, and their sum cannot exceed `64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quotient_bits` is 0, `remainder_bits` is less than 2, or if `quotient_bits +
+    /// remainder_bits` is greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    /// ```
+    pub fn new(quotient_bits: u8, remainder_bits: u8) -> Self {
+        Self::with_hasher(
+            quotient_bits,
+            remainder_bits,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+
+    /// Constructs a new, empty `CountingQuotientFilter` that can store `capacity` distinct items
+    /// with an estimated false positive probability of less than `fpp`. Items that are inserted
+    /// more than twice use additional slots for their counters, so `capacity` should account for
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, or if `fpp` is not in the range `(0, 1)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::from_fpp(100, 0.05);
+    /// ```
+    pub fn from_fpp(capacity: usize, fpp: f64) -> Self {
+        Self::from_fpp_with_hasher(capacity, fpp, SipHasherBuilder::from_entropy())
+    }
+}
+
+impl<T, B> CountingQuotientFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_mask(size: u8) -> u64 {
+        (1u64 << size) - 1
+    }
+
+    fn get_quotient_and_remainder(&self, hash: u64) -> (usize, u64) {
+        (
+            ((hash >> self.remainder_bits) & self.quotient_mask) as usize,
+            hash & self.remainder_mask,
+        )
+    }
+
+    fn get_index(&self, start: usize, offset: usize) -> usize {
+        (start + offset) & (self.capacity() - 1)
+    }
+
+    fn get_offset(&self, start: usize, index: usize) -> usize {
+        (index + self.capacity() - start) & (self.capacity() - 1)
+    }
+
+    fn is_empty_slot(&self, index: usize) -> bool {
+        self.slot_vec.get(index) & METADATA_MASK == 0
+    }
+
+    fn get_run_start(&self, mut index: usize) -> usize {
+        // find start of cluster
+        let mut occupied_count = 0;
+        loop {
+            let slot = self.slot_vec.get(index);
+            if slot & OCCUPIED_MASK != 0 {
+                occupied_count += 1;
+            }
+            if slot & SHIFTED_MASK == 0 {
+                break;
+            }
+            index = self.get_index(index, self.capacity() - 1);
+        }
+
+        // find start of run
+        let mut runs_count = 0;
+        loop {
+            if self.slot_vec.get(index) & CONTINUATION_MASK == 0 {
+                runs_count += 1;
+            }
+            if occupied_count == runs_count {
+                return index;
+            }
+            index = self.get_index(index, 1);
+        }
+    }
+
+    // Returns the quotient and remainder slots of every run in the at most `max_len` non-empty
+    // slots starting at `start`, along with the number of slots that were read. `start` must be
+    // empty or the start of a cluster.
+    fn get_runs(&self, start: usize, max_len: usize) -> (Vec<(usize, Vec<u64>)>, usize) {
+        let mut runs: Vec<(usize, Vec<u64>)> = Vec::new();
+        let mut canonical_slots = VecDeque::new();
+        let mut len = 0;
+        while len < max_len {
+            let index = self.get_index(start, len);
+            let slot = self.slot_vec.get(index);
+            if slot & METADATA_MASK == 0 {
+                break;
+            }
+            if slot & OCCUPIED_MASK != 0 {
+                canonical_slots.push_back(index);
+            }
+            if slot & CONTINUATION_MASK == 0 {
+                let quotient = canonical_slots
+                    .pop_front()
+                    .expect("Expected canonical slot for run.");
+                runs.push((quotient, Vec::new()));
+            }
+            runs.last_mut()
+                .expect("Expected run for slot.")
+                .1
+                .push(slot >> METADATA_BITS);
+            len += 1;
+        }
+        (runs, len)
+    }
+
+    // Returns the offset from `start` of the end of the runs if they were written starting at
+    // offset `wrap_len`.
+    fn get_runs_end(&self, start: usize, runs: &[(usize, Vec<u64>)], wrap_len: usize) -> usize {
+        runs.iter().fold(wrap_len, |end, (quotient, slots)| {
+            cmp::max(end, self.get_offset(start, *quotient)) + slots.len()
+        })
+    }
+
+    // Returns the quotient, remainder, and count of every fingerprint in the counting quotient
+    // filter.
+    fn get_counters(&self) -> Vec<(usize, u64, u64)> {
+        let mut counters = Vec::with_capacity(self.len);
+        let start = (0..self.capacity())
+            .find(|index| self.slot_vec.get(*index) & SHIFTED_MASK == 0)
+            .unwrap_or(0);
+        let mut offset = 0;
+        while offset < self.capacity() {
+            if self.is_empty_slot(self.get_index(start, offset)) {
+                offset += 1;
+                continue;
+            }
+            let (runs, len) =
+                self.get_runs(self.get_index(start, offset), self.capacity() - offset);
+            for (quotient, slots) in runs {
+                for (remainder, count) in decode_run(&slots, self.remainder_bits) {
+                    counters.push((quotient, remainder, count));
+                }
+            }
+            offset += len;
+        }
+        counters
+    }
+
+    fn get_count(&self, quotient: usize, remainder: u64) -> u64 {
+        if self.slot_vec.get(quotient) & OCCUPIED_MASK == 0 {
+            return 0;
+        }
+
+        let mut index = self.get_run_start(quotient);
+        let mut slots = vec![self.slot_vec.get(index) >> METADATA_BITS];
+        loop {
+            index = self.get_index(index, 1);
+            let slot = self.slot_vec.get(index);
+            if slot & CONTINUATION_MASK == 0 {
+                break;
+            }
+            slots.push(slot >> METADATA_BITS);
+        }
+
+        decode_run(&slots, self.remainder_bits)
+            .into_iter()
+            .find(|counter| counter.0 == remainder)
+            .map_or(0, |counter| counter.1)
+    }
+
+    // Replaces the count of a fingerprint with the result of `f`. The run of the fingerprint is
+    // re-encoded and the runs of its cluster are rewritten, along with the runs of any following
+    // clusters that the rewritten cluster runs into.
+    fn update_count<F>(&mut self, quotient: usize, remainder: u64, f: F)
+    where
+        F: FnOnce(u64) -> u64,
+    {
+        let capacity = self.capacity();
+        let mut start = quotient;
+        while self.slot_vec.get(start) & SHIFTED_MASK != 0 {
+            start = self.get_index(start, capacity - 1);
+        }
+
+        let (mut runs, mut len) = self.get_runs(start, capacity);
+        let offset = self.get_offset(start, quotient);
+        let run_index =
+            match runs.binary_search_by_key(&offset, |run| self.get_offset(start, run.0)) {
+                Ok(run_index) => run_index,
+                Err(run_index) => {
+                    runs.insert(run_index, (quotient, Vec::new()));
+                    run_index
+                }
+            };
+
+        let mut counters = decode_run(&runs[run_index].1, self.remainder_bits);
+        let counter_index = counters.binary_search_by_key(&remainder, |counter| counter.0);
+        let old_count = counter_index.map_or(0, |counter_index| counters[counter_index].1);
+        let new_count = f(old_count);
+        if old_count == new_count {
+            return;
+        }
+        match counter_index {
+            Ok(counter_index) if new_count == 0 => {
+                counters.remove(counter_index);
+                self.len -= 1;
+            }
+            Ok(counter_index) => counters[counter_index].1 = new_count,
+            Err(counter_index) => {
+                counters.insert(counter_index, (remainder, new_count));
+                self.len += 1;
+            }
+        }
+
+        let slots = encode_run(&counters, self.remainder_bits);
+        let used_slot_count = self.used_slot_count - runs[run_index].1.len() + slots.len();
+        assert!(used_slot_count <= capacity);
+        self.used_slot_count = used_slot_count;
+        if slots.is_empty() {
+            runs.remove(run_index);
+        } else {
+            runs[run_index].1 = slots;
+        }
+
+        // include following clusters until the rewritten runs fit before the next cluster
+        loop {
+            let mut next_offset = len;
+            while next_offset < capacity && self.is_empty_slot(self.get_index(start, next_offset)) {
+                next_offset += 1;
+            }
+            if next_offset == capacity || self.get_runs_end(start, &runs, 0) <= next_offset {
+                break;
+            }
+            let (next_runs, next_len) =
+                self.get_runs(self.get_index(start, next_offset), capacity - next_offset);
+            runs.extend(next_runs);
+            len = next_offset + next_len;
+        }
+
+        // if the runs wrap around to the start, the first run must start after the wrapped slots
+        let mut wrap_len = 0;
+        loop {
+            let end = self.get_runs_end(start, &runs, wrap_len);
+            if end <= capacity + wrap_len {
+                break;
+            }
+            wrap_len = end - capacity;
+        }
+
+        for offset in 0..len {
+            let index = self.get_index(start, offset);
+            self.slot_vec.set(index, 0);
+        }
+        let mut offset = wrap_len;
+        for (quotient, slots) in &runs {
+            let canonical_offset = self.get_offset(start, *quotient);
+            offset = cmp::max(offset, canonical_offset);
+            for (slot_index, remainder) in slots.iter().enumerate() {
+                let mut slot = remainder << METADATA_BITS;
+                if slot_index != 0 {
+                    slot |= CONTINUATION_MASK;
+                }
+                if offset != canonical_offset {
+                    slot |= SHIFTED_MASK;
+                }
+                let index = self.get_index(start, offset);
+                self.slot_vec.set(index, slot);
+                offset += 1;
+            }
+        }
+        for (quotient, _) in &runs {
+            let slot = self.slot_vec.get(*quotient);
+            self.slot_vec.set(*quotient, slot | OCCUPIED_MASK);
+        }
+    }
+
+    /// Constructs a new, empty `CountingQuotientFilter` with the specified number of quotient and
+    /// remainder bits, and hasher builder. `quotient_bits` must be a positive integer,
+    /// `remainder_bits` must be at least `2`, and their sum cannot exceed `64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quotient_bits` is 0, `remainder_bits` is less than 2, or if `quotient_bits +
+    /// remainder_bits` is greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::with_hasher(
+    ///     8,
+    ///     4,
+    ///     SipHasherBuilder::from_entropy(),
+    /// );
+    /// ```
+    pub fn with_hasher(quotient_bits: u8, remainder_bits: u8, hash_builder: B) -> Self {
+        assert!(quotient_bits > 0);
+        assert!(remainder_bits > 1);
+        assert!(quotient_bits + remainder_bits <= 64);
+        let slot_bits = remainder_bits + METADATA_BITS;
+        CountingQuotientFilter {
+            quotient_bits,
+            remainder_bits,
+            quotient_mask: Self::get_mask(quotient_bits),
+            remainder_mask: Self::get_mask(remainder_bits),
+            slot_vec: BitstringVec::new(slot_bits as usize, 1 << quotient_bits),
+            hash_builder,
+            len: 0,
+            used_slot_count: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty `CountingQuotientFilter` that can store `capacity` distinct items
+    /// with an estimated false positive probability of less than `fpp` with a specified hasher
+    /// builder. Items that are inserted more than twice use additional slots for their counters,
+    /// so `capacity` should account for them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, or if `fpp` is not in the range `(0, 1)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::from_fpp_with_hasher(
+    ///     100,
+    ///     0.05,
+    ///     SipHasherBuilder::from_entropy(),
+    /// );
+    /// ```
+    pub fn from_fpp_with_hasher(capacity: usize, fpp: f64, hash_builder: B) -> Self {
+        let (quotient_bits, remainder_bits) =
+            quotient::get_quotient_and_remainder_bits(capacity, fpp);
+        Self::with_hasher(quotient_bits, cmp::max(remainder_bits, 2), hash_builder)
+    }
+
+    /// Inserts an element into the counting quotient filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counting quotient filter does not have enough empty slots for the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert("foo");
+    /// ```
+    pub fn insert<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.insert_count(item, 1);
+    }
+
+    /// Inserts an element `count` times into the counting quotient filter. The count of an element
+    /// saturates at `u64::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counting quotient filter does not have enough empty slots for the item's
+    /// counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_count("foo", 10);
+    /// assert_eq!(filter.count("foo"), 10);
+    /// ```
+    pub fn insert_count<U>(&mut self, item: &U, count: u64)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = util::hash(&self.hash_builder, &item);
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
+        self.update_count(quotient, remainder, |old_count| {
+            old_count.saturating_add(count)
+        });
+    }
+
+    /// Returns the number of times an element was possibly inserted into the counting quotient
+    /// filter. The returned count is never less than the actual count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert_eq!(filter.count("foo"), 0);
+    /// filter.insert("foo");
+    /// filter.insert("foo");
+    /// assert_eq!(filter.count("foo"), 2);
+    /// ```
+    pub fn count<U>(&self, item: &U) -> u64
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = util::hash(&self.hash_builder, &item);
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
+        self.get_count(quotient, remainder)
+    }
+
+    /// Checks if an element is possibly in the counting quotient filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.count(item) > 0
+    }
+
+    /// Removes an element from the counting quotient filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.contains("foo"));
+    /// filter.remove("foo");
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn remove<U>(&mut self, item: &U)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.remove_count(item, 1);
+    }
+
+    /// Removes an element `count` times from the counting quotient filter. The count of an element
+    /// does not go below zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_count("foo", 10);
+    /// filter.remove_count("foo", 3);
+    /// assert_eq!(filter.count("foo"), 7);
+    /// filter.remove_count("foo", 10);
+    /// assert_eq!(filter.count("foo"), 0);
+    /// ```
+    pub fn remove_count<U>(&mut self, item: &U, count: u64)
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = util::hash(&self.hash_builder, &item);
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
+        if self.slot_vec.get(quotient) & OCCUPIED_MASK == 0 {
+            return;
+        }
+        self.update_count(quotient, remainder, |old_count| {
+            old_count.saturating_sub(count)
+        });
+    }
+
+    /// Merges `other` into the counting quotient filter so that the count of every item is the sum
+    /// of its counts in both filters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two counting quotient filters do not have the same number of
+    /// quotient bits, the same number of remainder bits, and the same hasher builders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counting quotient filter does not have enough empty slots for the merged
+    /// counters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter1 = CountingQuotientFilter::<String>::new(8, 4);
+    /// let mut filter2 =
+    ///     CountingQuotientFilter::<String>::with_hasher(8, 4, *filter1.hasher());
+    ///
+    /// filter1.insert_count("foo", 2);
+    /// filter2.insert_count("foo", 3);
+    /// filter2.insert("bar");
+    /// filter1.merge(&filter2).unwrap();
+    ///
+    /// assert_eq!(filter1.count("foo"), 5);
+    /// assert_eq!(filter1.count("bar"), 1);
+    /// ```
+    pub fn merge(&mut self, other: &CountingQuotientFilter<T, B>) -> Result<(), MergeError>
+    where
+        B: PartialEq,
+    {
+        if self.quotient_bits != other.quotient_bits {
+            return Err(MergeError::LengthMismatch);
+        }
+        if self.remainder_bits != other.remainder_bits {
+            return Err(MergeError::ParameterMismatch);
+        }
+        if self.hash_builder != other.hash_builder {
+            return Err(MergeError::HasherMismatch);
+        }
+
+        for (quotient, remainder, count) in other.get_counters() {
+            self.update_count(quotient, remainder, |old_count| {
+                old_count.saturating_add(count)
+            });
+        }
+        Ok(())
+    }
+
+    /// Clears the counting quotient filter, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert("foo");
+    /// filter.clear();
+    ///
+    /// assert!(!filter.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.slot_vec.clear();
+        self.len = 0;
+        self.used_slot_count = 0;
+    }
+
+    /// Returns the number of distinct fingerprints in the counting quotient filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_count("foo", 10);
+    /// assert_eq!(filter.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the counting quotient filter is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert!(filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of slots that are used to store remainders and counters in the counting
+    /// quotient filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert("foo");
+    /// assert_eq!(filter.used_slot_count(), 1);
+    /// filter.insert("foo");
+    /// assert_eq!(filter.used_slot_count(), 2);
+    /// ```
+    pub fn used_slot_count(&self) -> usize {
+        self.used_slot_count
+    }
+
+    /// Returns the number of slots in the counting quotient filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert_eq!(filter.capacity(), 256);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        1 << self.quotient_bits
+    }
+
+    /// Returns the number of quotient bits in a fingerprint for a item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert_eq!(filter.quotient_bits(), 8);
+    /// ```
+    pub fn quotient_bits(&self) -> u8 {
+        self.quotient_bits
+    }
+
+    /// Returns the number of remainder bits in a fingerprint for a item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert_eq!(filter.remainder_bits(), 4);
+    /// ```
+    pub fn remainder_bits(&self) -> u8 {
+        self.remainder_bits
+    }
+
+    /// Returns the estimated false positive probability of the counting quotient filter. This
+    /// value will increase as more distinct items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let mut filter = CountingQuotientFilter::<String>::from_fpp(100, 0.05);
+    /// assert!(filter.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// filter.insert("foo");
+    /// assert!(filter.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(filter.estimated_fpp() < 0.05);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        let fill_ratio = self.len() as f64 / self.capacity() as f64;
+        1.0 - consts::E.powf(-fill_ratio / 2.0f64.powf(f64::from(self.remainder_bits)))
+    }
+
+    /// Returns a reference to the counting quotient filter's hasher builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::CountingQuotientFilter;
+    ///
+    /// let filter = CountingQuotientFilter::<String>::new(8, 4);
+    /// let hasher_builder = filter.hasher();
+    /// ```
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_run, encode_run, CountingQuotientFilter};
+    use crate::util::tests::{hash_builder_1, hash_builder_2};
+    use crate::MergeError;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_new() {
+        let mut filter = CountingQuotientFilter::<usize>::new(8, 4);
+        assert_eq!(filter.capacity(), 256);
+        assert_eq!(filter.quotient_bits(), 8);
+        assert_eq!(filter.remainder_bits(), 4);
+        assert!(filter.is_empty());
+
+        for i in 0..128 {
+            filter.insert(&i);
+        }
+
+        assert!(filter.estimated_fpp() < 0.05);
+    }
+
+    #[test]
+    fn test_from_fpp() {
+        let filter = CountingQuotientFilter::<usize>::from_fpp(100, 0.05);
+        assert_eq!(filter.capacity(), 256);
+        assert_eq!(filter.quotient_bits(), 8);
+        assert_eq!(filter.remainder_bits(), 4);
+
+        let filter = CountingQuotientFilter::<usize>::from_fpp(100, 0.5);
+        assert_eq!(filter.remainder_bits(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_one_remainder_bit() {
+        CountingQuotientFilter::<usize>::new(8, 1);
+    }
+
+    #[test]
+    fn test_encode_run() {
+        for remainder_bits in 2..5 {
+            let remainder_count = 1 << remainder_bits;
+            for count in 1..200 {
+                for remainder in 0..remainder_count {
+                    let counters = vec![(remainder, count)];
+                    let slots = encode_run(&counters, remainder_bits);
+                    assert_eq!(decode_run(&slots, remainder_bits), counters);
+                }
+
+                let counters = (0..remainder_count)
+                    .map(|remainder| (remainder, (count + remainder) % 7 + 1))
+                    .collect::<Vec<_>>();
+                let slots = encode_run(&counters, remainder_bits);
+                assert_eq!(decode_run(&slots, remainder_bits), counters);
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+        filter.insert("foo");
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.used_slot_count(), 1);
+        assert!(!filter.is_empty());
+        assert!(filter.contains("foo"));
+        assert_eq!(filter.count("foo"), 1);
+    }
+
+    #[test]
+    fn test_insert_count() {
+        let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+        filter.insert_count("foo", 1_000_000);
+        filter.insert_count("foo", 0);
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.count("foo"), 1_000_000);
+
+        filter.insert_count("foo", u64::MAX);
+        assert_eq!(filter.count("foo"), u64::MAX);
+    }
+
+    #[test]
+    fn test_singletons() {
+        let mut filter = CountingQuotientFilter::<u32>::with_hasher(8, 8, hash_builder_1());
+        for item in 0..200 {
+            filter.insert(&item);
+        }
+
+        assert_eq!(filter.len(), 200);
+        assert_eq!(filter.used_slot_count(), 200);
+        for item in 0..200 {
+            assert_eq!(filter.count(&item), 1);
+        }
+    }
+
+    #[test]
+    fn test_remove_count() {
+        let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+        filter.insert_count("foo", 10);
+        filter.remove_count("foo", 4);
+        assert_eq!(filter.count("foo"), 6);
+
+        filter.remove("foo");
+        assert_eq!(filter.count("foo"), 5);
+
+        filter.remove_count("foo", 10);
+        assert_eq!(filter.count("foo"), 0);
+        assert_eq!(filter.len(), 0);
+        assert_eq!(filter.used_slot_count(), 0);
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    fn test_full() {
+        let mut filter = CountingQuotientFilter::<u32>::with_hasher(4, 8, hash_builder_1());
+        for item in 0..16 {
+            filter.insert(&item);
+        }
+
+        assert_eq!(filter.used_slot_count(), 16);
+        for item in 0..16 {
+            assert!(filter.count(&item) >= 1);
+        }
+        for item in 0..16 {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_count_full() {
+        let mut filter = CountingQuotientFilter::<u32>::with_hasher(4, 8, hash_builder_1());
+        for item in 0..15 {
+            filter.insert(&item);
+        }
+        filter.insert_count(&100, 10);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut filter1 = CountingQuotientFilter::<u32>::with_hasher(10, 8, hash_builder_1());
+        let mut filter2 = CountingQuotientFilter::<u32>::with_hasher(10, 8, hash_builder_1());
+        for item in 0..200 {
+            filter1.insert_count(&item, u64::from(item));
+            filter2.insert_count(&(item + 100), 2);
+        }
+
+        filter1.merge(&filter2).unwrap();
+        for item in 0..300 {
+            let mut count = 0;
+            if item < 200 {
+                count += u64::from(item);
+            }
+            if item >= 100 {
+                count += 2;
+            }
+            assert!(filter1.count(&item) >= count);
+        }
+    }
+
+    #[test]
+    fn test_merge_mismatch() {
+        let mut filter = CountingQuotientFilter::<u32>::with_hasher(8, 4, hash_builder_1());
+
+        assert_eq!(
+            filter.merge(&CountingQuotientFilter::with_hasher(9, 4, hash_builder_1())),
+            Err(MergeError::LengthMismatch),
+        );
+        assert_eq!(
+            filter.merge(&CountingQuotientFilter::with_hasher(8, 5, hash_builder_1())),
+            Err(MergeError::ParameterMismatch),
+        );
+        assert_eq!(
+            filter.merge(&CountingQuotientFilter::with_hasher(8, 4, hash_builder_2())),
+            Err(MergeError::HasherMismatch),
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+
+        filter.insert_count("foo", 10);
+        filter.insert("bar");
+        filter.clear();
+
+        assert!(filter.is_empty());
+        assert_eq!(filter.used_slot_count(), 0);
+        assert!(!filter.contains("foo"));
+        assert!(!filter.contains("bar"));
+    }
+
+    #[test]
+    fn test_stress() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let quotient_bits = 11;
+        let remainder_bits = 48;
+
+        // large remainder to decrease chance of false positives
+        let mut filter = CountingQuotientFilter::<u64>::new(quotient_bits, remainder_bits);
+        let mut counts = HashMap::new();
+        for _ in 0..10_000 {
+            let item = rng.gen_range(0, 256);
+            let count = rng.gen_range(0, 20);
+            let expected_count = counts.entry(item).or_insert(0);
+            if rng.gen() {
+                filter.insert_count(&item, count);
+                *expected_count += count;
+            } else {
+                filter.remove_count(&item, count);
+                *expected_count -= std::cmp::min(*expected_count, count);
+            }
+            assert_eq!(filter.count(&item), *expected_count);
+        }
+
+        for (item, count) in &counts {
+            assert_eq!(filter.count(item), *count);
+        }
+        assert_eq!(
+            filter.len(),
+            counts.values().filter(|count| **count > 0).count(),
+        );
+    }
+
+    #[test]
+    fn test_update_count_full() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut filter = CountingQuotientFilter::<u64>::with_hasher(4, 3, hash_builder_1());
+        let mut counts = vec![vec![0u64; 8]; 16];
+        let get_slot_count = |counts: &Vec<Vec<u64>>| -> usize {
+            counts
+                .iter()
+                .map(|run_counts| {
+                    let counters = (0..8)
+                        .filter(|remainder| run_counts[*remainder as usize] > 0)
+                        .map(|remainder| (remainder, run_counts[remainder as usize]))
+                        .collect::<Vec<_>>();
+                    encode_run(&counters, 3).len()
+                })
+                .sum()
+        };
+
+        for _ in 0..20_000 {
+            let quotient = rng.gen_range(0, 16);
+            let remainder = rng.gen_range(0, 8);
+            let old_count = counts[quotient][remainder as usize];
+            let new_count = if rng.gen() {
+                old_count + rng.gen_range(0, 5)
+            } else {
+                old_count - std::cmp::min(old_count, rng.gen_range(0, 5))
+            };
+
+            counts[quotient][remainder as usize] = new_count;
+            if get_slot_count(&counts) > filter.capacity() {
+                counts[quotient][remainder as usize] = old_count;
+                continue;
+            }
+            filter.update_count(quotient, remainder, |_| new_count);
+            assert_eq!(filter.used_slot_count(), get_slot_count(&counts));
+            for (quotient, run_counts) in counts.iter().enumerate() {
+                for (remainder, count) in run_counts.iter().enumerate() {
+                    assert_eq!(filter.get_count(quotient, remainder as u64), *count);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut filter = CountingQuotientFilter::<String>::new(8, 4);
+        filter.insert_count("foo", 10);
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: CountingQuotientFilter<String> =
+            bincode::deserialize(&serialized_filter).unwrap();
+
+        assert_eq!(de_filter.count("foo"), 10);
+        assert_eq!(filter.quotient_bits(), de_filter.quotient_bits());
+        assert_eq!(filter.remainder_bits(), de_filter.remainder_bits());
+        assert_eq!(filter.slot_vec, de_filter.slot_vec);
+        assert_eq!(filter.len(), de_filter.len());
+        assert_eq!(filter.used_slot_count(), de_filter.used_slot_count());
+        assert_eq!(filter.hasher(), de_filter.hasher());
+    }
+}
@@ -0,0 +1,24 @@
+//! Space-efficient probabilistic data structures for approximate membership queries in a set,
+//! approximate counts of items in a multiset, and approximate maps from items to small values.
+
+mod counting_quotient_filter;
+mod quotient_filter;
+mod quotient_map;
+
+pub use self::counting_quotient_filter::CountingQuotientFilter;
+pub use self::quotient_filter::{Fingerprints, QuotientFilter};
+pub use self::quotient_map::QuotientMap;
+
+const SHIFTED_MASK: u64 = 0b001;
+const CONTINUATION_MASK: u64 = 0b010;
+const OCCUPIED_MASK: u64 = 0b100;
+const METADATA_MASK: u64 = 0b111;
+const METADATA_BITS: u8 = 3;
+
+// Returns the number of quotient and remainder bits needed to store `capacity` items with an
+// estimated false positive probability of less than `fpp` at a fullness of 75%.
+fn get_quotient_and_remainder_bits(capacity: usize, fpp: f64) -> (u8, u8) {
+    let quotient_bits = (capacity as f64 * 1.33).log2().ceil() as u8;
+    let remainder_bits = (1.0 / -2.0 / (1.0 - fpp).ln()).log2().ceil() as u8;
+    (quotient_bits, remainder_bits)
+}
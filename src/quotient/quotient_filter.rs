@@ -1,22 +1,18 @@
-//! Space-efficient probabilistic data structure for approximate membership queries in a set.
-
 use crate::bitstring_vec::BitstringVec;
+use crate::quotient::{
+    self, CONTINUATION_MASK, METADATA_BITS, METADATA_MASK, OCCUPIED_MASK, SHIFTED_MASK,
+};
 use crate::util;
-use crate::SipHasherBuilder;
+use crate::{MergeError, SipHasherBuilder};
 #[cfg(feature = "serde")]
 use serde_crate::{Deserialize, Serialize};
 use std::borrow::Borrow;
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
+use std::collections::VecDeque;
 use std::f64::consts;
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
 
-const SHIFTED_MASK: u64 = 0b001;
-const CONTINUATION_MASK: u64 = 0b010;
-const OCCUPIED_MASK: u64 = 0b100;
-const METADATA_MASK: u64 = 0b111;
-const METADATA_BITS: u8 = 3;
-
 /// A space-efficient probabilistic data structure to test for membership in a set.
 ///
 /// A quotient filter is essentially a compact hash table. Each item is hashed to a 64-bit
@@ -57,7 +53,9 @@ const METADATA_BITS: u8 = 3;
 pub struct QuotientFilter<T, B = SipHasherBuilder> {
     quotient_bits: u8,
     remainder_bits: u8,
-    // Defined as RR...RRMMM where R are remainder bits and M are metadata bits
+    value_bits: u8,
+    // Defined as RR...RRVV...VVMMM where R are remainder bits, V are value bits, and M are metadata
+    // bits. Quotient filters that are not used as maps have no value bits.
     // MMM
     // |||
     // ||- is_shifted: is set when remainder is in this slot that is not its canonical slot
@@ -131,6 +129,14 @@ where
         )
     }
 
+    fn get_slot_remainder(&self, slot: u64) -> u64 {
+        slot >> (METADATA_BITS + self.value_bits)
+    }
+
+    fn get_slot_value(&self, slot: u64) -> u64 {
+        (slot >> METADATA_BITS) & Self::get_mask(self.value_bits)
+    }
+
     fn increment_index(&self, index: &mut usize) {
         if *index == self.capacity() - 1 {
             *index = 0;
@@ -184,6 +190,82 @@ where
         (index, runs_count, total_occupied_count)
     }
 
+    // Returns the index of the slot containing a fingerprint.
+    fn find(&self, quotient: usize, remainder: u64) -> Option<usize> {
+        let slot = self.slot_vec.get(quotient);
+
+        // no such run exists
+        if slot & OCCUPIED_MASK == 0 {
+            return None;
+        }
+
+        // item in canonical slot
+        if self.get_slot_remainder(slot) == remainder
+            && slot & CONTINUATION_MASK == 0
+            && slot & SHIFTED_MASK == 0
+        {
+            return Some(quotient);
+        }
+
+        let (mut index, ..) = self.get_run_start(quotient);
+
+        let mut slot = self.slot_vec.get(index);
+        loop {
+            match self.get_slot_remainder(slot).cmp(&remainder) {
+                Ordering::Equal => return Some(index),
+                // runs are sorted, so further items in run will always be larger
+                Ordering::Greater => return None,
+                Ordering::Less => {
+                    self.increment_index(&mut index);
+                    slot = self.slot_vec.get(index);
+
+                    // end of run
+                    if slot & CONTINUATION_MASK == 0 {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    // Writes fingerprints that are sorted by quotient and then by remainder into an empty
+    // quotient filter in a single pass.
+    fn insert_sorted(&mut self, quotients_and_remainders: &[(usize, u64)]) {
+        let capacity = self.capacity();
+        let get_end = |start| {
+            quotients_and_remainders
+                .iter()
+                .fold(start, |index, (quotient, _)| cmp::max(index, *quotient) + 1)
+        };
+
+        // if the last runs wrap around, the first run must start after the wrapped slots
+        let mut start = 0;
+        while get_end(start) > capacity + start {
+            start = get_end(start) - capacity;
+        }
+
+        let mut index = start;
+        let mut prev_quotient = None;
+        for (quotient, remainder) in quotients_and_remainders {
+            index = cmp::max(index, *quotient);
+            let mut slot = remainder << METADATA_BITS;
+            if prev_quotient == Some(*quotient) {
+                slot |= CONTINUATION_MASK;
+            }
+            if index != *quotient {
+                slot |= SHIFTED_MASK;
+            }
+            self.slot_vec.set(index % capacity, slot);
+            prev_quotient = Some(*quotient);
+            index += 1;
+        }
+        for (quotient, _) in quotients_and_remainders {
+            let slot = self.slot_vec.get(*quotient);
+            self.slot_vec.set(*quotient, slot | OCCUPIED_MASK);
+        }
+        self.len = quotients_and_remainders.len();
+    }
+
     fn insert_and_shift_right(&mut self, mut index: usize, slot: u64) {
         let mut curr_slot = slot;
 
@@ -228,14 +310,25 @@ where
     /// let filter = QuotientFilter::<String>::with_hasher(8, 4, SipHasherBuilder::from_entropy());
     /// ```
     pub fn with_hasher(quotient_bits: u8, remainder_bits: u8, hash_builder: B) -> Self {
+        Self::with_value_bits_and_hasher(quotient_bits, remainder_bits, 0, hash_builder)
+    }
+
+    pub(super) fn with_value_bits_and_hasher(
+        quotient_bits: u8,
+        remainder_bits: u8,
+        value_bits: u8,
+        hash_builder: B,
+    ) -> Self {
         assert!(quotient_bits > 0);
         assert!(remainder_bits > 0);
         assert!(quotient_bits + remainder_bits <= 64);
-        let slot_bits = remainder_bits + METADATA_BITS;
+        assert!(remainder_bits + value_bits + METADATA_BITS <= 64);
+        let slot_bits = remainder_bits + value_bits + METADATA_BITS;
         let slot_vec_len = u64::from(slot_bits) * (1u64 << quotient_bits);
         QuotientFilter {
             quotient_bits,
             remainder_bits,
+            value_bits,
             quotient_mask: Self::get_mask(quotient_bits),
             remainder_mask: Self::get_mask(remainder_bits),
             slot_vec: BitstringVec::new(slot_bits as usize, slot_vec_len as usize),
@@ -267,11 +360,106 @@ where
     /// );
     /// ```
     pub fn from_fpp_with_hasher(capacity: usize, fpp: f64, hash_builder: B) -> Self {
-        let quotient_bits = (capacity as f64 * 1.33).log2().ceil() as u8;
-        let remainder_bits = (1.0 / -2.0 / (1.0 - fpp).ln()).log2().ceil() as u8;
+        let (quotient_bits, remainder_bits) =
+            quotient::get_quotient_and_remainder_bits(capacity, fpp);
         Self::with_hasher(quotient_bits, remainder_bits, hash_builder)
     }
 
+    /// Constructs a new `QuotientFilter` that contains the fingerprints of both `filter1` and
+    /// `filter2`. The two filters must have the same number of bits in a fingerprint, but may
+    /// divide them differently between the quotient and the remainder, such as after one of them
+    /// grows. The merged quotient filter has at least as many quotient bits as either filter, and
+    /// more quotient bits are used until it is at most 75% full or has one remainder bit left. The
+    /// fingerprints of both filters are read in sorted order, so the filters are merged in linear
+    /// time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two quotient filters do not have the same number of bits in a
+    /// fingerprint, or the same hasher builders, or if the merged fingerprints do not fit in a
+    /// quotient filter with one remainder bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter1 = QuotientFilter::<String>::new(8, 4);
+    /// let mut filter2 = QuotientFilter::<String>::with_hasher(8, 4, *filter1.hasher());
+    ///
+    /// filter1.insert("foo");
+    /// filter2.insert("bar");
+    /// let filter = QuotientFilter::merge(&filter1, &filter2).unwrap();
+    ///
+    /// assert!(filter.contains("foo"));
+    /// assert!(filter.contains("bar"));
+    /// ```
+    pub fn merge(
+        filter1: &QuotientFilter<T, B>,
+        filter2: &QuotientFilter<T, B>,
+    ) -> Result<Self, MergeError>
+    where
+        B: Clone + PartialEq,
+    {
+        let fingerprint_bits = filter1.quotient_bits + filter1.remainder_bits;
+        if fingerprint_bits != filter2.quotient_bits + filter2.remainder_bits {
+            return Err(MergeError::ParameterMismatch);
+        }
+        if filter1.hash_builder != filter2.hash_builder {
+            return Err(MergeError::HasherMismatch);
+        }
+
+        let mut fingerprints = Vec::with_capacity(filter1.len() + filter2.len());
+        let mut iter1 = filter1
+            .fingerprints()
+            .map(|(quotient, remainder)| ((quotient as u64) << filter1.remainder_bits) | remainder)
+            .peekable();
+        let mut iter2 = filter2
+            .fingerprints()
+            .map(|(quotient, remainder)| ((quotient as u64) << filter2.remainder_bits) | remainder)
+            .peekable();
+        loop {
+            let fingerprint = match (iter1.peek(), iter2.peek()) {
+                (Some(fingerprint1), Some(fingerprint2)) => match fingerprint1.cmp(fingerprint2) {
+                    Ordering::Less => iter1.next(),
+                    Ordering::Greater => iter2.next(),
+                    Ordering::Equal => {
+                        iter2.next();
+                        iter1.next()
+                    }
+                },
+                (Some(_), None) => iter1.next(),
+                (None, _) => iter2.next(),
+            };
+            match fingerprint {
+                Some(fingerprint) => fingerprints.push(fingerprint),
+                None => break,
+            }
+        }
+
+        let mut quotient_bits = cmp::max(filter1.quotient_bits, filter2.quotient_bits);
+        while quotient_bits + 1 < fingerprint_bits
+            && fingerprints.len() * 4 > (1usize << quotient_bits) * 3
+        {
+            quotient_bits += 1;
+        }
+        if fingerprints.len() > 1usize << quotient_bits {
+            return Err(MergeError::CapacityExceeded);
+        }
+
+        let mut filter = Self::with_hasher(
+            quotient_bits,
+            fingerprint_bits - quotient_bits,
+            filter1.hash_builder.clone(),
+        );
+        let quotients_and_remainders: Vec<_> = fingerprints
+            .into_iter()
+            .map(|fingerprint| filter.get_quotient_and_remainder(fingerprint))
+            .collect();
+        filter.insert_sorted(&quotients_and_remainders);
+        Ok(filter)
+    }
+
     /// Inserts an element into the quotient filter.
     ///
     /// # Panics
@@ -292,21 +480,51 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let (quotient, remainder) =
-            self.get_quotient_and_remainder(util::hash(&self.hash_builder, &item));
+        self.insert_hash(util::hash(&self.hash_builder, &item));
+    }
+
+    /// Inserts an element into the quotient filter using its precomputed 64-bit hash. The
+    /// quotient and remainder are taken from the lowest `quotient_bits + remainder_bits` bits of
+    /// `hash`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the quotient filter is completely full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter = QuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef);
+    /// ```
+    pub fn insert_hash(&mut self, hash: u64) {
+        self.insert_hash_and_value(hash, 0);
+    }
+
+    // Inserts a fingerprint with a value, or replaces the value of the fingerprint if it already
+    // exists. Returns the previous value of the fingerprint.
+    pub(super) fn insert_hash_and_value(&mut self, hash: u64, value: u64) -> Option<u64> {
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
         let slot = self.slot_vec.get(quotient);
+        let payload = (remainder << self.value_bits) | value;
 
         // empty slot
         if slot & METADATA_MASK == 0 {
             self.slot_vec
-                .set(quotient, (remainder << METADATA_BITS) | OCCUPIED_MASK);
+                .set(quotient, (payload << METADATA_BITS) | OCCUPIED_MASK);
             self.len += 1;
-            return;
+            return None;
         }
 
         // item already exists
-        if self.contains(item) {
-            return;
+        if let Some(index) = self.find(quotient, remainder) {
+            let slot = self.slot_vec.get(index);
+            self.slot_vec
+                .set(index, (payload << METADATA_BITS) | (slot & METADATA_MASK));
+            return Some(self.get_slot_value(slot));
         }
         assert!(self.len() < self.capacity());
 
@@ -325,14 +543,14 @@ where
         // insert into run and maintain sorted order
         let (mut index, ..) = self.get_run_start(quotient);
         let run_start = index;
-        let mut new_slot = remainder << METADATA_BITS;
+        let mut new_slot = payload << METADATA_BITS;
         let mut slot = self.slot_vec.get(index);
 
         if !new_run {
             // find position to insert
             loop {
                 // found position in run to insert
-                if remainder < slot >> METADATA_BITS {
+                if remainder < self.get_slot_remainder(slot) {
                     break;
                 }
 
@@ -362,6 +580,7 @@ where
 
         self.len += 1;
         self.insert_and_shift_right(index, new_slot);
+        None
     }
 
     /// Checks if an element is possibly in the quotient filter.
@@ -382,42 +601,34 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let (quotient, remainder) =
-            self.get_quotient_and_remainder(util::hash(&self.hash_builder, &item));
-        let slot = self.slot_vec.get(quotient);
-
-        // no such run exists
-        if slot & OCCUPIED_MASK == 0 {
-            return false;
-        }
-
-        // item in canonical slot
-        if slot >> METADATA_BITS == remainder
-            && slot & CONTINUATION_MASK == 0
-            && slot & SHIFTED_MASK == 0
-        {
-            return true;
-        }
-
-        let (mut index, ..) = self.get_run_start(quotient);
+        self.contains_hash(util::hash(&self.hash_builder, &item))
+    }
 
-        let mut slot = self.slot_vec.get(index);
-        loop {
-            match (slot >> METADATA_BITS).cmp(&remainder) {
-                Ordering::Equal => return true,
-                // runs are sorted, so further items in run will always be larger
-                Ordering::Greater => return false,
-                Ordering::Less => {
-                    self.increment_index(&mut index);
-                    slot = self.slot_vec.get(index);
+    /// Checks if an element is possibly in the quotient filter using its precomputed 64-bit hash.
+    /// The quotient and remainder are taken from the lowest `quotient_bits + remainder_bits` bits
+    /// of `hash`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter = QuotientFilter::<String>::new(8, 4);
+    ///
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef));
+    /// filter.insert_hash(0x0123_4567_89ab_cdef);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef));
+    /// ```
+    pub fn contains_hash(&self, hash: u64) -> bool {
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
+        self.find(quotient, remainder).is_some()
+    }
 
-                    // end of run
-                    if slot & CONTINUATION_MASK == 0 {
-                        return false;
-                    }
-                }
-            }
-        }
+    // Returns the value of a fingerprint if it exists.
+    pub(super) fn get_hash_value(&self, hash: u64) -> Option<u64> {
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
+        self.find(quotient, remainder)
+            .map(|index| self.get_slot_value(self.slot_vec.get(index)))
     }
 
     /// Removes an element from the quotient filter.
@@ -439,21 +650,45 @@ where
         T: Borrow<U>,
         U: Hash + ?Sized,
     {
-        let (quotient, remainder) =
-            self.get_quotient_and_remainder(util::hash(&self.hash_builder, &item));
+        self.remove_hash(util::hash(&self.hash_builder, &item));
+    }
+
+    /// Removes an element from the quotient filter using its precomputed 64-bit hash. The
+    /// quotient and remainder are taken from the lowest `quotient_bits + remainder_bits` bits of
+    /// `hash`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter = QuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_hash(0x0123_4567_89ab_cdef);
+    /// assert!(filter.contains_hash(0x0123_4567_89ab_cdef));
+    /// filter.remove_hash(0x0123_4567_89ab_cdef);
+    /// assert!(!filter.contains_hash(0x0123_4567_89ab_cdef));
+    /// ```
+    pub fn remove_hash(&mut self, hash: u64) {
+        self.remove_hash_and_value(hash);
+    }
+
+    // Removes a fingerprint and returns its value if it exists.
+    pub(super) fn remove_hash_and_value(&mut self, hash: u64) -> Option<u64> {
+        let (quotient, remainder) = self.get_quotient_and_remainder(hash);
 
         // empty slot
         if self.slot_vec.get(quotient) & METADATA_MASK == 0 {
-            return;
+            return None;
         }
 
         let (mut index, mut runs_count, mut occupied_count) = self.get_run_start(quotient);
         let mut slot = self.slot_vec.get(index);
         loop {
-            match (slot >> METADATA_BITS).cmp(&remainder) {
+            match self.get_slot_remainder(slot).cmp(&remainder) {
                 Ordering::Equal => break,
                 // runs are sorted, so further items in run will always be larger
-                Ordering::Greater => return,
+                Ordering::Greater => return None,
                 Ordering::Less => {
                     self.increment_index(&mut index);
                     slot = self.slot_vec.get(index);
@@ -464,13 +699,14 @@ where
 
                     // end of run
                     if slot & CONTINUATION_MASK == 0 {
-                        return;
+                        return None;
                     }
                 }
             }
         }
 
         // found item, have to delete and shift left
+        let value = self.get_slot_value(slot);
         let mut is_run_start = slot & CONTINUATION_MASK == 0;
 
         // keep occupied bit only, if it exists
@@ -526,6 +762,87 @@ where
         }
 
         self.len -= 1;
+        Some(value)
+    }
+
+    /// Doubles the capacity of the quotient filter by moving the most significant remainder bit of
+    /// every fingerprint into its quotient. The items do not need to be rehashed, but the false
+    /// positive probability doubles for the same fill ratio.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the quotient filter only has one remainder bit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter = QuotientFilter::<String>::new(8, 4);
+    /// filter.insert("foo");
+    ///
+    /// filter.grow();
+    /// assert_eq!(filter.capacity(), 512);
+    /// assert_eq!(filter.quotient_bits(), 9);
+    /// assert_eq!(filter.remainder_bits(), 3);
+    /// assert!(filter.contains("foo"));
+    /// ```
+    pub fn grow(&mut self) {
+        assert!(self.remainder_bits > 1);
+        let remainder_bits = self.remainder_bits;
+        let fingerprints: Vec<_> = self
+            .fingerprints()
+            .map(|(quotient, remainder)| ((quotient as u64) << remainder_bits) | remainder)
+            .collect();
+
+        self.quotient_bits += 1;
+        self.remainder_bits -= 1;
+        self.quotient_mask = Self::get_mask(self.quotient_bits);
+        self.remainder_mask = Self::get_mask(self.remainder_bits);
+        let slot_bits = self.remainder_bits + METADATA_BITS;
+        let slot_vec_len = u64::from(slot_bits) * (1u64 << self.quotient_bits);
+        self.slot_vec = BitstringVec::new(slot_bits as usize, slot_vec_len as usize);
+
+        let quotients_and_remainders: Vec<_> = fingerprints
+            .into_iter()
+            .map(|fingerprint| self.get_quotient_and_remainder(fingerprint))
+            .collect();
+        self.insert_sorted(&quotients_and_remainders);
+    }
+
+    /// Returns an iterator over the fingerprints in the quotient filter as `(quotient, remainder)`
+    /// pairs, sorted by quotient and then by remainder. The fingerprints are reconstructed by
+    /// matching each run with its canonical slot using the metadata bits of the slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientFilter;
+    ///
+    /// let mut filter = QuotientFilter::<String>::new(8, 4);
+    ///
+    /// filter.insert_hash(0x0123);
+    /// filter.insert_hash(0x0012);
+    /// filter.insert_hash(0x0013);
+    ///
+    /// let fingerprints: Vec<_> = filter.fingerprints().collect();
+    /// assert_eq!(fingerprints, vec![(0x01, 0x2), (0x01, 0x3), (0x12, 0x3)]);
+    /// ```
+    pub fn fingerprints(&self) -> Fingerprints<'_, T, B> {
+        // the iterator starts at the cluster of the first slot, whose first runs may have wrapped
+        // around from the end
+        let mut start = 0;
+        while self.slot_vec.get(start) & SHIFTED_MASK != 0 {
+            self.decrement_index(&mut start);
+        }
+        Fingerprints {
+            filter: self,
+            start,
+            offset: 0,
+            canonical_slots: VecDeque::new(),
+            quotient: 0,
+            is_wrapped_pass: false,
+        }
     }
 
     /// Clears the quotient filter, removing all elements.
@@ -623,6 +940,10 @@ where
         self.remainder_bits
     }
 
+    pub(super) fn value_bits(&self) -> u8 {
+        self.value_bits
+    }
+
     /// Returns the estimated false positive probability of the quotient filter. This value will
     /// increase as more items are added.
     ///
@@ -658,6 +979,78 @@ where
     }
 }
 
+/// An iterator over the fingerprints in a `QuotientFilter` as `(quotient, remainder)` pairs.
+///
+/// This struct is created by [`QuotientFilter::fingerprints`].
+pub struct Fingerprints<'a, T, B> {
+    filter: &'a QuotientFilter<T, B>,
+    start: usize,
+    offset: usize,
+    canonical_slots: VecDeque<usize>,
+    quotient: usize,
+    // The slots are walked twice if the cluster of the first slot starts at the end of the quotient
+    // filter: first for the fingerprints with quotients before the start of the cluster, and then
+    // for the fingerprints with quotients after it.
+    is_wrapped_pass: bool,
+}
+
+impl<'a, T, B> Fingerprints<'a, T, B>
+where
+    B: BuildHasher,
+{
+    fn next_slot(&mut self) -> Option<(usize, u64)> {
+        while self.offset < self.filter.capacity() {
+            let index = (self.start + self.offset) % self.filter.capacity();
+            let slot = self.filter.slot_vec.get(index);
+            self.offset += 1;
+            if slot & OCCUPIED_MASK != 0 {
+                self.canonical_slots.push_back(index);
+            }
+            if slot & METADATA_MASK != 0 {
+                if slot & CONTINUATION_MASK == 0 {
+                    self.quotient = self
+                        .canonical_slots
+                        .pop_front()
+                        .expect("Expected canonical slot for run.");
+                }
+                return Some((self.quotient, self.filter.get_slot_remainder(slot)));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T, B> Iterator for Fingerprints<'a, T, B>
+where
+    B: BuildHasher,
+{
+    type Item = (usize, u64);
+
+    fn next(&mut self) -> Option<(usize, u64)> {
+        loop {
+            match self.next_slot() {
+                Some((quotient, remainder)) if !self.is_wrapped_pass => {
+                    if quotient < self.start || self.start == 0 {
+                        return Some((quotient, remainder));
+                    }
+                }
+                Some((quotient, remainder)) if quotient >= self.start => {
+                    return Some((quotient, remainder));
+                }
+                None if !self.is_wrapped_pass && self.start != 0 => {
+                    self.offset = 0;
+                    self.canonical_slots.clear();
+                    self.is_wrapped_pass = true;
+                }
+                _ => {
+                    self.offset = self.filter.capacity();
+                    return None;
+                }
+            }
+        }
+    }
+}
+
 use std::fmt;
 impl<T> fmt::Debug for QuotientFilter<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -672,6 +1065,11 @@ impl<T> fmt::Debug for QuotientFilter<T> {
 #[cfg(test)]
 mod tests {
     use super::QuotientFilter;
+    use crate::util::{
+        self,
+        tests::{hash_builder_1, hash_builder_2},
+    };
+    use crate::MergeError;
     use rand::{seq::SliceRandom, Rng, SeedableRng};
 
     #[test]
@@ -713,6 +1111,21 @@ mod tests {
         assert!(filter.contains("foo"));
     }
 
+    #[test]
+    fn test_insert_hash() {
+        let mut filter = QuotientFilter::<String>::with_hasher(8, 4, hash_builder_1());
+
+        filter.insert_hash(util::hash(&hash_builder_1(), &"foo"));
+        assert!(filter.contains("foo"));
+
+        filter.insert("bar");
+        assert!(filter.contains_hash(util::hash(&hash_builder_1(), &"bar")));
+
+        filter.remove_hash(util::hash(&hash_builder_1(), &"foo"));
+        assert!(!filter.contains("foo"));
+        assert_eq!(filter.len(), 1);
+    }
+
     #[test]
     fn test_insert_existing_item() {
         let mut filter = QuotientFilter::<String>::new(8, 4);
@@ -734,6 +1147,178 @@ mod tests {
         assert!(!filter.contains("foo"));
     }
 
+    #[test]
+    fn test_grow() {
+        let mut filter = QuotientFilter::<u32>::with_hasher(8, 8, hash_builder_1());
+        for item in 0..256 {
+            filter.insert(&item);
+        }
+        let len = filter.len();
+        assert_eq!(len, filter.capacity());
+
+        filter.grow();
+        assert_eq!(filter.capacity(), 512);
+        assert_eq!(filter.quotient_bits(), 9);
+        assert_eq!(filter.remainder_bits(), 7);
+        assert_eq!(filter.len(), len);
+        for item in 0..256 {
+            assert!(filter.contains(&item));
+        }
+
+        for item in 256..512 {
+            filter.insert(&item);
+        }
+        filter.grow();
+        for item in 0..512 {
+            assert!(filter.contains(&item));
+        }
+        for item in 0..512 {
+            filter.remove(&item);
+        }
+        assert!(filter.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_grow_one_remainder_bit() {
+        let mut filter = QuotientFilter::<u32>::new(8, 1);
+        filter.grow();
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let mut filter = QuotientFilter::<u32>::with_hasher(8, 8, hash_builder_1());
+        let mut expected_fingerprints = Vec::new();
+        for item in 0..200 {
+            let hash = util::hash(&hash_builder_1(), &item) & 0xFFFF;
+            filter.insert_hash(hash);
+            expected_fingerprints.push(((hash >> 8) as usize, hash & 0xFF));
+        }
+        expected_fingerprints.sort();
+        expected_fingerprints.dedup();
+
+        assert_eq!(
+            filter.fingerprints().collect::<Vec<_>>(),
+            expected_fingerprints,
+        );
+    }
+
+    #[test]
+    fn test_fingerprints_wrapped() {
+        let mut filter = QuotientFilter::<u32>::new(4, 4);
+        for hash in &[0xF0, 0xF1, 0xF2, 0xE0, 0xE1, 0xE2, 0xE3, 0x00, 0x10, 0x30] {
+            filter.insert_hash(*hash);
+        }
+
+        assert_eq!(
+            filter.fingerprints().collect::<Vec<_>>(),
+            vec![
+                (0x0, 0x0),
+                (0x1, 0x0),
+                (0x3, 0x0),
+                (0xE, 0x0),
+                (0xE, 0x1),
+                (0xE, 0x2),
+                (0xE, 0x3),
+                (0xF, 0x0),
+                (0xF, 0x1),
+                (0xF, 0x2),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut filter1 = QuotientFilter::<u32>::with_hasher(8, 8, hash_builder_1());
+        let mut filter2 = QuotientFilter::<u32>::with_hasher(8, 8, hash_builder_1());
+        for item in 0..150 {
+            filter1.insert(&item);
+            filter2.insert(&(item + 100));
+        }
+
+        let filter = QuotientFilter::merge(&filter1, &filter2).unwrap();
+        assert_eq!(filter.quotient_bits(), 9);
+        assert_eq!(filter.remainder_bits(), 7);
+        for item in 0..250 {
+            assert!(filter.contains(&item));
+        }
+        assert!(filter.len() <= 250);
+        assert_eq!(filter.fingerprints().count(), filter.len());
+    }
+
+    #[test]
+    fn test_merge_grown() {
+        let mut filter1 = QuotientFilter::<u32>::with_hasher(4, 8, hash_builder_1());
+        let mut filter2 = QuotientFilter::<u32>::with_hasher(4, 8, hash_builder_1());
+        for item in 0..12 {
+            filter1.insert(&item);
+        }
+        filter1.grow();
+        filter1.grow();
+        for item in 12..16 {
+            filter2.insert(&item);
+        }
+
+        let filter = QuotientFilter::merge(&filter2, &filter1).unwrap();
+        assert_eq!(filter.quotient_bits(), 6);
+        assert_eq!(filter.remainder_bits(), 6);
+        for item in 0..16 {
+            assert!(filter.contains(&item));
+        }
+    }
+
+    #[test]
+    fn test_merge_full() {
+        let mut filter1 = QuotientFilter::<u32>::with_hasher(4, 1, hash_builder_1());
+        let filter2 = QuotientFilter::<u32>::with_hasher(4, 1, hash_builder_1());
+        for hash in 16..32 {
+            filter1.insert_hash(hash);
+        }
+
+        let filter = QuotientFilter::merge(&filter1, &filter2).unwrap();
+        assert_eq!(filter.len(), 16);
+        for hash in 16..32 {
+            assert!(filter.contains_hash(hash));
+        }
+    }
+
+    #[test]
+    fn test_merge_exceeds_capacity() {
+        let mut filter1 = QuotientFilter::<u32>::with_hasher(4, 1, hash_builder_1());
+        let mut filter2 = QuotientFilter::<u32>::with_hasher(4, 1, hash_builder_1());
+        for hash in 0..16 {
+            filter1.insert_hash(hash);
+            filter2.insert_hash(hash + 16);
+        }
+
+        assert_eq!(
+            QuotientFilter::merge(&filter1, &filter2).err(),
+            Some(MergeError::CapacityExceeded),
+        );
+    }
+
+    #[test]
+    fn test_merge_mismatch() {
+        let filter = QuotientFilter::<u32>::with_hasher(8, 4, hash_builder_1());
+
+        assert_eq!(
+            QuotientFilter::merge(
+                &filter,
+                &QuotientFilter::with_hasher(8, 5, hash_builder_1()),
+            )
+            .err(),
+            Some(MergeError::ParameterMismatch),
+        );
+        assert_eq!(
+            QuotientFilter::merge(
+                &filter,
+                &QuotientFilter::with_hasher(8, 4, hash_builder_2()),
+            )
+            .err(),
+            Some(MergeError::HasherMismatch),
+        );
+    }
+
     #[test]
     fn test_clear() {
         let mut filter = QuotientFilter::<String>::new(8, 4);
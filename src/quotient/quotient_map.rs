@@ -0,0 +1,537 @@
+use crate::quotient::{self, QuotientFilter};
+use crate::util;
+use crate::SipHasherBuilder;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A space-efficient probabilistic map from items to small values.
+///
+/// A quotient map, or maplet, is a quotient filter that stores a fixed-width value beside the
+/// remainder of each fingerprint. Values are kept with their remainders when runs are shifted, so
+/// looking up an item returns the value of its fingerprint. An item that is not in the map may
+/// share its fingerprint with an item that is, in which case the value of the other item is
+/// returned. This happens with the same probability as a false positive in a `QuotientFilter`.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::quotient::QuotientMap;
+///
+/// let mut map = QuotientMap::<String>::new(8, 8, 4);
+///
+/// assert_eq!(map.get("foo"), None);
+/// map.insert("foo", 3);
+/// assert_eq!(map.get("foo"), Some(3));
+///
+/// map.clear();
+/// assert_eq!(map.get("foo"), None);
+///
+/// assert_eq!(map.quotient_bits(), 8);
+/// assert_eq!(map.remainder_bits(), 8);
+/// assert_eq!(map.value_bits(), 4);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct QuotientMap<T, B = SipHasherBuilder> {
+    filter: QuotientFilter<T, B>,
+}
+
+impl<T> QuotientMap<T> {
+    /// Constructs a new, empty `QuotientMap` with the specified number of quotient, remainder, and
+    /// value bits. `quotient_bits`, `remainder_bits`, and `value_bits` must be positive integers,
+    /// the sum of `quotient_bits` and `remainder_bits` cannot exceed `64`, and the sum of
+    /// `remainder_bits` and `value_bits` cannot exceed `61`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quotient_bits`, `remainder_bits`, or `value_bits` is 0, if `quotient_bits +
+    /// remainder_bits` is greater than 64, or if `remainder_bits + value_bits` is greater than 61.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    /// ```
+    pub fn new(quotient_bits: u8, remainder_bits: u8, value_bits: u8) -> Self {
+        Self::with_hasher(
+            quotient_bits,
+            remainder_bits,
+            value_bits,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+
+    /// Constructs a new, empty `QuotientMap` that can store `capacity` items with values of
+    /// `value_bits` bits and an estimated false positive probability of less than `fpp`. The ideal
+    /// fullness of quotient map is 75%, so the contructed quotient map will have a maximum
+    /// capacity of `1.33 * capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, if `fpp` is not in the range `(0, 1)`, or if `value_bits` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::from_fpp(100, 0.05, 4);
+    /// ```
+    pub fn from_fpp(capacity: usize, fpp: f64, value_bits: u8) -> Self {
+        Self::from_fpp_with_hasher(capacity, fpp, value_bits, SipHasherBuilder::from_entropy())
+    }
+}
+
+impl<T, B> QuotientMap<T, B>
+where
+    B: BuildHasher,
+{
+    /// Constructs a new, empty `QuotientMap` with the specified number of quotient, remainder, and
+    /// value bits, and hasher builder. `quotient_bits`, `remainder_bits`, and `value_bits` must be
+    /// positive integers, the sum of `quotient_bits` and `remainder_bits` cannot exceed `64`, and
+    /// the sum of `remainder_bits` and `value_bits` cannot exceed `61`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quotient_bits`, `remainder_bits`, or `value_bits` is 0, if `quotient_bits +
+    /// remainder_bits` is greater than 64, or if `remainder_bits + value_bits` is greater than 61.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = QuotientMap::<String>::with_hasher(8, 8, 4, SipHasherBuilder::from_entropy());
+    /// ```
+    pub fn with_hasher(
+        quotient_bits: u8,
+        remainder_bits: u8,
+        value_bits: u8,
+        hash_builder: B,
+    ) -> Self {
+        assert!(value_bits > 0);
+        QuotientMap {
+            filter: QuotientFilter::with_value_bits_and_hasher(
+                quotient_bits,
+                remainder_bits,
+                value_bits,
+                hash_builder,
+            ),
+        }
+    }
+
+    /// Constructs a new, empty `QuotientMap` that can store `capacity` items with values of
+    /// `value_bits` bits and an estimated false positive probability of less than `fpp` with a
+    /// specified hasher builder. The ideal fullness of quotient map is 75%, so the contructed
+    /// quotient map will have a maximum capacity of `1.33 * capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0, if `fpp` is not in the range `(0, 1)`, or if `value_bits` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let map = QuotientMap::<String>::from_fpp_with_hasher(
+    ///     100,
+    ///     0.05,
+    ///     4,
+    ///     SipHasherBuilder::from_entropy(),
+    /// );
+    /// ```
+    pub fn from_fpp_with_hasher(
+        capacity: usize,
+        fpp: f64,
+        value_bits: u8,
+        hash_builder: B,
+    ) -> Self {
+        let (quotient_bits, remainder_bits) =
+            quotient::get_quotient_and_remainder_bits(capacity, fpp);
+        Self::with_hasher(quotient_bits, remainder_bits, value_bits, hash_builder)
+    }
+
+    /// Inserts an element with a value into the quotient map, and returns the previous value of the
+    /// element's fingerprint if it was in the quotient map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` does not fit in `value_bits` bits, or if the quotient map is completely
+    /// full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.insert("foo", 3), None);
+    /// assert_eq!(map.insert("foo", 5), Some(3));
+    /// ```
+    pub fn insert<U>(&mut self, item: &U, value: u64) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        assert!(value >> self.value_bits() == 0);
+        let hash = util::hash(self.filter.hasher(), &item);
+        self.filter.insert_hash_and_value(hash, value)
+    }
+
+    /// Returns the value of an element if it is possibly in the quotient map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.get("foo"), None);
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.get("foo"), Some(3));
+    /// ```
+    pub fn get<U>(&self, item: &U) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.filter
+            .get_hash_value(util::hash(self.filter.hasher(), &item))
+    }
+
+    /// Checks if an element is possibly in the quotient map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert!(!map.contains("foo"));
+    /// map.insert("foo", 3);
+    /// assert!(map.contains("foo"));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        self.filter.contains(item)
+    }
+
+    /// Removes an element from the quotient map, and returns the value of the element's
+    /// fingerprint if it was in the quotient map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.remove("foo"), Some(3));
+    /// assert_eq!(map.get("foo"), None);
+    /// ```
+    pub fn remove<U>(&mut self, item: &U) -> Option<u64>
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = util::hash(self.filter.hasher(), &item);
+        self.filter.remove_hash_and_value(hash)
+    }
+
+    /// Clears the quotient map, removing all elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// map.clear();
+    ///
+    /// assert!(!map.contains("foo"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.filter.clear();
+    }
+
+    /// Returns the number of items in the quotient map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// map.insert("foo", 3);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.filter.len()
+    }
+
+    /// Returns `true` if the quotient map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.filter.is_empty()
+    }
+
+    /// Returns the capacity of the quotient map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.capacity(), 256);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.filter.capacity()
+    }
+
+    /// Returns the number of quotient bits in a fingerprint for a item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.quotient_bits(), 8);
+    /// ```
+    pub fn quotient_bits(&self) -> u8 {
+        self.filter.quotient_bits()
+    }
+
+    /// Returns the number of remainder bits in a fingerprint for a item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.remainder_bits(), 8);
+    /// ```
+    pub fn remainder_bits(&self) -> u8 {
+        self.filter.remainder_bits()
+    }
+
+    /// Returns the number of bits in the value of an item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    ///
+    /// assert_eq!(map.value_bits(), 4);
+    /// ```
+    pub fn value_bits(&self) -> u8 {
+        self.filter.value_bits()
+    }
+
+    /// Returns the estimated false positive probability of the quotient map. This value will
+    /// increase as more items are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let mut map = QuotientMap::<String>::from_fpp(100, 0.05, 4);
+    /// assert!(map.estimated_fpp() < std::f64::EPSILON);
+    ///
+    /// map.insert("foo", 3);
+    /// assert!(map.estimated_fpp() > std::f64::EPSILON);
+    /// assert!(map.estimated_fpp() < 0.05);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        self.filter.estimated_fpp()
+    }
+
+    /// Returns a reference to the quotient map's hasher builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::quotient::QuotientMap;
+    ///
+    /// let map = QuotientMap::<String>::new(8, 8, 4);
+    /// let hasher_builder = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &B {
+        self.filter.hasher()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuotientMap;
+    use rand::{seq::SliceRandom, Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_new() {
+        let map = QuotientMap::<usize>::new(8, 8, 4);
+        assert_eq!(map.capacity(), 256);
+        assert_eq!(map.quotient_bits(), 8);
+        assert_eq!(map.remainder_bits(), 8);
+        assert_eq!(map.value_bits(), 4);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_from_fpp() {
+        let map = QuotientMap::<usize>::from_fpp(100, 0.05, 4);
+        assert_eq!(map.capacity(), 256);
+        assert_eq!(map.quotient_bits(), 8);
+        assert_eq!(map.remainder_bits(), 4);
+        assert_eq!(map.value_bits(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_zero_value_bits() {
+        QuotientMap::<usize>::new(8, 8, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_large_slot() {
+        QuotientMap::<usize>::new(8, 56, 6);
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        assert_eq!(map.insert("foo", 15), None);
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+        assert!(map.contains("foo"));
+        assert_eq!(map.get("foo"), Some(15));
+    }
+
+    #[test]
+    fn test_insert_existing_item() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        map.insert("foo", 1);
+        assert_eq!(map.insert("foo", 2), Some(1));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("foo"), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_large_value() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        map.insert("foo", 16);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        map.insert("foo", 1);
+        assert_eq!(map.remove("foo"), Some(1));
+        assert_eq!(map.remove("foo"), None);
+
+        assert!(map.is_empty());
+        assert_eq!(map.get("foo"), None);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        map.insert("foo", 1);
+        map.insert("bar", 2);
+        map.clear();
+
+        assert!(map.is_empty());
+        assert_eq!(map.get("foo"), None);
+        assert_eq!(map.get("bar"), None);
+    }
+
+    #[test]
+    fn test_stress() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let quotient_bits = 12;
+        let remainder_bits = 48;
+        let value_bits = 8;
+
+        // large remainder to decrease chance of false positives
+        let mut map = QuotientMap::<u64>::new(quotient_bits, remainder_bits, value_bits);
+        let mut values = HashMap::new();
+        while values.len() < 1 << quotient_bits {
+            let item = rng.gen::<u64>();
+            let value = rng.gen_range(0, 1 << value_bits);
+            assert_eq!(map.insert(&item, value), values.insert(item, value));
+            assert_eq!(map.len(), values.len());
+        }
+
+        for (item, value) in &values {
+            assert_eq!(map.get(item), Some(*value));
+        }
+
+        let mut items: Vec<_> = values.keys().cloned().collect();
+        items.shuffle(&mut rng);
+        for item in items {
+            assert_eq!(map.remove(&item), values.remove(&item));
+            assert_eq!(map.get(&item), None);
+            for item in values.keys().take(16) {
+                assert_eq!(map.get(item), values.get(item).cloned());
+            }
+        }
+        assert!(map.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let mut map = QuotientMap::<String>::new(8, 8, 4);
+        map.insert("foo", 3);
+
+        let serialized_map = bincode::serialize(&map).unwrap();
+        let de_map: QuotientMap<String> = bincode::deserialize(&serialized_map).unwrap();
+
+        assert_eq!(de_map.get("foo"), Some(3));
+        assert_eq!(map.quotient_bits(), de_map.quotient_bits());
+        assert_eq!(map.remainder_bits(), de_map.remainder_bits());
+        assert_eq!(map.value_bits(), de_map.value_bits());
+        assert_eq!(map.len(), de_map.len());
+        assert_eq!(map.hasher(), de_map.hasher());
+    }
+}
@@ -0,0 +1,538 @@
+//! Space-efficient probabilistic data structure for approximate membership queries in a static
+//! set.
+
+use crate::bitstring_vec::BitstringVec;
+use crate::util::{self, fmix64};
+use crate::SipHasherBuilder;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const RIBBON_WIDTH: usize = 64;
+// The number of items per extra slot of the standard and homogeneous ribbon filters.
+const STANDARD_OVERHEAD_DIVISOR: usize = 20;
+const HOMOGENEOUS_OVERHEAD_DIVISOR: usize = 8;
+const COEFFICIENT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// A space-efficient probabilistic data structure to test for membership in a static set.
+///
+/// A ribbon filter stores a solution `Z` to a system of linear equations over GF(2) with one
+/// equation per item. The equation of an item is `c · Z[s..s + 64] = r`, where the starting
+/// position `s`, the 64-bit coefficient row `c`, and the `fingerprint_bit_count`-bit result `r`
+/// are derived from the item's hash. Because every coefficient row only covers 64 consecutive
+/// slots, the system can be solved in linear time by incremental Gaussian elimination followed by
+/// back substitution. An item is possibly in the filter if its equation holds.
+///
+/// There are two variants of the ribbon filter:
+///
+/// - The standard ribbon filter uses the fingerprint of an item as its result. Its false positive
+///   probability is `2^-fingerprint_bit_count`, and it uses around `1.05 * fingerprint_bit_count`
+///   bits per item for large sets, but construction may fail, in which case it is retried with a
+///   new seed.
+/// - The homogeneous ribbon filter uses a result of zero for every item and picks a random
+///   solution to the system. Construction never fails, but it uses around
+///   `1.125 * fingerprint_bit_count` bits per item for large sets to keep its false positive
+///   probability close to `2^-fingerprint_bit_count`.
+///
+/// Items cannot be inserted after construction.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::ribbon::RibbonFilter;
+///
+/// let filter = RibbonFilter::<u32>::from_items(0..100, 16);
+///
+/// assert!(filter.contains(&0));
+/// assert!(filter.contains(&99));
+///
+/// assert_eq!(filter.fingerprint_bit_count(), 16);
+/// assert!(!filter.is_homogeneous());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct RibbonFilter<T, B = SipHasherBuilder> {
+    solution_vec: BitstringVec,
+    seed: u64,
+    is_homogeneous: bool,
+    item_count: usize,
+    hash_builder: B,
+    _marker: PhantomData<T>,
+}
+
+impl<T> RibbonFilter<T>
+where
+    T: Hash,
+{
+    /// Constructs a new standard `RibbonFilter` containing the items yielded by `items`, with
+    /// fingerprints of `fingerprint_bit_count` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not in the range `[1, 32]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    /// ```
+    pub fn from_items<I>(items: I, fingerprint_bit_count: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::from_items_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+
+    /// Constructs a new homogeneous `RibbonFilter` containing the items yielded by `items`, with
+    /// `fingerprint_bit_count` bits per slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not in the range `[1, 32]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items_homogeneous(0..100, 8);
+    /// ```
+    pub fn from_items_homogeneous<I>(items: I, fingerprint_bit_count: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::from_items_homogeneous_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+}
+
+impl<T, B> RibbonFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_slot_count(item_count: usize, is_homogeneous: bool) -> usize {
+        let overhead_divisor = if is_homogeneous {
+            HOMOGENEOUS_OVERHEAD_DIVISOR
+        } else {
+            STANDARD_OVERHEAD_DIVISOR
+        };
+        item_count + (item_count + overhead_divisor - 1) / overhead_divisor + RIBBON_WIDTH
+    }
+
+    // Returns the starting position, coefficient row, and result of the equation of a hash. The
+    // lowest bit of the coefficient row corresponds to the starting position and is always set.
+    fn get_equation(&self, hash: u64) -> (usize, u64, u64) {
+        let hash = fmix64(hash.wrapping_add(self.seed));
+        let start_count = (self.solution_vec.len() - RIBBON_WIDTH + 1) as u64;
+        let start = ((u128::from(hash) * u128::from(start_count)) >> 64) as usize;
+        let coefficient = fmix64(hash ^ COEFFICIENT_SEED) | 1;
+        let result = if self.is_homogeneous {
+            0
+        } else {
+            hash & ((1 << self.fingerprint_bit_count()) - 1)
+        };
+        (start, coefficient, result)
+    }
+
+    fn from_hashes(
+        mut hashes: Vec<u64>,
+        fingerprint_bit_count: usize,
+        is_homogeneous: bool,
+        mut hash_builder: B,
+    ) -> Self {
+        assert!(fingerprint_bit_count > 0 && fingerprint_bit_count <= 32);
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let slot_count = Self::get_slot_count(hashes.len(), is_homogeneous);
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        loop {
+            let mut filter = RibbonFilter {
+                solution_vec: BitstringVec::new(fingerprint_bit_count, slot_count),
+                seed: rng.gen(),
+                is_homogeneous,
+                item_count: hashes.len(),
+                hash_builder,
+                _marker: PhantomData,
+            };
+            if filter.solve(&hashes, &mut rng) {
+                return filter;
+            }
+            hash_builder = filter.hash_builder;
+        }
+    }
+
+    // Solves the system of equations of `hashes` and stores the solution. Returns `false` if the
+    // system is inconsistent, which can only happen for the standard ribbon filter.
+    fn solve(&mut self, hashes: &[u64], rng: &mut XorShiftRng) -> bool {
+        let slot_count = self.solution_vec.len();
+        let mut coefficients = vec![0u64; slot_count];
+        let mut results = vec![0u64; slot_count];
+
+        for hash in hashes {
+            let (mut start, mut coefficient, mut result) = self.get_equation(*hash);
+            loop {
+                if coefficients[start] == 0 {
+                    coefficients[start] = coefficient;
+                    results[start] = result;
+                    break;
+                }
+                coefficient ^= coefficients[start];
+                result ^= results[start];
+                if coefficient == 0 {
+                    if result == 0 {
+                        break;
+                    }
+                    return false;
+                }
+                let shift = coefficient.trailing_zeros();
+                start += shift as usize;
+                coefficient >>= shift;
+            }
+        }
+
+        let mask = (1 << self.fingerprint_bit_count()) - 1;
+        for index in (0..slot_count).rev() {
+            let mut solution = if coefficients[index] == 0 {
+                // Free variables are only randomized in the homogeneous ribbon filter, where the
+                // random choice of solution determines the false positive probability.
+                if self.is_homogeneous {
+                    rng.gen::<u64>() & mask
+                } else {
+                    0
+                }
+            } else {
+                results[index]
+            };
+            let mut coefficient = coefficients[index] >> 1;
+            let mut offset = index + 1;
+            while coefficient != 0 {
+                let shift = coefficient.trailing_zeros() as usize;
+                offset += shift;
+                solution ^= self.solution_vec.get(offset);
+                coefficient >>= shift + 1;
+                offset += 1;
+            }
+            self.solution_vec.set(index, solution);
+        }
+        true
+    }
+
+    /// Constructs a new standard `RibbonFilter` containing the items yielded by `items`, with
+    /// fingerprints of `fingerprint_bit_count` bits and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not in the range `[1, 32]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items_with_hasher(
+    ///     0..100,
+    ///     8,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_items_with_hasher<I>(
+        items: I,
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Hash,
+    {
+        let hashes = items
+            .into_iter()
+            .map(|item| util::hash(&hash_builder, &item))
+            .collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, false, hash_builder)
+    }
+
+    /// Constructs a new homogeneous `RibbonFilter` containing the items yielded by `items`, with
+    /// `fingerprint_bit_count` bits per slot and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not in the range `[1, 32]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items_homogeneous_with_hasher(
+    ///     0..100,
+    ///     8,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_items_homogeneous_with_hasher<I>(
+        items: I,
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Hash,
+    {
+        let hashes = items
+            .into_iter()
+            .map(|item| util::hash(&hash_builder, &item))
+            .collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, true, hash_builder)
+    }
+
+    /// Checks if an element is possibly in the ribbon filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!(filter.contains(&0));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let (start, mut coefficient, mut result) =
+            self.get_equation(util::hash(&self.hash_builder, &item));
+        let mut offset = start;
+        while coefficient != 0 {
+            let shift = coefficient.trailing_zeros() as usize;
+            offset += shift;
+            result ^= self.solution_vec.get(offset);
+            coefficient = (coefficient >> shift) >> 1;
+            offset += 1;
+        }
+        result == 0
+    }
+
+    /// Returns the number of slots in the ribbon filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.len(), 169);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.solution_vec.len()
+    }
+
+    /// Returns `true` if the ribbon filter has no slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.solution_vec.is_empty()
+    }
+
+    /// Returns the number of distinct items that the ribbon filter was constructed from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(vec![1, 2, 2, 3], 8);
+    ///
+    /// assert_eq!(filter.item_count(), 3);
+    /// ```
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    /// Returns the number of bits in each slot of the ribbon filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.solution_vec.bit_count()
+    }
+
+    /// Returns `true` if the ribbon filter is a homogeneous ribbon filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items_homogeneous(0..100, 8);
+    ///
+    /// assert!(filter.is_homogeneous());
+    /// ```
+    pub fn is_homogeneous(&self) -> bool {
+        self.is_homogeneous
+    }
+
+    /// Returns the estimated false positive probability of the ribbon filter, which is
+    /// `2^-fingerprint_bit_count`.
+    ///
+    /// For the homogeneous ribbon filter, this value is a lower bound. A query always succeeds if
+    /// the coefficient row of the item is a combination of the coefficient rows of the items in
+    /// the filter, so the actual false positive probability is measurably higher than
+    /// `2^-fingerprint_bit_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!((filter.estimated_fpp() - 1.0 / 256.0).abs() < std::f64::EPSILON);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        0.5f64.powi(self.fingerprint_bit_count() as i32)
+    }
+
+    /// Returns a reference to the ribbon filter's hasher builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::ribbon::RibbonFilter;
+    ///
+    /// let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+    /// let hasher = filter.hasher();
+    /// ```
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RibbonFilter;
+    use crate::util::tests::hash_builder_1;
+
+    #[test]
+    fn test_from_items() {
+        let filter = RibbonFilter::<u32>::from_items_with_hasher(0..10_000, 8, hash_builder_1());
+
+        assert_eq!(filter.len(), 10_564);
+        assert_eq!(filter.item_count(), 10_000);
+        assert!(!filter.is_homogeneous());
+        for item in 0..10_000 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positive_count = (10_000..110_000)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!((false_positive_count as f64 / 100_000.0) < 1.5 * filter.estimated_fpp());
+    }
+
+    #[test]
+    fn test_from_items_homogeneous() {
+        let filter =
+            RibbonFilter::<u32>::from_items_homogeneous_with_hasher(0..10_000, 8, hash_builder_1());
+
+        assert_eq!(filter.len(), 11_314);
+        assert_eq!(filter.item_count(), 10_000);
+        assert!(filter.is_homogeneous());
+        for item in 0..10_000 {
+            assert!(filter.contains(&item));
+        }
+
+        let false_positive_count = (10_000..110_000)
+            .filter(|item| filter.contains(item))
+            .count();
+        assert!((false_positive_count as f64 / 100_000.0) < 1.5 * filter.estimated_fpp());
+    }
+
+    #[test]
+    fn test_small() {
+        for item_count in 0..100 {
+            let filter =
+                RibbonFilter::<u32>::from_items_with_hasher(0..item_count, 4, hash_builder_1());
+            let homogeneous_filter = RibbonFilter::<u32>::from_items_homogeneous_with_hasher(
+                0..item_count,
+                4,
+                hash_builder_1(),
+            );
+
+            for item in 0..item_count {
+                assert!(filter.contains(&item));
+                assert!(homogeneous_filter.contains(&item));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_bit_count() {
+        for fingerprint_bit_count in 1..=32 {
+            let filter = RibbonFilter::<u32>::from_items_with_hasher(
+                0..1000,
+                fingerprint_bit_count,
+                hash_builder_1(),
+            );
+
+            assert_eq!(filter.fingerprint_bit_count(), fingerprint_bit_count);
+            for item in 0..1000 {
+                assert!(filter.contains(&item));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_fingerprint_bit_count() {
+        RibbonFilter::<u32>::from_items_with_hasher(0..100, 33, hash_builder_1());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let filter = RibbonFilter::<u32>::from_items(0..100, 8);
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: RibbonFilter<u32> = bincode::deserialize(&serialized_filter).unwrap();
+
+        assert_eq!(filter, de_filter);
+    }
+}
@@ -132,6 +132,17 @@ pub fn hash(hash_builder: &impl BuildHasher, item: &impl Hash) -> u64 {
     hasher.finish()
 }
 
+/// The 64-bit finalizer of MurmurHash3, which is a bijection that mixes all bits of its input.
+pub fn fmix64(key: u64) -> u64 {
+    let mut hash = key;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+    hash
+}
+
 #[derive(Clone, Copy)]
 pub struct HashIter {
     a: u64,
@@ -139,6 +150,16 @@ pub struct HashIter {
     c: u64,
 }
 
+impl HashIter {
+    pub fn from_hash(hash: u128) -> Self {
+        HashIter {
+            a: (hash >> 64) as u64,
+            b: hash as u64,
+            c: 0,
+        }
+    }
+}
+
 impl Iterator for HashIter {
     type Item = u64;
 
@@ -154,8 +175,9 @@ impl Iterator for HashIter {
 
 #[cfg(test)]
 pub mod tests {
-    use super::SipHasherBuilder;
+    use super::{hash, SipHasherBuilder};
     use siphasher::sip::SipHasher;
+    use std::hash::Hash;
 
     pub fn hash_builder_1() -> SipHasherBuilder {
         SipHasherBuilder {
@@ -172,4 +194,12 @@ pub mod tests {
             hasher: SipHasher::new_with_keys(1, 1),
         }
     }
+
+    pub fn hash_item<U>(item: &U) -> u128
+    where
+        U: Hash + ?Sized,
+    {
+        u128::from(hash(&hash_builder_1(), &item)) << 64
+            | u128::from(hash(&hash_builder_2(), &item))
+    }
 }
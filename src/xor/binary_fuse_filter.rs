@@ -0,0 +1,525 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::util::hash;
+use crate::xor::{
+    build_fingerprint_vec, contains_fingerprint, get_hash, is_valid_fingerprint_bit_count,
+    read_fingerprint_vec, write_fingerprint_vec,
+};
+use crate::{DecodeError, SipHasherBuilder};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const HEADER_BYTE_COUNT: usize = 17;
+const MAX_SEGMENT_LEN: usize = 1 << 18;
+
+/// A space-efficient probabilistic data structure to test for membership in a static set.
+///
+/// A binary fuse filter is a variant of a xor filter where the three locations of an item are in
+/// three consecutive segments of the fingerprint array instead of three fixed blocks. This makes
+/// the hypergraph formed by the items easier to peel, so a binary fuse filter only uses around
+/// 1.13 times the fingerprint size in bits per item for large sets. Construction may fail, in
+/// which case it is retried with a new seed. The false positive probability is around
+/// `2^-fingerprint_bit_count`. Items cannot be inserted after construction.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::xor::BinaryFuseFilter;
+///
+/// let filter =
+///     BinaryFuseFilter::<String>::from_items(vec!["foo".to_string(), "bar".to_string()], 32);
+///
+/// assert!(filter.contains("foo"));
+/// assert!(filter.contains("bar"));
+/// assert!(!filter.contains("baz"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct BinaryFuseFilter<T, B = SipHasherBuilder> {
+    fingerprint_vec: BitstringVec,
+    segment_len: usize,
+    segment_count: usize,
+    seed: u64,
+    hash_builder: B,
+    _marker: PhantomData<T>,
+}
+
+impl<T> BinaryFuseFilter<T>
+where
+    T: Hash,
+{
+    /// Constructs a new `BinaryFuseFilter` containing the items in `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    /// ```
+    pub fn from_slice(items: &[T], fingerprint_bit_count: usize) -> Self {
+        Self::from_slice_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+
+    /// Constructs a new `BinaryFuseFilter` containing the items yielded by `items`, with
+    /// fingerprints of `fingerprint_bit_count` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 16);
+    /// ```
+    pub fn from_items<I>(items: I, fingerprint_bit_count: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::from_items_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+}
+
+impl<T, B> BinaryFuseFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_segment_len(item_count: usize) -> usize {
+        if item_count == 0 {
+            return 4;
+        }
+        let exponent = ((item_count as f64).ln() / 3.33f64.ln() + 2.25).floor() as u32;
+        cmp::min(1 << exponent, MAX_SEGMENT_LEN)
+    }
+
+    fn get_segment_count(item_count: usize, segment_len: usize) -> usize {
+        if item_count <= 1 {
+            return 1;
+        }
+        let size_factor = f64::max(
+            1.125,
+            0.875 + 0.25 * 1_000_000f64.ln() / (item_count as f64).ln(),
+        );
+        let capacity = (item_count as f64 * size_factor).round() as usize;
+        cmp::max(
+            ((capacity + segment_len - 1) / segment_len).saturating_sub(2),
+            1,
+        )
+    }
+
+    fn get_indexes(hash: u64, segment_len: usize, segment_count: usize) -> [usize; 3] {
+        let segment_len = segment_len as u64;
+        let segment_mask = segment_len - 1;
+        let index =
+            ((u128::from(hash) * u128::from(segment_count as u64 * segment_len)) >> 64) as u64;
+        [
+            index as usize,
+            ((index + segment_len) ^ ((hash >> 18) & segment_mask)) as usize,
+            ((index + 2 * segment_len) ^ (hash & segment_mask)) as usize,
+        ]
+    }
+
+    fn from_hashes(mut hashes: Vec<u64>, fingerprint_bit_count: usize, hash_builder: B) -> Self {
+        assert!(is_valid_fingerprint_bit_count(fingerprint_bit_count));
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let segment_len = Self::get_segment_len(hashes.len());
+        let segment_count = Self::get_segment_count(hashes.len(), segment_len);
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        loop {
+            let seed = rng.gen();
+            let seeded_hashes: Vec<u64> = hashes.iter().map(|hash| get_hash(*hash, seed)).collect();
+            let fingerprint_vec = build_fingerprint_vec(
+                &seeded_hashes,
+                (segment_count + 2) * segment_len,
+                fingerprint_bit_count,
+                |hash| Self::get_indexes(hash, segment_len, segment_count),
+            );
+            if let Some(fingerprint_vec) = fingerprint_vec {
+                return BinaryFuseFilter {
+                    fingerprint_vec,
+                    segment_len,
+                    segment_count,
+                    seed,
+                    hash_builder,
+                    _marker: PhantomData,
+                };
+            }
+        }
+    }
+
+    /// Constructs a new `BinaryFuseFilter` containing the items in `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_slice_with_hasher(
+    ///     &[1, 2, 3],
+    ///     8,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_slice_with_hasher(
+        items: &[T],
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        T: Hash,
+    {
+        let hashes = items.iter().map(|item| hash(&hash_builder, item)).collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, hash_builder)
+    }
+
+    /// Constructs a new `BinaryFuseFilter` containing the items yielded by `items`, with
+    /// fingerprints of `fingerprint_bit_count` bits and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items_with_hasher(
+    ///     0..100,
+    ///     16,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_items_with_hasher<I>(
+        items: I,
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Hash,
+    {
+        let hashes = items
+            .into_iter()
+            .map(|item| hash(&hash_builder, &item))
+            .collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, hash_builder)
+    }
+
+    /// Constructs a `BinaryFuseFilter` from the bytes returned by `to_bytes` and the hasher
+    /// builder used to construct the original filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fingerprint size in the header is not 8, 16, or 32 bits, if the
+    /// segment length in the header is not a power of two that is at most `2^18`, if the segment
+    /// count in the header is 0, or if the number of bytes does not match the header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let de_filter = BinaryFuseFilter::<u32>::from_bytes(&bytes, *filter.hasher()).unwrap();
+    ///
+    /// assert!(de_filter.contains(&1));
+    /// ```
+    pub fn from_bytes(bytes: &[u8], hash_builder: B) -> Result<Self, DecodeError> {
+        if bytes.len() < HEADER_BYTE_COUNT {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let mut seed_bytes = [0; 8];
+        seed_bytes.copy_from_slice(&bytes[..8]);
+        let seed = u64::from_le_bytes(seed_bytes);
+        let fingerprint_bit_count = bytes[8] as usize;
+        let mut segment_bytes = [0; 4];
+        segment_bytes.copy_from_slice(&bytes[9..13]);
+        let segment_len = u32::from_le_bytes(segment_bytes) as usize;
+        segment_bytes.copy_from_slice(&bytes[13..17]);
+        let segment_count = u32::from_le_bytes(segment_bytes) as usize;
+        if !is_valid_fingerprint_bit_count(fingerprint_bit_count)
+            || !segment_len.is_power_of_two()
+            || segment_len > MAX_SEGMENT_LEN
+            || segment_count == 0
+        {
+            return Err(DecodeError::InvalidHeader);
+        }
+
+        let fingerprint_bytes = &bytes[HEADER_BYTE_COUNT..];
+        let fingerprint_byte_count = fingerprint_bit_count / 8;
+        if fingerprint_bytes.len() != (segment_count + 2) * segment_len * fingerprint_byte_count {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        Ok(BinaryFuseFilter {
+            fingerprint_vec: read_fingerprint_vec(fingerprint_bytes, fingerprint_bit_count),
+            segment_len,
+            segment_count,
+            seed,
+            hash_builder,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Checks if an element is possibly in the binary fuse filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    ///
+    /// assert!(filter.contains(&1));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = get_hash(hash(&self.hash_builder, &item), self.seed);
+        contains_fingerprint(
+            &self.fingerprint_vec,
+            hash,
+            Self::get_indexes(hash, self.segment_len, self.segment_count),
+        )
+    }
+
+    /// Returns the byte representation of the binary fuse filter. The bytes consist of the seed,
+    /// the fingerprint size, the segment length, and the segment count, followed by the
+    /// fingerprints in little-endian order. The hasher builder is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 16);
+    ///
+    /// assert_eq!(filter.to_bytes().len(), 17 + 2 * filter.len());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            HEADER_BYTE_COUNT + self.fingerprint_vec.len() * self.fingerprint_bit_count() / 8,
+        );
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.fingerprint_bit_count() as u8);
+        bytes.extend_from_slice(&(self.segment_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.segment_count as u32).to_le_bytes());
+        write_fingerprint_vec(&self.fingerprint_vec, &mut bytes);
+        bytes
+    }
+
+    /// Returns the number of fingerprints in the binary fuse filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.len(), 192);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.fingerprint_vec.len()
+    }
+
+    /// Returns `true` if the binary fuse filter has no fingerprints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.fingerprint_vec.is_empty()
+    }
+
+    /// Returns the number of bits in each fingerprint of the binary fuse filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_vec.bit_count()
+    }
+
+    /// Returns the estimated false positive probability of the binary fuse filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!((filter.estimated_fpp() - 1.0 / 256.0).abs() < std::f64::EPSILON);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        0.5f64.powi(self.fingerprint_bit_count() as i32)
+    }
+
+    /// Returns a reference to the binary fuse filter's hasher builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::BinaryFuseFilter;
+    ///
+    /// let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+    /// let hasher = filter.hasher();
+    /// ```
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryFuseFilter;
+    use crate::util::tests::hash_builder_1;
+    use crate::DecodeError;
+
+    #[test]
+    fn test_from_items() {
+        for &fingerprint_bit_count in &[8, 16, 32] {
+            let filter = BinaryFuseFilter::<u32>::from_items_with_hasher(
+                0..10_000,
+                fingerprint_bit_count,
+                hash_builder_1(),
+            );
+
+            assert_eq!(filter.len(), 12800);
+            assert_eq!(filter.fingerprint_bit_count(), fingerprint_bit_count);
+            for item in 0..10_000 {
+                assert!(filter.contains(&item));
+            }
+
+            let false_positive_count = (10_000..110_000)
+                .filter(|item| filter.contains(item))
+                .count();
+            assert!((false_positive_count as f64 / 100_000.0) < 2.0 * filter.estimated_fpp());
+        }
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let items = vec!["foo".to_string(), "bar".to_string(), "foo".to_string()];
+        let filter =
+            BinaryFuseFilter::<String>::from_slice_with_hasher(&items, 8, hash_builder_1());
+
+        assert!(filter.contains("foo"));
+        assert!(filter.contains("bar"));
+        assert!(!filter.contains("baz"));
+    }
+
+    #[test]
+    fn test_small() {
+        for item_count in 0..100 {
+            let filter =
+                BinaryFuseFilter::<u32>::from_items_with_hasher(0..item_count, 8, hash_builder_1());
+
+            for item in 0..item_count {
+                assert!(filter.contains(&item));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_fingerprint_bit_count() {
+        BinaryFuseFilter::<u32>::from_items_with_hasher(0..100, 4, hash_builder_1());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let filter = BinaryFuseFilter::<u32>::from_items_with_hasher(0..100, 16, hash_builder_1());
+        let bytes = filter.to_bytes();
+
+        let de_filter = BinaryFuseFilter::<u32>::from_bytes(&bytes, hash_builder_1()).unwrap();
+        assert_eq!(filter, de_filter);
+
+        assert_eq!(
+            BinaryFuseFilter::<u32>::from_bytes(&bytes[..16], hash_builder_1()),
+            Err(DecodeError::InvalidLength),
+        );
+        assert_eq!(
+            BinaryFuseFilter::<u32>::from_bytes(&bytes[..bytes.len() - 2], hash_builder_1()),
+            Err(DecodeError::InvalidLength),
+        );
+
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[8] = 12;
+        assert_eq!(
+            BinaryFuseFilter::<u32>::from_bytes(&invalid_bytes, hash_builder_1()),
+            Err(DecodeError::InvalidHeader),
+        );
+
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[9] = 3;
+        assert_eq!(
+            BinaryFuseFilter::<u32>::from_bytes(&invalid_bytes, hash_builder_1()),
+            Err(DecodeError::InvalidHeader),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let filter = BinaryFuseFilter::<u32>::from_items(0..100, 8);
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: BinaryFuseFilter<u32> = bincode::deserialize(&serialized_filter).unwrap();
+
+        assert_eq!(filter, de_filter);
+    }
+}
@@ -0,0 +1,103 @@
+//! Space-efficient probabilistic data structures for approximate membership queries in a static
+//! set.
+
+mod binary_fuse_filter;
+mod xor_filter;
+
+pub use self::binary_fuse_filter::BinaryFuseFilter;
+pub use self::xor_filter::XorFilter;
+
+use crate::bitstring_vec::BitstringVec;
+use crate::util::fmix64;
+
+fn is_valid_fingerprint_bit_count(fingerprint_bit_count: usize) -> bool {
+    fingerprint_bit_count == 8 || fingerprint_bit_count == 16 || fingerprint_bit_count == 32
+}
+
+fn get_hash(hash: u64, seed: u64) -> u64 {
+    fmix64(hash.wrapping_add(seed))
+}
+
+fn get_fingerprint(hash: u64, fingerprint_bit_count: usize) -> u64 {
+    (hash ^ (hash >> 32)) & ((1 << fingerprint_bit_count) - 1)
+}
+
+// Assigns fingerprints such that the XOR of the fingerprints at the three indexes of each hash is
+// the fingerprint of the hash. The hashes are peeled by repeatedly removing a hash from an index
+// that only one hash maps to, and the fingerprints are then assigned in the reverse order. Returns
+// `None` if the hashes could not be completely peeled.
+fn build_fingerprint_vec<F>(
+    hashes: &[u64],
+    len: usize,
+    fingerprint_bit_count: usize,
+    get_indexes: F,
+) -> Option<BitstringVec>
+where
+    F: Fn(u64) -> [usize; 3],
+{
+    let mut counts = vec![0u32; len];
+    let mut hash_xors = vec![0u64; len];
+    for hash in hashes {
+        for index in &get_indexes(*hash) {
+            counts[*index] += 1;
+            hash_xors[*index] ^= hash;
+        }
+    }
+
+    let mut queue: Vec<usize> = (0..len).filter(|index| counts[*index] == 1).collect();
+    let mut stack = Vec::with_capacity(hashes.len());
+    while let Some(index) = queue.pop() {
+        if counts[index] != 1 {
+            continue;
+        }
+        let hash = hash_xors[index];
+        stack.push((hash, index));
+        for other_index in &get_indexes(hash) {
+            counts[*other_index] -= 1;
+            hash_xors[*other_index] ^= hash;
+            if counts[*other_index] == 1 {
+                queue.push(*other_index);
+            }
+        }
+    }
+
+    if stack.len() != hashes.len() {
+        return None;
+    }
+
+    let mut fingerprint_vec = BitstringVec::new(fingerprint_bit_count, len);
+    for (hash, index) in stack.into_iter().rev() {
+        let fingerprint = get_indexes(hash).iter().fold(
+            get_fingerprint(hash, fingerprint_bit_count),
+            |fingerprint, index| fingerprint ^ fingerprint_vec.get(*index),
+        );
+        fingerprint_vec.set(index, fingerprint);
+    }
+    Some(fingerprint_vec)
+}
+
+fn contains_fingerprint(fingerprint_vec: &BitstringVec, hash: u64, indexes: [usize; 3]) -> bool {
+    let fingerprint = get_fingerprint(hash, fingerprint_vec.bit_count());
+    indexes.iter().fold(fingerprint, |fingerprint, index| {
+        fingerprint ^ fingerprint_vec.get(*index)
+    }) == 0
+}
+
+fn write_fingerprint_vec(fingerprint_vec: &BitstringVec, bytes: &mut Vec<u8>) {
+    let fingerprint_byte_count = fingerprint_vec.bit_count() / 8;
+    for fingerprint in fingerprint_vec {
+        bytes.extend_from_slice(&fingerprint.to_le_bytes()[..fingerprint_byte_count]);
+    }
+}
+
+fn read_fingerprint_vec(bytes: &[u8], fingerprint_bit_count: usize) -> BitstringVec {
+    let fingerprint_byte_count = fingerprint_bit_count / 8;
+    let mut fingerprint_vec =
+        BitstringVec::new(fingerprint_bit_count, bytes.len() / fingerprint_byte_count);
+    for (index, fingerprint_bytes) in bytes.chunks(fingerprint_byte_count).enumerate() {
+        let mut buffer = [0; 8];
+        buffer[..fingerprint_byte_count].copy_from_slice(fingerprint_bytes);
+        fingerprint_vec.set(index, u64::from_le_bytes(buffer));
+    }
+    fingerprint_vec
+}
@@ -0,0 +1,479 @@
+use crate::bitstring_vec::BitstringVec;
+use crate::util::hash;
+use crate::xor::{
+    build_fingerprint_vec, contains_fingerprint, get_hash, is_valid_fingerprint_bit_count,
+    read_fingerprint_vec, write_fingerprint_vec,
+};
+use crate::{DecodeError, SipHasherBuilder};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+const HEADER_BYTE_COUNT: usize = 9;
+
+/// A space-efficient probabilistic data structure to test for membership in a static set.
+///
+/// A xor filter stores a fingerprint for each item such that the XOR of the values at three
+/// locations determined by the item's hash is equal to the item's fingerprint. The locations are
+/// in three separate blocks, and the values are assigned by peeling the hypergraph formed by the
+/// items. Construction may fail, in which case it is retried with a new seed. A xor filter uses
+/// around 1.23 times the fingerprint size in bits per item, and its false positive probability is
+/// around `2^-fingerprint_bit_count`. Items cannot be inserted after construction.
+///
+/// # Examples
+///
+/// ```
+/// use probabilistic_collections::xor::XorFilter;
+///
+/// let filter = XorFilter::<String>::from_items(vec!["foo".to_string(), "bar".to_string()], 32);
+///
+/// assert!(filter.contains("foo"));
+/// assert!(filter.contains("bar"));
+/// assert!(!filter.contains("baz"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(crate = "serde_crate")
+)]
+pub struct XorFilter<T, B = SipHasherBuilder> {
+    fingerprint_vec: BitstringVec,
+    block_len: usize,
+    seed: u64,
+    hash_builder: B,
+    _marker: PhantomData<T>,
+}
+
+impl<T> XorFilter<T>
+where
+    T: Hash,
+{
+    /// Constructs a new `XorFilter` containing the items in `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    /// ```
+    pub fn from_slice(items: &[T], fingerprint_bit_count: usize) -> Self {
+        Self::from_slice_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+
+    /// Constructs a new `XorFilter` containing the items yielded by `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 16);
+    /// ```
+    pub fn from_items<I>(items: I, fingerprint_bit_count: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::from_items_with_hasher(
+            items,
+            fingerprint_bit_count,
+            SipHasherBuilder::from_entropy(),
+        )
+    }
+}
+
+impl<T, B> XorFilter<T, B>
+where
+    B: BuildHasher,
+{
+    fn get_indexes(hash: u64, block_len: usize) -> [usize; 3] {
+        let reduce = |hash: u64| ((hash as u32 as u64 * block_len as u64) >> 32) as usize;
+        [
+            reduce(hash),
+            reduce(hash.rotate_left(21)) + block_len,
+            reduce(hash.rotate_left(42)) + 2 * block_len,
+        ]
+    }
+
+    fn from_hashes(mut hashes: Vec<u64>, fingerprint_bit_count: usize, hash_builder: B) -> Self {
+        assert!(is_valid_fingerprint_bit_count(fingerprint_bit_count));
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let block_len = (32 + (1.23 * hashes.len() as f64).ceil() as usize) / 3;
+        let mut rng = XorShiftRng::seed_from_u64(0);
+        loop {
+            let seed = rng.gen();
+            let seeded_hashes: Vec<u64> = hashes.iter().map(|hash| get_hash(*hash, seed)).collect();
+            let fingerprint_vec = build_fingerprint_vec(
+                &seeded_hashes,
+                3 * block_len,
+                fingerprint_bit_count,
+                |hash| Self::get_indexes(hash, block_len),
+            );
+            if let Some(fingerprint_vec) = fingerprint_vec {
+                return XorFilter {
+                    fingerprint_vec,
+                    block_len,
+                    seed,
+                    hash_builder,
+                    _marker: PhantomData,
+                };
+            }
+        }
+    }
+
+    /// Constructs a new `XorFilter` containing the items in `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = XorFilter::<u32>::from_slice_with_hasher(
+    ///     &[1, 2, 3],
+    ///     8,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_slice_with_hasher(
+        items: &[T],
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        T: Hash,
+    {
+        let hashes = items.iter().map(|item| hash(&hash_builder, item)).collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, hash_builder)
+    }
+
+    /// Constructs a new `XorFilter` containing the items yielded by `items`, with fingerprints of
+    /// `fingerprint_bit_count` bits and a specified hasher builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fingerprint_bit_count` is not 8, 16, or 32.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    /// use probabilistic_collections::SipHasherBuilder;
+    ///
+    /// let filter = XorFilter::<u32>::from_items_with_hasher(
+    ///     0..100,
+    ///     16,
+    ///     SipHasherBuilder::from_seed(0, 0),
+    /// );
+    /// ```
+    pub fn from_items_with_hasher<I>(
+        items: I,
+        fingerprint_bit_count: usize,
+        hash_builder: B,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Hash,
+    {
+        let hashes = items
+            .into_iter()
+            .map(|item| hash(&hash_builder, &item))
+            .collect();
+        Self::from_hashes(hashes, fingerprint_bit_count, hash_builder)
+    }
+
+    /// Constructs a `XorFilter` from the bytes returned by `to_bytes` and the hasher builder used
+    /// to construct the original filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fingerprint size in the header is not 8, 16, or 32 bits, or if the
+    /// number of bytes does not correspond to a number of fingerprints that is a nonzero multiple
+    /// of 3.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    ///
+    /// let bytes = filter.to_bytes();
+    /// let de_filter = XorFilter::<u32>::from_bytes(&bytes, *filter.hasher()).unwrap();
+    ///
+    /// assert!(de_filter.contains(&1));
+    /// ```
+    pub fn from_bytes(bytes: &[u8], hash_builder: B) -> Result<Self, DecodeError> {
+        if bytes.len() < HEADER_BYTE_COUNT {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let mut seed_bytes = [0; 8];
+        seed_bytes.copy_from_slice(&bytes[..8]);
+        let seed = u64::from_le_bytes(seed_bytes);
+        let fingerprint_bit_count = bytes[8] as usize;
+        if !is_valid_fingerprint_bit_count(fingerprint_bit_count) {
+            return Err(DecodeError::InvalidHeader);
+        }
+
+        let fingerprint_bytes = &bytes[HEADER_BYTE_COUNT..];
+        let fingerprint_byte_count = fingerprint_bit_count / 8;
+        if fingerprint_bytes.is_empty()
+            || fingerprint_bytes.len() % (3 * fingerprint_byte_count) != 0
+        {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        let fingerprint_vec = read_fingerprint_vec(fingerprint_bytes, fingerprint_bit_count);
+        Ok(XorFilter {
+            block_len: fingerprint_vec.len() / 3,
+            fingerprint_vec,
+            seed,
+            hash_builder,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Checks if an element is possibly in the xor filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_slice(&[1, 2, 3], 8);
+    ///
+    /// assert!(filter.contains(&1));
+    /// ```
+    pub fn contains<U>(&self, item: &U) -> bool
+    where
+        T: Borrow<U>,
+        U: Hash + ?Sized,
+    {
+        let hash = get_hash(hash(&self.hash_builder, &item), self.seed);
+        contains_fingerprint(
+            &self.fingerprint_vec,
+            hash,
+            Self::get_indexes(hash, self.block_len),
+        )
+    }
+
+    /// Returns the byte representation of the xor filter. The bytes consist of the seed and the
+    /// fingerprint size, followed by the fingerprints in little-endian order. The hasher builder
+    /// is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 16);
+    ///
+    /// assert_eq!(filter.to_bytes().len(), 9 + 2 * filter.len());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            HEADER_BYTE_COUNT + self.fingerprint_vec.len() * self.fingerprint_bit_count() / 8,
+        );
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(self.fingerprint_bit_count() as u8);
+        write_fingerprint_vec(&self.fingerprint_vec, &mut bytes);
+        bytes
+    }
+
+    /// Returns the number of fingerprints in the xor filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.len(), 153);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.fingerprint_vec.len()
+    }
+
+    /// Returns `true` if the xor filter has no fingerprints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!(!filter.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.fingerprint_vec.is_empty()
+    }
+
+    /// Returns the number of bits in each fingerprint of the xor filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert_eq!(filter.fingerprint_bit_count(), 8);
+    /// ```
+    pub fn fingerprint_bit_count(&self) -> usize {
+        self.fingerprint_vec.bit_count()
+    }
+
+    /// Returns the estimated false positive probability of the xor filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 8);
+    ///
+    /// assert!((filter.estimated_fpp() - 1.0 / 256.0).abs() < std::f64::EPSILON);
+    /// ```
+    pub fn estimated_fpp(&self) -> f64 {
+        0.5f64.powi(self.fingerprint_bit_count() as i32)
+    }
+
+    /// Returns a reference to the xor filter's hasher builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use probabilistic_collections::xor::XorFilter;
+    ///
+    /// let filter = XorFilter::<u32>::from_items(0..100, 8);
+    /// let hasher = filter.hasher();
+    /// ```
+    pub fn hasher(&self) -> &B {
+        &self.hash_builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XorFilter;
+    use crate::util::tests::hash_builder_1;
+    use crate::DecodeError;
+
+    #[test]
+    fn test_from_items() {
+        for &fingerprint_bit_count in &[8, 16, 32] {
+            let filter = XorFilter::<u32>::from_items_with_hasher(
+                0..10_000,
+                fingerprint_bit_count,
+                hash_builder_1(),
+            );
+
+            assert_eq!(filter.len(), 12330);
+            assert_eq!(filter.fingerprint_bit_count(), fingerprint_bit_count);
+            for item in 0..10_000 {
+                assert!(filter.contains(&item));
+            }
+
+            let false_positive_count = (10_000..110_000)
+                .filter(|item| filter.contains(item))
+                .count();
+            assert!((false_positive_count as f64 / 100_000.0) < 2.0 * filter.estimated_fpp());
+        }
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let items = vec!["foo".to_string(), "bar".to_string(), "foo".to_string()];
+        let filter = XorFilter::<String>::from_slice_with_hasher(&items, 8, hash_builder_1());
+
+        assert!(filter.contains("foo"));
+        assert!(filter.contains("bar"));
+        assert!(!filter.contains("baz"));
+    }
+
+    #[test]
+    fn test_small() {
+        let filter = XorFilter::<u32>::from_items_with_hasher(0..0, 8, hash_builder_1());
+        assert_eq!(filter.len(), 30);
+
+        for item_count in 1..100 {
+            let filter =
+                XorFilter::<u32>::from_items_with_hasher(0..item_count, 8, hash_builder_1());
+
+            for item in 0..item_count {
+                assert!(filter.contains(&item));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_fingerprint_bit_count() {
+        XorFilter::<u32>::from_items_with_hasher(0..100, 4, hash_builder_1());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let filter = XorFilter::<u32>::from_items_with_hasher(0..100, 16, hash_builder_1());
+        let bytes = filter.to_bytes();
+
+        let de_filter = XorFilter::<u32>::from_bytes(&bytes, hash_builder_1()).unwrap();
+        assert_eq!(filter, de_filter);
+
+        assert_eq!(
+            XorFilter::<u32>::from_bytes(&bytes[..8], hash_builder_1()),
+            Err(DecodeError::InvalidLength),
+        );
+        assert_eq!(
+            XorFilter::<u32>::from_bytes(&bytes[..bytes.len() - 2], hash_builder_1()),
+            Err(DecodeError::InvalidLength),
+        );
+
+        let mut invalid_bytes = bytes.clone();
+        invalid_bytes[8] = 12;
+        assert_eq!(
+            XorFilter::<u32>::from_bytes(&invalid_bytes, hash_builder_1()),
+            Err(DecodeError::InvalidHeader),
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ser_de() {
+        let filter = XorFilter::<u32>::from_items(0..100, 8);
+
+        let serialized_filter = bincode::serialize(&filter).unwrap();
+        let de_filter: XorFilter<u32> = bincode::deserialize(&serialized_filter).unwrap();
+
+        assert_eq!(filter, de_filter);
+    }
+}